@@ -0,0 +1,6 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tonic_build::configure()
+        .build_client(false)
+        .compile(&["proto/rusty_accounts/v1/accounts.proto"], &["proto"])?;
+    Ok(())
+}
@@ -0,0 +1,14 @@
+CREATE TYPE transaction_kind AS ENUM ('deposit', 'withdrawal', 'hold_capture', 'reversal');
+
+CREATE TABLE
+  IF NOT EXISTS account_transaction (
+    account_id uuid NOT NULL REFERENCES account (id),
+    seq_no bigint NOT NULL,
+    kind transaction_kind NOT NULL,
+    amount bigint NOT NULL,
+    balance bigint NOT NULL,
+    at timestamptz NOT NULL,
+    reverses bigint,
+    reversed_by bigint,
+    PRIMARY KEY (account_id, seq_no)
+  );
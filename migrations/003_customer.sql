@@ -0,0 +1,18 @@
+CREATE TYPE kyc_status AS ENUM ('pending', 'verified', 'rejected');
+
+CREATE TABLE
+  IF NOT EXISTS customer (
+    id uuid PRIMARY KEY,
+    name text NOT NULL,
+    email text NOT NULL,
+    kyc_status kyc_status NOT NULL
+  );
+
+CREATE TABLE
+  IF NOT EXISTS account_holder (
+    account_id uuid NOT NULL REFERENCES account (id),
+    customer_id uuid NOT NULL,
+    PRIMARY KEY (account_id, customer_id)
+  );
+
+CREATE INDEX IF NOT EXISTS account_holder_customer_id ON account_holder (customer_id);
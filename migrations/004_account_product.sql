@@ -0,0 +1,6 @@
+ALTER TABLE account
+ADD COLUMN IF NOT EXISTS product text NOT NULL DEFAULT 'checking';
+
+ALTER TABLE account
+ALTER COLUMN product
+DROP DEFAULT;
@@ -0,0 +1 @@
+ALTER TYPE transaction_kind ADD VALUE IF NOT EXISTS 'interest';
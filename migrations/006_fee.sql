@@ -0,0 +1,6 @@
+ALTER TYPE transaction_kind ADD VALUE IF NOT EXISTS 'fee';
+
+ALTER TYPE transaction_kind ADD VALUE IF NOT EXISTS 'fee_waiver';
+
+ALTER TABLE account_transaction
+ADD COLUMN IF NOT EXISTS fee bigint NOT NULL DEFAULT 0;
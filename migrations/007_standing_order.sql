@@ -0,0 +1,22 @@
+CREATE TYPE standing_order_status AS ENUM ('active', 'cancelled');
+
+CREATE TABLE
+  IF NOT EXISTS standing_order (
+    id uuid PRIMARY KEY,
+    account_id uuid NOT NULL REFERENCES account (id),
+    payee_id uuid NOT NULL,
+    amount bigint NOT NULL,
+    day_of_month smallint NOT NULL,
+    next_due date NOT NULL,
+    status standing_order_status NOT NULL,
+    failed_attempts integer NOT NULL,
+    last_failure_due date,
+    last_failure_reason text,
+    last_failure_at timestamptz
+  );
+
+CREATE INDEX IF NOT EXISTS standing_order_account_id ON standing_order (account_id);
+
+CREATE INDEX IF NOT EXISTS standing_order_next_due ON standing_order (next_due)
+WHERE
+  status = 'active';
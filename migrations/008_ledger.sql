@@ -0,0 +1,17 @@
+CREATE TYPE ledger_account AS ENUM (
+  'cash',
+  'customer_liabilities',
+  'fee_income',
+  'interest_expense'
+);
+
+CREATE TABLE
+  IF NOT EXISTS ledger_posting (
+    account_id uuid NOT NULL,
+    seq_no bigint NOT NULL,
+    ledger_account ledger_account NOT NULL,
+    debit bigint NOT NULL,
+    credit bigint NOT NULL,
+    at timestamptz NOT NULL,
+    PRIMARY KEY (account_id, seq_no, ledger_account)
+  );
@@ -0,0 +1,12 @@
+CREATE TYPE api_key_scope AS ENUM ('teller', 'admin');
+
+CREATE TABLE
+  IF NOT EXISTS api_key (
+    id uuid PRIMARY KEY,
+    name text NOT NULL,
+    hash bytea NOT NULL,
+    scopes api_key_scope[] NOT NULL,
+    created_at timestamptz NOT NULL,
+    rotated_at timestamptz,
+    revoked_at timestamptz
+  );
@@ -0,0 +1,28 @@
+CREATE TABLE
+  IF NOT EXISTS webhook_subscription (
+    id uuid PRIMARY KEY,
+    url text NOT NULL,
+    secret text NOT NULL,
+    event_types text[] NOT NULL,
+    created_at timestamptz NOT NULL
+  );
+
+CREATE TYPE webhook_delivery_status AS ENUM ('pending', 'delivered', 'dead');
+
+CREATE TABLE
+  IF NOT EXISTS webhook_delivery (
+    id uuid PRIMARY KEY,
+    subscription_id uuid NOT NULL REFERENCES webhook_subscription (id) ON DELETE CASCADE,
+    event_type text NOT NULL,
+    payload text NOT NULL,
+    status webhook_delivery_status NOT NULL,
+    attempts integer NOT NULL,
+    last_error text,
+    next_attempt_at timestamptz NOT NULL,
+    created_at timestamptz NOT NULL,
+    delivered_at timestamptz
+  );
+
+CREATE INDEX IF NOT EXISTS webhook_delivery_pending_idx ON webhook_delivery (next_attempt_at)
+WHERE
+  status = 'pending';
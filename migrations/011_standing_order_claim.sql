@@ -0,0 +1,2 @@
+ALTER TABLE standing_order
+ADD COLUMN IF NOT EXISTS claimed date;
@@ -0,0 +1,2 @@
+ALTER TABLE standing_order
+DROP CONSTRAINT IF EXISTS standing_order_account_id_fkey;
@@ -1,27 +1,54 @@
+mod api_key;
+mod auth;
+mod authorization;
+mod cors;
+mod deprecation;
+mod graphql;
+mod grpc;
+mod limits;
+mod rate_limit;
+mod sse;
+mod tls;
 mod v0;
+mod v1;
+mod ws;
 
-use crate::domain::AccountRepository;
+use crate::domain::{
+    AccountRepository, ApiKeyRepository, CustomerRepository, ProductCatalog,
+    StandingOrderRepository, WebhookRepository,
+};
 use anyhow::{Context, Result};
 use api_version::api_version;
 use axum::{
     body::Body,
-    http::{HeaderMap, Request, StatusCode, Uri},
+    error_handling::HandleErrorLayer,
+    extract::{DefaultBodyLimit, Extension},
+    http::{header, HeaderMap, Request, StatusCode, Uri},
+    middleware::{from_fn_with_state, map_response_with_state},
     routing::get,
     Router, ServiceExt,
 };
+use axum_server::Handle;
 use eventsourced::event_log::EventLog;
 use opentelemetry::{global, propagation::Extractor, trace::TraceContextExt};
 use serde::Deserialize;
-use std::{convert::Infallible, net::IpAddr};
+use std::{
+    convert::Infallible,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
 use tokio::{
     net::TcpListener,
     signal::unix::{signal, SignalKind},
 };
-use tower::{Layer, ServiceBuilder};
-use tower_http::{cors::CorsLayer, trace::TraceLayer};
+use tower::{limit::GlobalConcurrencyLimitLayer, Layer, ServiceBuilder};
+use tower_http::{limit::RequestBodyLimitLayer, trace::TraceLayer};
 use tracing::{field, info_span, warn, Span};
 use tracing_opentelemetry::OpenTelemetrySpanExt;
-use utoipa::OpenApi;
+use utoipa::{
+    openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme},
+    Modify, OpenApi,
+};
 use utoipa_swagger_ui::SwaggerUi;
 use uuid::Uuid;
 
@@ -30,54 +57,208 @@ use uuid::Uuid;
 pub struct Config {
     addr: IpAddr,
     port: u16,
+    auth: auth::Config,
+    cors: cors::Config,
+    limits: limits::Config,
+    rate_limit: rate_limit::Config,
+
+    /// The gRPC API is served on its own port.
+    grpc: grpc::Config,
+
+    /// Deprecation of v0, which is superseded by v1.
+    #[serde(default)]
+    v0_deprecation: deprecation::Config,
+
+    /// If given, TLS is terminated by this service.
+    tls: Option<tls::Config>,
 }
 
 #[derive(Debug, OpenApi)]
-#[openapi()]
+#[openapi(
+    modifiers(&SecurityAddon),
+    security(("bearer_auth" = []), ("api_key" = []))
+)]
 pub struct ApiDoc;
 
-pub async fn serve<R, E>(config: Config, account_repository: R, event_log: E) -> Result<()>
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-Api-Key"))),
+        );
+    }
+}
+
+pub async fn serve<R, C, S, K, W, E>(
+    config: Config,
+    products: ProductCatalog,
+    account_repository: R,
+    customer_repository: C,
+    standing_order_repository: S,
+    api_key_repository: K,
+    webhook_repository: W,
+    event_log: E,
+) -> Result<()>
 where
     R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
     E: EventLog<Id = Uuid> + Sync,
 {
-    let Config { addr, port } = config;
+    let Config {
+        addr,
+        port,
+        auth,
+        cors,
+        limits,
+        rate_limit,
+        grpc: grpc_config,
+        v0_deprecation,
+        tls,
+    } = config;
+
+    let authenticator = auth::Authenticator::new(auth).context("create Authenticator")?;
+    let authenticator = Arc::new(authenticator);
+
+    let cors = cors.layer().context("create CorsLayer")?;
+
+    let rate_limiter = rate_limit::RateLimiter::new(rate_limit);
+
+    let v0_deprecation = v0_deprecation
+        .headers()
+        .context("create v0 deprecation headers")?;
 
     let app_state = AppState {
+        products: Arc::new(products),
         account_repository,
+        customer_repository,
+        standing_order_repository,
+        api_key_repository: api_key_repository.clone(),
+        webhook_repository,
         event_log,
+        rate_limiter: rate_limiter.clone(),
     };
 
-    let mut api_doc = ApiDoc::openapi();
-    api_doc.merge(v0::ApiDoc::openapi());
+    let mut v0_api_doc = ApiDoc::openapi();
+    v0_api_doc.merge(v0::ApiDoc::openapi());
+
+    // v1 shares most paths with v0; merging overrides the superseded ones.
+    let mut v1_api_doc = v0_api_doc.clone();
+    v1_api_doc.merge(v1::ApiDoc::openapi());
 
     let app = Router::new()
         .route("/", get(ready))
-        .nest("/v0", v0::app())
-        .merge(SwaggerUi::new("/api-doc").url("/openapi.json", api_doc))
-        .with_state(app_state)
+        .nest(
+            "/v0",
+            v0::app()
+                .layer(from_fn_with_state(
+                    rate_limiter.clone(),
+                    rate_limit::rate_limit,
+                ))
+                .layer(from_fn_with_state(
+                    authenticator.clone(),
+                    auth::authenticate,
+                ))
+                .layer(map_response_with_state(
+                    v0_deprecation,
+                    deprecation::add_headers,
+                )),
+        )
+        .nest(
+            "/v1",
+            v1::app()
+                .layer(from_fn_with_state(rate_limiter, rate_limit::rate_limit))
+                .layer(from_fn_with_state(authenticator, auth::authenticate)),
+        )
+        .merge(
+            SwaggerUi::new("/api-doc")
+                .url("/openapi.json", v0_api_doc)
+                .url("/openapi-v1.json", v1_api_doc),
+        )
+        .layer(Extension(graphql::schema(app_state.clone())))
+        .with_state(app_state.clone())
+        .layer(DefaultBodyLimit::disable())
         .layer(
             ServiceBuilder::new()
                 .layer(TraceLayer::new_for_http().make_span_with(make_span))
-                .layer(CorsLayer::permissive())
+                .layer(HandleErrorLayer::new(limits::handle_error))
+                .load_shed()
+                .layer(GlobalConcurrencyLimitLayer::new(
+                    limits.max_concurrent_requests,
+                ))
+                .timeout(limits.request_timeout())
+                .layer(RequestBodyLimitLayer::new(limits.max_body_bytes))
+                .layer(cors)
                 .map_request(accept_trace)
-                .map_request(record_trace_id),
+                .map_request(record_trace_id)
+                .layer(from_fn_with_state(
+                    api_key_repository,
+                    api_key::authenticate::<K>,
+                )),
         );
-    let app = api_version!(0..=0, ApiVersionFilter).layer(app);
-
-    let listener = TcpListener::bind((addr, port))
-        .await
-        .context("bind TcpListener")?;
-    axum::serve(listener, app.into_make_service())
-        .with_graceful_shutdown(shutdown_signal())
-        .await
-        .context("run server")
+    let app = api_version!(0..=1, ApiVersionFilter).layer(app);
+
+    let rest = async {
+        match tls {
+            None => {
+                let listener = TcpListener::bind((addr, port))
+                    .await
+                    .context("bind TcpListener")?;
+                axum::serve(listener, app.into_make_service())
+                    .with_graceful_shutdown(shutdown_signal())
+                    .await
+                    .context("run server")
+            }
+
+            Some(tls) => {
+                let rustls_config = tls.rustls_config().context("create TLS configuration")?;
+                tokio::spawn(tls.reload(rustls_config.clone()));
+
+                let handle = Handle::new();
+                let shutdown_handle = handle.clone();
+                tokio::spawn(async move {
+                    shutdown_signal().await;
+                    shutdown_handle.graceful_shutdown(None);
+                });
+
+                axum_server::bind_rustls(SocketAddr::from((addr, port)), rustls_config)
+                    .handle(handle)
+                    .serve(app.into_make_service())
+                    .await
+                    .context("run server")
+            }
+        }
+    };
+
+    let grpc = grpc::serve(grpc_config, addr, app_state, shutdown_signal());
+
+    tokio::try_join!(rest, grpc).map(|_| ())
 }
 
 #[derive(Clone)]
-struct AppState<R, E> {
+struct AppState<R, C, S, K, W, E> {
+    products: Arc<ProductCatalog>,
     account_repository: R,
+    customer_repository: C,
+    standing_order_repository: S,
+    api_key_repository: K,
+    webhook_repository: W,
     event_log: E,
+    rate_limiter: rate_limit::RateLimiter,
 }
 
 #[derive(Clone)]
@@ -88,7 +269,8 @@ impl api_version::ApiVersionFilter for ApiVersionFilter {
 
     async fn filter(&self, uri: &Uri) -> Result<bool, Self::Error> {
         let path = uri.path();
-        let no_rewrite = (path == "/") || path.starts_with("/api-doc") || path == "/openapi.json";
+        let no_rewrite =
+            (path == "/") || path.starts_with("/api-doc") || path.starts_with("/openapi");
         Ok(!no_rewrite)
     }
 }
@@ -105,9 +287,23 @@ async fn shutdown_signal() {
 }
 
 fn make_span(request: &Request<Body>) -> Span {
-    let headers = request.headers();
+    // Do not log bearer tokens and API keys.
+    let mut headers = request.headers().clone();
+    if let Some(authorization) = headers.get_mut(header::AUTHORIZATION) {
+        authorization.set_sensitive(true);
+    }
+    if let Some(api_key) = headers.get_mut(api_key::X_API_KEY) {
+        api_key.set_sensitive(true);
+    }
     let path = request.uri().path();
-    info_span!("incoming request", path, ?headers, trace_id = field::Empty)
+    info_span!(
+        "incoming request",
+        path,
+        ?headers,
+        trace_id = field::Empty,
+        subject = field::Empty,
+        api_key_id = field::Empty
+    )
 }
 
 struct HeaderExtractor<'a>(&'a HeaderMap);
@@ -0,0 +1,71 @@
+use crate::{
+    api::{
+        auth::{Principal, Role},
+        problem::Problem,
+    },
+    domain::{verify_api_key, ApiKey, ApiKeyRepository, ApiKeyScope},
+};
+use axum::{
+    body::Body,
+    extract::State,
+    http::{HeaderName, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use error_ext::StdErrorExt;
+use tracing::{debug, error, Span};
+
+pub const X_API_KEY: HeaderName = HeaderName::from_static("x-api-key");
+
+/// Middleware authenticating requests with an `X-Api-Key` header, if present, adding a principal
+/// with the roles given by the scopes of the API key; responds with 401 Unauthorized for invalid
+/// or revoked keys. Requests without that header are passed on to be authenticated otherwise.
+pub async fn authenticate<K>(
+    State(api_key_repository): State<K>,
+    mut request: Request<Body>,
+    next: Next,
+) -> Response
+where
+    K: ApiKeyRepository,
+{
+    let Some(key) = request.headers().get(X_API_KEY) else {
+        return next.run(request).await;
+    };
+
+    let key = key.to_str().unwrap_or_default();
+    let api_key = match verify_api_key(&api_key_repository, key).await {
+        Ok(Some(api_key)) => api_key,
+
+        Ok(None) => {
+            debug!("invalid, unknown or revoked API key");
+            return Problem::new(StatusCode::UNAUTHORIZED).into_response();
+        }
+
+        Err(error) => {
+            error!(error = error.as_chain(), "cannot get API key");
+            return Problem::new(StatusCode::INTERNAL_SERVER_ERROR).into_response();
+        }
+    };
+
+    Span::current().record("api_key_id", api_key.id.to_string());
+    request.extensions_mut().insert(principal(api_key));
+
+    next.run(request).await
+}
+
+/// The principal for the given API key with the roles given by its scopes.
+pub fn principal(api_key: ApiKey) -> Principal {
+    let roles = api_key
+        .scopes
+        .into_iter()
+        .map(|scope| match scope {
+            ApiKeyScope::Teller => Role::Teller,
+            ApiKeyScope::Admin => Role::Admin,
+        })
+        .collect();
+
+    Principal {
+        subject: format!("api-key:{}", api_key.id),
+        roles,
+    }
+}
@@ -0,0 +1,273 @@
+use crate::api::problem::Problem;
+use anyhow::{anyhow, Context, Result};
+use axum::{
+    body::Body,
+    extract::State,
+    http::{header, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use jsonwebtoken::{decode, decode_header, jwk::JwkSet, Algorithm, DecodingKey, Validation};
+use secrecy::{ExposeSecret, SecretString};
+use serde::Deserialize;
+use std::{fs, path::PathBuf, sync::Arc};
+use tracing::{debug, Span};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Config {
+    key: KeyConfig,
+
+    /// If given, the `iss` claim must match.
+    issuer: Option<String>,
+
+    /// If given, the `aud` claim must match.
+    audience: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+enum KeyConfig {
+    /// A shared secret for HS256.
+    Hs256 { secret: SecretString },
+
+    /// A PEM encoded RSA public key for RS256.
+    #[serde(rename_all = "kebab-case")]
+    Rs256 { public_key_path: PathBuf },
+
+    /// A local JWKS file with RSA keys for RS256, selected by the `kid` header of tokens.
+    Jwks { path: PathBuf },
+}
+
+/// The authenticated caller, added to the extensions of authenticated requests.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Principal {
+    pub subject: String,
+    pub roles: Vec<Role>,
+}
+
+impl Principal {
+    pub fn has_role(&self, role: Role) -> bool {
+        self.roles.contains(&role)
+    }
+
+    /// The customer ID, i.e. the subject, if this principal has the customer role.
+    pub fn customer_id(&self) -> Option<Uuid> {
+        self.has_role(Role::Customer)
+            .then(|| self.subject.parse().ok())
+            .flatten()
+    }
+}
+
+/// Roles given by the `roles` claim.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// May see and operate their own accounts; the subject is the customer ID.
+    Customer,
+
+    /// May see and operate all accounts and customers.
+    Teller,
+
+    /// May do anything, including waiving fees and repairing projections.
+    Admin,
+}
+
+#[derive(Debug, Deserialize)]
+struct Claims {
+    sub: String,
+
+    #[serde(default)]
+    roles: Vec<Role>,
+}
+
+/// Validates JWT bearer tokens.
+pub struct Authenticator {
+    keys: Keys,
+    validation: Validation,
+}
+
+enum Keys {
+    Single(DecodingKey),
+    Jwks(JwkSet),
+}
+
+impl Authenticator {
+    pub fn new(config: Config) -> Result<Self> {
+        let Config {
+            key,
+            issuer,
+            audience,
+        } = config;
+
+        let (keys, algorithm) = match key {
+            KeyConfig::Hs256 { secret } => {
+                let key = DecodingKey::from_secret(secret.expose_secret().as_bytes());
+                (Keys::Single(key), Algorithm::HS256)
+            }
+
+            KeyConfig::Rs256 { public_key_path } => {
+                let key = fs::read(&public_key_path)
+                    .with_context(|| format!("read public key {}", public_key_path.display()))?;
+                let key = DecodingKey::from_rsa_pem(&key).context("parse RSA public key")?;
+                (Keys::Single(key), Algorithm::RS256)
+            }
+
+            KeyConfig::Jwks { path } => {
+                let jwks = fs::read(&path)
+                    .with_context(|| format!("read JWKS file {}", path.display()))?;
+                let jwks = serde_json::from_slice::<JwkSet>(&jwks).context("parse JWKS file")?;
+                (Keys::Jwks(jwks), Algorithm::RS256)
+            }
+        };
+
+        let mut validation = Validation::new(algorithm);
+        if let Some(issuer) = issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        match audience {
+            Some(audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+
+        Ok(Self { keys, validation })
+    }
+
+    /// Validate the given token and return the principal.
+    pub fn authenticate(&self, token: &str) -> Result<Principal> {
+        let key = match &self.keys {
+            Keys::Single(key) => key.clone(),
+
+            Keys::Jwks(jwks) => {
+                let kid = decode_header(token)
+                    .context("decode token header")?
+                    .kid
+                    .ok_or_else(|| anyhow!("token header has no kid"))?;
+                let jwk = jwks
+                    .find(&kid)
+                    .ok_or_else(|| anyhow!("unknown kid {kid}"))?;
+                DecodingKey::from_jwk(jwk).context("create key from JWK")?
+            }
+        };
+
+        let claims = decode::<Claims>(token, &key, &self.validation)
+            .context("validate token")?
+            .claims;
+
+        Ok(Principal {
+            subject: claims.sub,
+            roles: claims.roles,
+        })
+    }
+}
+
+/// Middleware authenticating requests with a JWT bearer token unless already authenticated, e.g.
+/// with an API key; responds with 401 Unauthorized for requests without a valid one.
+pub async fn authenticate(
+    State(authenticator): State<Arc<Authenticator>>,
+    mut request: Request<Body>,
+    next: Next,
+) -> Response {
+    if request.extensions().get::<Principal>().is_some() {
+        return next.run(request).await;
+    }
+
+    let token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    let principal = match token.map(|token| authenticator.authenticate(token)) {
+        Some(Ok(principal)) => principal,
+
+        Some(Err(error)) => {
+            debug!(error = format!("{error:#}"), "cannot authenticate request");
+            return unauthorized();
+        }
+
+        None => return unauthorized(),
+    };
+
+    Span::current().record("subject", principal.subject.as_str());
+    request.extensions_mut().insert(principal);
+
+    next.run(request).await
+}
+
+fn unauthorized() -> Response {
+    (
+        [(header::WWW_AUTHENTICATE, "Bearer")],
+        Problem::new(StatusCode::UNAUTHORIZED),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Authenticator, Config, KeyConfig, Principal, Role};
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use serde_json::json;
+    use time::{Duration, OffsetDateTime};
+    use uuid::Uuid;
+
+    #[test]
+    fn test_authenticate() {
+        let config = Config {
+            key: KeyConfig::Hs256 {
+                secret: "secret".to_string().into(),
+            },
+            issuer: Some("rusty-accounts".to_string()),
+            audience: None,
+        };
+        let authenticator = Authenticator::new(config).unwrap();
+
+        let token = |secret: &str, iss: &str, exp: OffsetDateTime| {
+            let claims = json!({
+                "sub": "alice",
+                "roles": ["teller"],
+                "iss": iss,
+                "exp": exp.unix_timestamp()
+            });
+            encode(
+                &Header::default(),
+                &claims,
+                &EncodingKey::from_secret(secret.as_bytes()),
+            )
+            .unwrap()
+        };
+        let exp = OffsetDateTime::now_utc() + Duration::hours(1);
+
+        let principal = authenticator.authenticate(&token("secret", "rusty-accounts", exp));
+        assert_eq!(
+            principal.ok(),
+            Some(Principal {
+                subject: "alice".to_string(),
+                roles: vec![Role::Teller]
+            })
+        );
+
+        let principal = authenticator.authenticate(&token("other", "rusty-accounts", exp));
+        assert!(principal.is_err());
+
+        let principal = authenticator.authenticate(&token("secret", "other", exp));
+        assert!(principal.is_err());
+
+        let expired = OffsetDateTime::now_utc() - Duration::hours(1);
+        let principal = authenticator.authenticate(&token("secret", "rusty-accounts", expired));
+        assert!(principal.is_err());
+    }
+
+    #[test]
+    fn test_customer_id() {
+        let id = Uuid::now_v7();
+        let principal = |roles| Principal {
+            subject: id.to_string(),
+            roles,
+        };
+
+        assert_eq!(principal(vec![Role::Customer]).customer_id(), Some(id));
+        assert_eq!(principal(vec![Role::Teller]).customer_id(), None);
+    }
+}
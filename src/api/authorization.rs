@@ -0,0 +1,182 @@
+use crate::{
+    api::{
+        auth::{Principal, Role},
+        problem::Problem,
+        AppState,
+    },
+    domain::{
+        AccountRepository, ApiKeyRepository, CustomerRepository, StandingOrderRepository,
+        WebhookRepository,
+    },
+};
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Path},
+    http::{request::Parts, StatusCode},
+    response::{IntoResponse, Response},
+};
+use error_ext::StdErrorExt;
+use std::{collections::HashMap, fmt, marker::PhantomData};
+use tracing::{debug, error};
+use uuid::Uuid;
+
+/// Which principals besides admins are authorized for a request.
+pub trait Policy {
+    /// Principals with any of these roles are authorized.
+    const ROLES: &'static [Role];
+
+    /// Customers owning the resource given by the `id` path parameter are authorized.
+    const OWNERSHIP: Option<Ownership>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ownership {
+    /// The customer is a holder of the account.
+    Account,
+
+    /// The customer is the customer.
+    Customer,
+}
+
+/// Any authenticated principal.
+pub struct Anyone;
+
+impl Policy for Anyone {
+    const ROLES: &'static [Role] = &[Role::Customer, Role::Teller];
+    const OWNERSHIP: Option<Ownership> = None;
+}
+
+/// Holders of the account, tellers and admins.
+pub struct AccountHolder;
+
+impl Policy for AccountHolder {
+    const ROLES: &'static [Role] = &[Role::Teller];
+    const OWNERSHIP: Option<Ownership> = Some(Ownership::Account);
+}
+
+/// The customer, tellers and admins.
+pub struct Myself;
+
+impl Policy for Myself {
+    const ROLES: &'static [Role] = &[Role::Teller];
+    const OWNERSHIP: Option<Ownership> = Some(Ownership::Customer);
+}
+
+/// Tellers and admins.
+pub struct Teller;
+
+impl Policy for Teller {
+    const ROLES: &'static [Role] = &[Role::Teller];
+    const OWNERSHIP: Option<Ownership> = None;
+}
+
+/// Admins only.
+pub struct Admin;
+
+impl Policy for Admin {
+    const ROLES: &'static [Role] = &[];
+    const OWNERSHIP: Option<Ownership> = None;
+}
+
+/// Extracts the authenticated principal, rejecting requests with 403 Forbidden unless it is
+/// authorized according to the policy `P`.
+pub struct Authorized<P>(pub Principal, PhantomData<P>);
+
+impl<P> fmt::Debug for Authorized<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Authorized").field(&self.0).finish()
+    }
+}
+
+#[async_trait]
+impl<P, R, C, S, K, W, L> FromRequestParts<AppState<R, C, S, K, W, L>> for Authorized<P>
+where
+    P: Policy,
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState<R, C, S, K, W, L>,
+    ) -> Result<Self, Self::Rejection> {
+        let principal = parts
+            .extensions
+            .get::<Principal>()
+            .cloned()
+            .ok_or_else(|| Problem::new(StatusCode::UNAUTHORIZED).into_response())?;
+
+        if principal.has_role(Role::Admin) || P::ROLES.iter().any(|&r| principal.has_role(r)) {
+            return Ok(Self(principal, PhantomData));
+        }
+
+        let (Some(ownership), Some(customer_id)) = (P::OWNERSHIP, principal.customer_id()) else {
+            debug!(?principal, "principal not authorized");
+            return Err(Problem::new(StatusCode::FORBIDDEN).into_response());
+        };
+
+        let Path(params) = Path::<HashMap<String, String>>::from_request_parts(parts, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+        let id = params
+            .get("id")
+            .and_then(|id| id.parse::<Uuid>().ok())
+            .ok_or_else(|| Problem::new(StatusCode::BAD_REQUEST).into_response())?;
+
+        let owner = match ownership {
+            Ownership::Account => is_holder(state, customer_id, id)
+                .await
+                .map_err(IntoResponse::into_response)?,
+            Ownership::Customer => customer_id == id,
+        };
+        if !owner {
+            debug!(?principal, %id, "principal does not own resource");
+            return Err(Problem::new(StatusCode::FORBIDDEN).into_response());
+        }
+
+        Ok(Self(principal, PhantomData))
+    }
+}
+
+/// Whether the given principal may access the account with the given ID, i.e. is an admin, a
+/// teller or a holder of the account; for requests without an `id` path parameter.
+pub async fn may_access_account<R, C, S, K, W, L>(
+    state: &AppState<R, C, S, K, W, L>,
+    principal: &Principal,
+    account_id: Uuid,
+) -> Result<bool, Problem>
+where
+    C: CustomerRepository,
+{
+    if principal.has_role(Role::Admin) || principal.has_role(Role::Teller) {
+        return Ok(true);
+    }
+
+    match principal.customer_id() {
+        Some(customer_id) => is_holder(state, customer_id, account_id).await,
+        None => Ok(false),
+    }
+}
+
+async fn is_holder<R, C, S, K, W, L>(
+    state: &AppState<R, C, S, K, W, L>,
+    customer_id: Uuid,
+    account_id: Uuid,
+) -> Result<bool, Problem>
+where
+    C: CustomerRepository,
+{
+    state
+        .customer_repository
+        .is_holder(customer_id, account_id)
+        .await
+        .map_err(|error| {
+            error!(error = error.as_chain(), "cannot check account holder");
+            Problem::internal()
+        })
+}
@@ -0,0 +1,85 @@
+use anyhow::{Context, Result};
+use axum::http::{HeaderName, HeaderValue, Method};
+use serde::Deserialize;
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, CorsLayer};
+
+/// Allowed origins, methods and headers for CORS; `*` allows any.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Config {
+    allowed_origins: Vec<String>,
+    allowed_methods: Vec<String>,
+    allowed_headers: Vec<String>,
+}
+
+impl Config {
+    pub fn layer(&self) -> Result<CorsLayer> {
+        let allow_origin = if is_any(&self.allowed_origins) {
+            AllowOrigin::any()
+        } else {
+            let origins = self
+                .allowed_origins
+                .iter()
+                .map(|origin| HeaderValue::from_str(origin))
+                .collect::<Result<Vec<_>, _>>()
+                .context("parse allowed origins")?;
+            AllowOrigin::list(origins)
+        };
+
+        let allow_methods = if is_any(&self.allowed_methods) {
+            AllowMethods::any()
+        } else {
+            let methods = self
+                .allowed_methods
+                .iter()
+                .map(|method| method.parse::<Method>())
+                .collect::<Result<Vec<_>, _>>()
+                .context("parse allowed methods")?;
+            AllowMethods::list(methods)
+        };
+
+        let allow_headers = if is_any(&self.allowed_headers) {
+            AllowHeaders::any()
+        } else {
+            let headers = self
+                .allowed_headers
+                .iter()
+                .map(|header| header.parse::<HeaderName>())
+                .collect::<Result<Vec<_>, _>>()
+                .context("parse allowed headers")?;
+            AllowHeaders::list(headers)
+        };
+
+        let layer = CorsLayer::new()
+            .allow_origin(allow_origin)
+            .allow_methods(allow_methods)
+            .allow_headers(allow_headers);
+        Ok(layer)
+    }
+}
+
+fn is_any(values: &[String]) -> bool {
+    values.iter().any(|value| value == "*")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Config;
+
+    #[test]
+    fn test_layer() {
+        let config = Config {
+            allowed_origins: vec!["https://example.com".to_string()],
+            allowed_methods: vec!["GET".to_string(), "POST".to_string()],
+            allowed_headers: vec!["*".to_string()],
+        };
+        assert!(config.layer().is_ok());
+
+        let config = Config {
+            allowed_origins: vec!["*".to_string()],
+            allowed_methods: vec!["NOT A METHOD".to_string()],
+            allowed_headers: vec![],
+        };
+        assert!(config.layer().is_err());
+    }
+}
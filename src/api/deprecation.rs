@@ -0,0 +1,107 @@
+use anyhow::{Context, Result};
+use axum::{
+    extract::State,
+    http::{header, HeaderName, HeaderValue},
+    response::Response,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use time::{macros::format_description, OffsetDateTime, UtcOffset};
+
+const DEPRECATION: HeaderName = HeaderName::from_static("deprecation");
+const SUNSET: HeaderName = HeaderName::from_static("sunset");
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Config {
+    /// If given, responses carry a `Deprecation` header according to RFC 9745.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    deprecated_at: Option<OffsetDateTime>,
+
+    /// If given, responses carry a `Sunset` header according to RFC 8594.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    sunset_at: Option<OffsetDateTime>,
+
+    /// If given, responses carry a `Link` header with relation type `deprecation`, e.g. pointing
+    /// to a migration guide.
+    link: Option<String>,
+}
+
+impl Config {
+    /// The headers to add to the responses of a deprecated API version.
+    pub fn headers(&self) -> Result<Arc<Vec<(HeaderName, HeaderValue)>>> {
+        let mut headers = vec![];
+
+        if let Some(deprecated_at) = self.deprecated_at {
+            let value = format!("@{}", deprecated_at.unix_timestamp());
+            headers.push((DEPRECATION, HeaderValue::from_str(&value)?));
+        }
+
+        if let Some(sunset_at) = self.sunset_at {
+            let value = http_date(sunset_at).context("format sunset date")?;
+            headers.push((SUNSET, HeaderValue::from_str(&value)?));
+        }
+
+        if let Some(link) = &self.link {
+            let value = format!(r#"<{link}>; rel="deprecation""#);
+            let value = HeaderValue::from_str(&value).context("invalid deprecation link")?;
+            headers.push((header::LINK, value));
+        }
+
+        Ok(Arc::new(headers))
+    }
+}
+
+/// Middleware adding the given deprecation headers to responses.
+pub async fn add_headers(
+    State(headers): State<Arc<Vec<(HeaderName, HeaderValue)>>>,
+    mut response: Response,
+) -> Response {
+    for (name, value) in headers.iter() {
+        response.headers_mut().append(name, value.clone());
+    }
+    response
+}
+
+/// Format the given time as IMF-fixdate, the preferred format for HTTP dates.
+fn http_date(time: OffsetDateTime) -> Result<String> {
+    let format = format_description!(
+        "[weekday repr:short], [day] [month repr:short] [year] [hour]:[minute]:[second] GMT"
+    );
+    let date = time.to_offset(UtcOffset::UTC).format(format)?;
+    Ok(date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Config, DEPRECATION, SUNSET};
+    use axum::http::header;
+    use time::macros::datetime;
+
+    #[test]
+    fn test_headers() {
+        let config = Config {
+            deprecated_at: Some(datetime!(2026-10-18 00:00 UTC)),
+            sunset_at: Some(datetime!(2027-04-18 12:00 +02:00)),
+            link: Some("https://example.com/migration".to_string()),
+        };
+        let headers = config.headers().unwrap();
+        assert_eq!(
+            headers
+                .iter()
+                .map(|(name, value)| (name.clone(), value.to_str().unwrap()))
+                .collect::<Vec<_>>(),
+            vec![
+                (DEPRECATION, "@1792281600"),
+                (SUNSET, "Sun, 18 Apr 2027 10:00:00 GMT"),
+                (
+                    header::LINK,
+                    r#"<https://example.com/migration>; rel="deprecation""#
+                ),
+            ]
+        );
+
+        let headers = Config::default().headers().unwrap();
+        assert!(headers.is_empty());
+    }
+}
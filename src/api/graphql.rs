@@ -0,0 +1,458 @@
+use crate::{
+    api::{
+        auth::{Principal, Role},
+        authorization::{self, Anyone, Authorized},
+        problem::Problem,
+        v0, AppState,
+    },
+    domain::{
+        self, AccountFilter, AccountRepository, ApiKeyRepository, CustomerRepository,
+        StandingOrderRepository, TransactionFilter, WebhookRepository,
+    },
+};
+use async_graphql::{
+    connection::{Connection, Edge},
+    Context, Data, EmptyMutation, Enum, Error, ErrorExtensions, InputObject, Object, Schema,
+    SimpleObject, Subscription,
+};
+use async_graphql_axum::{GraphQLProtocol, GraphQLRequest, GraphQLResponse, GraphQLWebSocket};
+use axum::{
+    extract::{ws::WebSocketUpgrade, Extension},
+    http::StatusCode,
+    response::Response,
+};
+use error_ext::StdErrorExt;
+use eventsourced::event_log::EventLog;
+use futures::{Stream, TryStreamExt};
+use std::marker::PhantomData;
+use time::OffsetDateTime;
+use tracing::error;
+use uuid::Uuid;
+
+/// Number of items per page if not given.
+const DEFAULT_PAGE_SIZE: usize = 20;
+
+const MAX_PAGE_SIZE: usize = 100;
+
+const MAX_DEPTH: usize = 8;
+
+const MAX_COMPLEXITY: usize = 1_000;
+
+pub type AccountSchema<R, C, S, K, W, E> =
+    Schema<Query<R, C, S, K, W, E>, EmptyMutation, Subscription<R, C, S, K, W, E>>;
+
+/// The read-only GraphQL schema over the projections with subscriptions fed from the event log.
+pub fn schema<R, C, S, K, W, E>(
+    app_state: AppState<R, C, S, K, W, E>,
+) -> AccountSchema<R, C, S, K, W, E>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    E: EventLog<Id = Uuid> + Sync,
+{
+    Schema::build(Query(PhantomData), EmptyMutation, Subscription(PhantomData))
+        .data(app_state.account_repository.clone())
+        .data(app_state)
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_COMPLEXITY)
+        .finish()
+}
+
+/// Execute a GraphQL query on behalf of the authenticated principal.
+pub async fn execute<R, C, S, K, W, E>(
+    Extension(schema): Extension<AccountSchema<R, C, S, K, W, E>>,
+    authorized: Authorized<Anyone>,
+    request: GraphQLRequest,
+) -> GraphQLResponse
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    E: EventLog<Id = Uuid> + Sync,
+{
+    schema
+        .execute(request.into_inner().data(authorized.0))
+        .await
+        .into()
+}
+
+/// Upgrade to a WebSocket connection serving GraphQL subscriptions on behalf of the authenticated
+/// principal.
+pub async fn subscribe<R, C, S, K, W, E>(
+    Extension(schema): Extension<AccountSchema<R, C, S, K, W, E>>,
+    authorized: Authorized<Anyone>,
+    protocol: GraphQLProtocol,
+    upgrade: WebSocketUpgrade,
+) -> Response
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    E: EventLog<Id = Uuid> + Sync,
+{
+    let mut data = Data::default();
+    data.insert(authorized.0);
+
+    upgrade
+        .protocols(async_graphql::http::ALL_WEBSOCKET_PROTOCOLS)
+        .on_upgrade(move |socket| {
+            GraphQLWebSocket::new(socket, schema, protocol)
+                .with_data(data)
+                .serve()
+        })
+}
+
+pub struct Query<R, C, S, K, W, E>(PhantomData<fn() -> (R, C, S, K, W, E)>);
+
+#[Object(name = "Query")]
+impl<R, C, S, K, W, E> Query<R, C, S, K, W, E>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    E: EventLog<Id = Uuid> + Sync,
+{
+    /// Accounts matching the given filter, ordered by ID; customers only get their own ones.
+    async fn accounts(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<AccountFilterInput>,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, Account<R>>, Error> {
+        let app_state = ctx.data::<AppState<R, C, S, K, W, E>>()?;
+        let principal = ctx.data::<Principal>()?;
+
+        let first = page_size(first)?;
+        let after = after
+            .map(|after| after.parse::<Uuid>())
+            .transpose()
+            .map_err(|_| Error::new("after must be a cursor returned before"))?;
+
+        let mut filter = filter.map(AccountFilter::from).unwrap_or_default();
+        if !principal.has_role(Role::Admin) && !principal.has_role(Role::Teller) {
+            let Some(customer_id) = principal.customer_id() else {
+                return Ok(Connection::new(false, false));
+            };
+            filter.holder = Some(customer_id);
+        }
+
+        let mut accounts = app_state
+            .account_repository
+            .accounts_page(&filter, after, first as u32 + 1)
+            .await
+            .map_err(|error| {
+                error!(error = error.as_chain(), "cannot list accounts");
+                Error::from(Problem::internal())
+            })?;
+
+        let has_next_page = accounts.len() > first;
+        accounts.truncate(first);
+
+        let mut connection = Connection::new(after.is_some(), has_next_page);
+        connection.edges.extend(
+            accounts
+                .into_iter()
+                .map(|account| Edge::new(account.id.to_string(), Account::new(account))),
+        );
+        Ok(connection)
+    }
+
+    /// The account with the given ID, if any.
+    async fn account(&self, ctx: &Context<'_>, id: Uuid) -> Result<Option<Account<R>>, Error> {
+        let app_state = ctx.data::<AppState<R, C, S, K, W, E>>()?;
+        let principal = ctx.data::<Principal>()?;
+
+        authorize(app_state, principal, id).await?;
+
+        let account = app_state
+            .account_repository
+            .account(id)
+            .await
+            .map_err(|error| {
+                error!(error = error.as_chain(), "cannot get account");
+                Error::from(Problem::internal())
+            })?;
+        Ok(account.map(Account::new))
+    }
+}
+
+pub struct Subscription<R, C, S, K, W, E>(PhantomData<fn() -> (R, C, S, K, W, E)>);
+
+#[Subscription(name = "Subscription")]
+impl<R, C, S, K, W, E> Subscription<R, C, S, K, W, E>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    E: EventLog<Id = Uuid> + Sync,
+{
+    /// The current balance of the account with the given ID and then every change of it.
+    async fn balances(
+        &self,
+        ctx: &Context<'_>,
+        account_id: Uuid,
+    ) -> Result<impl Stream<Item = Result<Balance, Error>>, Error> {
+        let app_state = ctx.data::<AppState<R, C, S, K, W, E>>()?;
+        let principal = ctx.data::<Principal>()?;
+
+        authorize(app_state, principal, account_id).await?;
+
+        let balances = v0::balances(app_state.event_log.clone(), account_id)
+            .await?
+            .map_ok(move |balance| Balance {
+                account_id,
+                balance,
+            })
+            .map_err(|error| {
+                error!(error = error.as_chain(), "cannot get account event");
+                Error::from(Problem::internal())
+            });
+        Ok(balances)
+    }
+}
+
+pub struct Account<R> {
+    account: domain::Account,
+    _repository: PhantomData<fn() -> R>,
+}
+
+impl<R> Account<R> {
+    fn new(account: domain::Account) -> Self {
+        Self {
+            account,
+            _repository: PhantomData,
+        }
+    }
+}
+
+#[Object(name = "Account")]
+impl<R> Account<R>
+where
+    R: AccountRepository,
+{
+    async fn id(&self) -> Uuid {
+        self.account.id
+    }
+
+    async fn product(&self) -> &str {
+        &self.account.product
+    }
+
+    async fn balance(&self) -> i64 {
+        self.account.balance
+    }
+
+    /// Transactions matching the given filter, ordered by sequence number.
+    async fn transactions(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<TransactionFilterInput>,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, Transaction>, Error> {
+        let account_repository = ctx.data::<R>()?;
+
+        let first = page_size(first)?;
+        let after = after
+            .map(|after| after.parse::<u64>())
+            .transpose()
+            .map_err(|_| Error::new("after must be a cursor returned before"))?;
+
+        let filter = filter.map(TransactionFilter::from).unwrap_or_default();
+        let mut transactions = account_repository
+            .transactions_page(self.account.id, &filter, after, first as u32 + 1)
+            .await
+            .map_err(|error| {
+                error!(error = error.as_chain(), "cannot list transactions");
+                Error::from(Problem::internal())
+            })?;
+
+        let has_next_page = transactions.len() > first;
+        transactions.truncate(first);
+
+        let mut connection = Connection::new(after.is_some(), has_next_page);
+        connection.edges.extend(
+            transactions
+                .into_iter()
+                .map(|transaction| Edge::new(transaction.seq_no.to_string(), transaction.into())),
+        );
+        Ok(connection)
+    }
+}
+
+#[derive(Debug, SimpleObject)]
+pub struct Transaction {
+    seq_no: u64,
+    kind: TransactionKind,
+    amount: u64,
+    fee: u64,
+    balance: i64,
+    at: OffsetDateTime,
+    reverses: Option<u64>,
+    reversed_by: Option<u64>,
+}
+
+impl From<domain::Transaction> for Transaction {
+    fn from(transaction: domain::Transaction) -> Self {
+        Self {
+            seq_no: transaction.seq_no,
+            kind: transaction.kind.into(),
+            amount: transaction.amount,
+            fee: transaction.fee,
+            balance: transaction.balance,
+            at: transaction.at,
+            reverses: transaction.reverses,
+            reversed_by: transaction.reversed_by,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+pub enum TransactionKind {
+    Deposit,
+    Withdrawal,
+    HoldCapture,
+    Reversal,
+    Interest,
+    Fee,
+    FeeWaiver,
+}
+
+impl From<domain::TransactionKind> for TransactionKind {
+    fn from(kind: domain::TransactionKind) -> Self {
+        match kind {
+            domain::TransactionKind::Deposit => TransactionKind::Deposit,
+            domain::TransactionKind::Withdrawal => TransactionKind::Withdrawal,
+            domain::TransactionKind::HoldCapture => TransactionKind::HoldCapture,
+            domain::TransactionKind::Reversal => TransactionKind::Reversal,
+            domain::TransactionKind::Interest => TransactionKind::Interest,
+            domain::TransactionKind::Fee => TransactionKind::Fee,
+            domain::TransactionKind::FeeWaiver => TransactionKind::FeeWaiver,
+        }
+    }
+}
+
+impl From<TransactionKind> for domain::TransactionKind {
+    fn from(kind: TransactionKind) -> Self {
+        match kind {
+            TransactionKind::Deposit => domain::TransactionKind::Deposit,
+            TransactionKind::Withdrawal => domain::TransactionKind::Withdrawal,
+            TransactionKind::HoldCapture => domain::TransactionKind::HoldCapture,
+            TransactionKind::Reversal => domain::TransactionKind::Reversal,
+            TransactionKind::Interest => domain::TransactionKind::Interest,
+            TransactionKind::Fee => domain::TransactionKind::Fee,
+            TransactionKind::FeeWaiver => domain::TransactionKind::FeeWaiver,
+        }
+    }
+}
+
+#[derive(Debug, SimpleObject)]
+pub struct Balance {
+    account_id: Uuid,
+    balance: i64,
+}
+
+#[derive(Debug, InputObject)]
+#[graphql(name = "AccountFilter")]
+pub struct AccountFilterInput {
+    product: Option<String>,
+    min_balance: Option<i64>,
+    max_balance: Option<i64>,
+
+    /// Only accounts held by the customer with this ID; ignored for customers.
+    holder: Option<Uuid>,
+}
+
+impl From<AccountFilterInput> for AccountFilter {
+    fn from(filter: AccountFilterInput) -> Self {
+        Self {
+            product: filter.product,
+            min_balance: filter.min_balance,
+            max_balance: filter.max_balance,
+            holder: filter.holder,
+        }
+    }
+}
+
+#[derive(Debug, InputObject)]
+#[graphql(name = "TransactionFilter")]
+pub struct TransactionFilterInput {
+    kind: Option<TransactionKind>,
+
+    /// Only transactions at or after this time.
+    from: Option<OffsetDateTime>,
+
+    /// Only transactions before this time.
+    to: Option<OffsetDateTime>,
+}
+
+impl From<TransactionFilterInput> for TransactionFilter {
+    fn from(filter: TransactionFilterInput) -> Self {
+        Self {
+            kind: filter.kind.map(Into::into),
+            from: filter.from,
+            to: filter.to,
+        }
+    }
+}
+
+impl From<Problem> for Error {
+    fn from(problem: Problem) -> Self {
+        let status = problem.status;
+        Error::new(problem.detail.unwrap_or(problem.title))
+            .extend_with(|_, extensions| extensions.set("status", status))
+    }
+}
+
+async fn authorize<R, C, S, K, W, E>(
+    app_state: &AppState<R, C, S, K, W, E>,
+    principal: &Principal,
+    account_id: Uuid,
+) -> Result<(), Error>
+where
+    C: CustomerRepository,
+{
+    if authorization::may_access_account(app_state, principal, account_id).await? {
+        Ok(())
+    } else {
+        Err(Problem::new(StatusCode::FORBIDDEN)
+            .with_account_id(account_id)
+            .into())
+    }
+}
+
+fn page_size(first: Option<i32>) -> Result<usize, Error> {
+    match first {
+        None => Ok(DEFAULT_PAGE_SIZE),
+        Some(first) if (1..=MAX_PAGE_SIZE as i32).contains(&first) => Ok(first as usize),
+        Some(_) => Err(Error::new(format!(
+            "first must be between 1 and {MAX_PAGE_SIZE}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{page_size, DEFAULT_PAGE_SIZE};
+
+    #[test]
+    fn test_page_size() {
+        assert_eq!(page_size(None).ok(), Some(DEFAULT_PAGE_SIZE));
+        assert_eq!(page_size(Some(1)).ok(), Some(1));
+        assert_eq!(page_size(Some(100)).ok(), Some(100));
+        assert!(page_size(Some(0)).is_err());
+        assert!(page_size(Some(101)).is_err());
+    }
+}
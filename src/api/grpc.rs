@@ -0,0 +1,336 @@
+use crate::{
+    api::{
+        api_key,
+        auth::{Principal, Role},
+        authorization,
+        problem::Problem,
+        v0, AppState,
+    },
+    domain::{
+        verify_api_key, Account, AccountRepository, ApiKeyRepository, CustomerRepository,
+        StandingOrderRepository, WebhookRepository,
+    },
+};
+use anyhow::{Context, Result};
+use axum::http::StatusCode;
+use error_ext::StdErrorExt;
+use eventsourced::event_log::EventLog;
+use futures::{channel::mpsc, SinkExt, Stream, StreamExt, TryStreamExt};
+use serde::Deserialize;
+use std::{
+    future::Future,
+    net::{IpAddr, SocketAddr},
+    pin::{pin, Pin},
+};
+use tonic::{transport::Server, Code, Request, Response, Status};
+use tracing::{debug, error, info_span, instrument};
+use uuid::Uuid;
+
+mod proto {
+    tonic::include_proto!("rusty_accounts.v1");
+}
+
+use proto::account_service_server::{AccountService, AccountServiceServer};
+
+/// Number of accounts buffered when listing accounts.
+const BUFFER_SIZE: usize = 64;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Config {
+    port: u16,
+}
+
+/// Serve the gRPC API on the given address and the configured port until the given shutdown
+/// signal completes.
+pub async fn serve<R, C, S, K, W, E>(
+    config: Config,
+    addr: IpAddr,
+    app_state: AppState<R, C, S, K, W, E>,
+    shutdown_signal: impl Future<Output = ()>,
+) -> Result<()>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    E: EventLog<Id = Uuid> + Sync,
+{
+    let service = AccountServiceServer::new(GrpcAccountService { app_state });
+
+    Server::builder()
+        .trace_fn(|request| info_span!("incoming gRPC request", path = request.uri().path()))
+        .add_service(service)
+        .serve_with_shutdown(SocketAddr::from((addr, config.port)), shutdown_signal)
+        .await
+        .context("run gRPC server")
+}
+
+struct GrpcAccountService<R, C, S, K, W, E> {
+    app_state: AppState<R, C, S, K, W, E>,
+}
+
+impl<R, C, S, K, W, E> GrpcAccountService<R, C, S, K, W, E>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    E: EventLog<Id = Uuid> + Sync,
+{
+    /// Authenticate the given request with the API key given as `x-api-key` metadata.
+    async fn authenticate<T>(&self, request: &Request<T>) -> Result<Principal, Status> {
+        let key = request
+            .metadata()
+            .get(api_key::X_API_KEY.as_str())
+            .and_then(|key| key.to_str().ok())
+            .ok_or_else(|| Status::unauthenticated("missing API key"))?;
+
+        let api_key = verify_api_key(&self.app_state.api_key_repository, key)
+            .await
+            .map_err(|error| {
+                error!(error = error.as_chain(), "cannot get API key");
+                Status::internal("cannot get API key")
+            })?
+            .ok_or_else(|| {
+                debug!("invalid, unknown or revoked API key");
+                Status::unauthenticated("invalid API key")
+            })?;
+
+        Ok(api_key::principal(api_key))
+    }
+
+    /// Authorize admins and tellers only, like `Authorized<Teller>` for the REST API.
+    fn authorize_teller(&self, principal: &Principal) -> Result<(), Status> {
+        if principal.has_role(Role::Admin) || principal.has_role(Role::Teller) {
+            Ok(())
+        } else {
+            debug!(?principal, "principal not authorized");
+            Err(Status::permission_denied("principal not authorized"))
+        }
+    }
+
+    /// Authorize admins, tellers and holders of the account with the given ID, like
+    /// `Authorized<AccountHolder>` for the REST API.
+    async fn authorize_account(&self, principal: &Principal, id: Uuid) -> Result<(), Status> {
+        if authorization::may_access_account(&self.app_state, principal, id).await? {
+            Ok(())
+        } else {
+            debug!(?principal, %id, "principal does not own account");
+            Err(Status::permission_denied("principal does not own account"))
+        }
+    }
+
+    /// Apply the rate limits for commands like for the REST API.
+    fn rate_limit(&self, principal: &Principal, account_id: Option<Uuid>) -> Result<(), Status> {
+        self.app_state
+            .rate_limiter
+            .acquire(Some(principal), account_id)
+            .map_err(|_| Status::resource_exhausted("rate limit exceeded"))
+    }
+}
+
+#[tonic::async_trait]
+impl<R, C, S, K, W, E> AccountService for GrpcAccountService<R, C, S, K, W, E>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    E: EventLog<Id = Uuid> + Sync,
+{
+    type ListAccountsStream = mpsc::Receiver<Result<proto::Account, Status>>;
+
+    type WatchAccountStream = Pin<Box<dyn Stream<Item = Result<proto::Balance, Status>> + Send>>;
+
+    #[instrument(skip(self))]
+    async fn create_account(
+        &self,
+        request: Request<proto::CreateAccountRequest>,
+    ) -> Result<Response<proto::Account>, Status> {
+        let principal = self.authenticate(&request).await?;
+        self.authorize_teller(&principal)?;
+        self.rate_limit(&principal, None)?;
+
+        let proto::CreateAccountRequest { holders, product } = request.into_inner();
+        let holders = holders
+            .iter()
+            .map(|holder| parse_id("holders", holder))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let account = v0::handle_create_account(&self.app_state, holders, product).await?;
+        Ok(Response::new(account.into()))
+    }
+
+    #[instrument(skip(self))]
+    async fn deposit(
+        &self,
+        request: Request<proto::DepositRequest>,
+    ) -> Result<Response<proto::Account>, Status> {
+        let id = parse_id("account_id", &request.get_ref().account_id)?;
+        let principal = self.authenticate(&request).await?;
+        self.authorize_teller(&principal)?;
+        self.rate_limit(&principal, Some(id))?;
+
+        let amount = request.into_inner().amount;
+        let account = v0::handle_deposit(self.app_state.event_log.clone(), id, amount).await?;
+        Ok(Response::new(account.into()))
+    }
+
+    #[instrument(skip(self))]
+    async fn withdraw(
+        &self,
+        request: Request<proto::WithdrawRequest>,
+    ) -> Result<Response<proto::Account>, Status> {
+        let id = parse_id("account_id", &request.get_ref().account_id)?;
+        let principal = self.authenticate(&request).await?;
+        self.authorize_account(&principal, id).await?;
+        self.rate_limit(&principal, Some(id))?;
+
+        let amount = request.into_inner().amount;
+        let account = v0::handle_withdraw(self.app_state.event_log.clone(), id, amount).await?;
+        Ok(Response::new(account.into()))
+    }
+
+    #[instrument(skip(self))]
+    async fn get_account(
+        &self,
+        request: Request<proto::GetAccountRequest>,
+    ) -> Result<Response<proto::Account>, Status> {
+        let principal = self.authenticate(&request).await?;
+
+        let id = parse_id("account_id", &request.get_ref().account_id)?;
+        self.authorize_account(&principal, id).await?;
+        let account = self
+            .app_state
+            .account_repository
+            .account(id)
+            .await
+            .map_err(|error| {
+                error!(error = error.as_chain(), "cannot get account");
+                Status::internal("cannot get account")
+            })?
+            .ok_or_else(|| Status::not_found(format!("account with ID {id} not found")))?;
+        Ok(Response::new(account.into()))
+    }
+
+    #[instrument(skip(self))]
+    async fn list_accounts(
+        &self,
+        request: Request<proto::ListAccountsRequest>,
+    ) -> Result<Response<Self::ListAccountsStream>, Status> {
+        let principal = self.authenticate(&request).await?;
+        self.authorize_teller(&principal)?;
+
+        // The stream of accounts borrows the repository, hence forward it from a task.
+        let (mut accounts_tx, accounts_rx) = mpsc::channel(BUFFER_SIZE);
+        let account_repository = self.app_state.account_repository.clone();
+        tokio::spawn(async move {
+            let accounts = match account_repository.accounts().await {
+                Ok(accounts) => accounts,
+                Err(error) => {
+                    error!(error = error.as_chain(), "cannot list accounts");
+                    let _ = accounts_tx
+                        .send(Err(Status::internal("cannot list accounts")))
+                        .await;
+                    return;
+                }
+            };
+            let mut accounts = pin!(accounts);
+
+            while let Some(account) = accounts.next().await {
+                let account = account.map(proto::Account::from).map_err(|error| {
+                    error!(error = error.as_chain(), "cannot list accounts");
+                    Status::internal("cannot list accounts")
+                });
+                let failed = account.is_err();
+                if accounts_tx.send(account).await.is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        Ok(Response::new(accounts_rx))
+    }
+
+    #[instrument(skip(self))]
+    async fn watch_account(
+        &self,
+        request: Request<proto::WatchAccountRequest>,
+    ) -> Result<Response<Self::WatchAccountStream>, Status> {
+        let principal = self.authenticate(&request).await?;
+
+        let id = parse_id("account_id", &request.get_ref().account_id)?;
+        self.authorize_account(&principal, id).await?;
+        let balances = v0::balances(self.app_state.event_log.clone(), id)
+            .await?
+            .map_ok(move |balance| proto::Balance {
+                account_id: id.to_string(),
+                balance,
+            })
+            .map_err(|error| {
+                error!(error = error.as_chain(), "cannot get account event");
+                Status::internal("cannot get account event")
+            });
+        Ok(Response::new(Box::pin(balances)))
+    }
+}
+
+impl From<Account> for proto::Account {
+    fn from(account: Account) -> Self {
+        Self {
+            id: account.id.to_string(),
+            product: account.product,
+            balance: account.balance,
+        }
+    }
+}
+
+impl From<Problem> for Status {
+    fn from(problem: Problem) -> Self {
+        let code = match StatusCode::from_u16(problem.status) {
+            Ok(StatusCode::BAD_REQUEST) => Code::InvalidArgument,
+            Ok(StatusCode::UNAUTHORIZED) => Code::Unauthenticated,
+            Ok(StatusCode::FORBIDDEN) => Code::PermissionDenied,
+            Ok(StatusCode::NOT_FOUND) => Code::NotFound,
+            Ok(StatusCode::CONFLICT) => Code::AlreadyExists,
+            Ok(StatusCode::UNPROCESSABLE_ENTITY) => Code::FailedPrecondition,
+            Ok(StatusCode::TOO_MANY_REQUESTS) => Code::ResourceExhausted,
+            Ok(StatusCode::SERVICE_UNAVAILABLE) => Code::Unavailable,
+            _ => Code::Internal,
+        };
+        Status::new(code, problem.detail.unwrap_or(problem.title))
+    }
+}
+
+fn parse_id(field: &str, id: &str) -> Result<Uuid, Status> {
+    id.parse()
+        .map_err(|_| Status::invalid_argument(format!("{field} must be a UUID")))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{api::problem::Problem, domain::WithdrawError};
+    use axum::http::StatusCode;
+    use tonic::{Code, Status};
+    use uuid::Uuid;
+
+    #[test]
+    fn test_status_from_problem() {
+        let id = Uuid::now_v7();
+
+        let status = Status::from(Problem::from(WithdrawError::InsufficientBalance(id)));
+        assert_eq!(status.code(), Code::FailedPrecondition);
+        assert_eq!(
+            status.message(),
+            format!("account with ID {id} has insufficient balance for withdrawal")
+        );
+
+        let status = Status::from(Problem::new(StatusCode::TOO_MANY_REQUESTS));
+        assert_eq!(status.code(), Code::ResourceExhausted);
+        assert_eq!(status.message(), "Too Many Requests");
+    }
+}
@@ -0,0 +1,49 @@
+use crate::api::problem::Problem;
+use axum::{
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    BoxError,
+};
+use serde::Deserialize;
+use std::time::Duration;
+use tower::{load_shed::error::Overloaded, timeout::error::Elapsed};
+use tracing::{error, warn};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Config {
+    /// Requests taking longer are aborted with 503 Service Unavailable.
+    pub request_timeout_secs: u64,
+
+    /// Larger request bodies are rejected with 413 Payload Too Large.
+    pub max_body_bytes: usize,
+
+    /// Requests beyond this number of in-flight ones are shed with 503 Service Unavailable.
+    pub max_concurrent_requests: usize,
+}
+
+impl Config {
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.request_timeout_secs)
+    }
+}
+
+/// Respond with 503 Service Unavailable for timed out or shed requests.
+pub async fn handle_error(error: BoxError) -> Response {
+    if error.is::<Elapsed>() {
+        warn!("request timed out");
+        return Problem::new(StatusCode::SERVICE_UNAVAILABLE).into_response();
+    }
+
+    if error.is::<Overloaded>() {
+        warn!("request shed");
+        return (
+            [(header::RETRY_AFTER, "1")],
+            Problem::new(StatusCode::SERVICE_UNAVAILABLE),
+        )
+            .into_response();
+    }
+
+    error!(%error, "unexpected error");
+    Problem::new(StatusCode::INTERNAL_SERVER_ERROR).into_response()
+}
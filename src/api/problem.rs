@@ -0,0 +1,288 @@
+use crate::domain::{CreateAccountError, DepositError, WithdrawError, MAX_AMOUNT};
+use axum::{
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use opentelemetry::trace::{TraceContextExt, TraceId};
+use serde::Serialize;
+use std::error::Error as StdError;
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+pub const CONTENT_TYPE: &str = "application/problem+json";
+
+/// Problem details for HTTP APIs according to RFC 7807, extended with the account ID, if any,
+/// the trace ID and validation details.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct Problem {
+    /// A URI reference identifying the problem type; `about:blank` for problems which are
+    /// sufficiently described by the status.
+    #[serde(rename = "type")]
+    pub problem_type: String,
+
+    pub title: String,
+
+    pub status: u16,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_id: Option<Uuid>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trace_id: Option<String>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub invalid_params: Vec<InvalidParam>,
+}
+
+/// Validation details for a single invalid parameter.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct InvalidParam {
+    pub name: String,
+    pub reason: String,
+}
+
+impl Problem {
+    /// A problem of type `about:blank` with the canonical reason of the given status as title.
+    pub fn new(status: StatusCode) -> Self {
+        Self {
+            problem_type: "about:blank".to_string(),
+            title: status.canonical_reason().unwrap_or_default().to_string(),
+            status: status.as_u16(),
+            detail: None,
+            account_id: None,
+            trace_id: None,
+            invalid_params: vec![],
+        }
+    }
+
+    /// A problem of the given type, qualified as `urn:rusty-accounts:problem:<problem_type>`.
+    pub fn typed(status: StatusCode, problem_type: &str, title: &str) -> Self {
+        Self {
+            problem_type: format!("urn:rusty-accounts:problem:{problem_type}"),
+            title: title.to_string(),
+            ..Self::new(status)
+        }
+    }
+
+    pub fn internal() -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    pub fn not_found(error: impl StdError) -> Self {
+        Self::new(StatusCode::NOT_FOUND).with_detail(error)
+    }
+
+    pub fn conflict(error: impl StdError) -> Self {
+        Self::new(StatusCode::CONFLICT).with_detail(error)
+    }
+
+    pub fn invalid_entity(error: impl StdError) -> Self {
+        Self::new(StatusCode::UNPROCESSABLE_ENTITY).with_detail(error)
+    }
+
+    pub fn with_detail(mut self, error: impl StdError) -> Self {
+        self.detail = Some(error.to_string());
+        self
+    }
+
+    pub fn with_account_id(mut self, account_id: Uuid) -> Self {
+        self.account_id = Some(account_id);
+        self
+    }
+
+    pub fn with_invalid_param(mut self, name: &str, reason: impl ToString) -> Self {
+        self.invalid_params.push(InvalidParam {
+            name: name.to_string(),
+            reason: reason.to_string(),
+        });
+        self
+    }
+}
+
+impl IntoResponse for Problem {
+    fn into_response(mut self) -> Response {
+        if self.trace_id.is_none() {
+            let trace_id = Span::current().context().span().span_context().trace_id();
+            self.trace_id = (trace_id != TraceId::INVALID).then(|| trace_id.to_string());
+        }
+
+        let status =
+            StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let mut response = (status, Json(self)).into_response();
+        response
+            .headers_mut()
+            .insert(header::CONTENT_TYPE, HeaderValue::from_static(CONTENT_TYPE));
+        response
+    }
+}
+
+impl From<CreateAccountError> for Problem {
+    fn from(error: CreateAccountError) -> Self {
+        let (problem, id) = match &error {
+            CreateAccountError::AlreadyExisting(id) => (
+                Problem::typed(
+                    StatusCode::CONFLICT,
+                    "account-already-existing",
+                    "Account already existing",
+                ),
+                *id,
+            ),
+
+            CreateAccountError::NoHolders(id) => (
+                Problem::typed(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    "account-without-holders",
+                    "Account without holders",
+                )
+                .with_invalid_param("holders", "must not be empty"),
+                *id,
+            ),
+        };
+        problem.with_detail(error).with_account_id(id)
+    }
+}
+
+impl From<DepositError> for Problem {
+    fn from(error: DepositError) -> Self {
+        let (problem, id) = match &error {
+            DepositError::NotFound(id) => (
+                Problem::typed(
+                    StatusCode::NOT_FOUND,
+                    "account-not-found",
+                    "Account not found",
+                ),
+                *id,
+            ),
+
+            DepositError::NotAllowed(id) => (
+                Problem::typed(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    "operation-not-allowed",
+                    "Operation not allowed by product",
+                ),
+                *id,
+            ),
+
+            DepositError::InvalidAmount(id) => (invalid_amount(), *id),
+
+            DepositError::NotOpen(id) => (not_open(), *id),
+
+            DepositError::BalanceOverflow(id) => (
+                Problem::typed(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    "balance-overflow",
+                    "Balance overflow",
+                )
+                .with_invalid_param("amount", "exceeds maximum balance"),
+                *id,
+            ),
+        };
+        problem.with_detail(error).with_account_id(id)
+    }
+}
+
+impl From<WithdrawError> for Problem {
+    fn from(error: WithdrawError) -> Self {
+        let (problem, id) = match &error {
+            WithdrawError::NotFound(id) => (
+                Problem::typed(
+                    StatusCode::NOT_FOUND,
+                    "account-not-found",
+                    "Account not found",
+                ),
+                *id,
+            ),
+
+            WithdrawError::NotAllowed(id) => (
+                Problem::typed(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    "operation-not-allowed",
+                    "Operation not allowed by product",
+                ),
+                *id,
+            ),
+
+            WithdrawError::InvalidAmount(id) => (invalid_amount(), *id),
+
+            WithdrawError::NotOpen(id) => (not_open(), *id),
+
+            WithdrawError::InsufficientBalance(id) => (
+                Problem::typed(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    "insufficient-balance",
+                    "Insufficient balance",
+                )
+                .with_invalid_param("amount", "exceeds available balance"),
+                *id,
+            ),
+
+            WithdrawError::LimitExceeded {
+                id,
+                period,
+                remaining,
+            } => (
+                Problem::typed(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    "withdrawal-limit-exceeded",
+                    "Withdrawal limit exceeded",
+                )
+                .with_invalid_param(
+                    "amount",
+                    format!("exceeds {period} limit, remaining allowance is {remaining}"),
+                ),
+                *id,
+            ),
+        };
+        problem.with_detail(error).with_account_id(id)
+    }
+}
+
+fn invalid_amount() -> Problem {
+    Problem::typed(
+        StatusCode::UNPROCESSABLE_ENTITY,
+        "invalid-amount",
+        "Invalid amount",
+    )
+    .with_invalid_param("amount", format!("must be positive and at most {MAX_AMOUNT}"))
+}
+
+fn not_open() -> Problem {
+    Problem::typed(
+        StatusCode::UNPROCESSABLE_ENTITY,
+        "account-not-open",
+        "Account frozen or closed",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Problem;
+    use crate::domain::WithdrawError;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_serialize() {
+        let id = Uuid::now_v7();
+        let problem = Problem::from(WithdrawError::InsufficientBalance(id));
+
+        let json = serde_json::to_value(problem).unwrap();
+        assert_eq!(
+            json,
+            json!({
+                "type": "urn:rusty-accounts:problem:insufficient-balance",
+                "title": "Insufficient balance",
+                "status": 422,
+                "detail": format!("account with ID {id} has insufficient balance for withdrawal"),
+                "account_id": id,
+                "invalid_params": [{ "name": "amount", "reason": "exceeds available balance" }]
+            })
+        );
+    }
+}
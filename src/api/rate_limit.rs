@@ -0,0 +1,229 @@
+use crate::api::{auth::Principal, problem::Problem};
+use axum::{
+    body::Body,
+    extract::State,
+    http::{header, Method, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use opentelemetry::{global, metrics::Counter, KeyValue};
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::time::Instant;
+use tracing::debug;
+use uuid::Uuid;
+
+/// Beyond this number of buckets, full ones are dropped.
+const MAX_BUCKETS: usize = 10_000;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Config {
+    /// Limit per principal, e.g. a customer or an API key.
+    per_client: LimitConfig,
+
+    /// Limit per account.
+    per_account: LimitConfig,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct LimitConfig {
+    /// Maximum number of requests in a burst.
+    capacity: u32,
+
+    /// Requests per second on average.
+    refill_per_sec: f64,
+}
+
+/// Token bucket rate limits for command requests, i.e. ones with methods other than GET, HEAD and
+/// OPTIONS, keyed by principal and by account.
+#[derive(Clone)]
+pub struct RateLimiter {
+    per_client: Arc<TokenBuckets<String>>,
+    per_account: Arc<TokenBuckets<Uuid>>,
+    rate_limited: Counter<u64>,
+}
+
+impl RateLimiter {
+    pub fn new(config: Config) -> Self {
+        let rate_limited = global::meter("rusty-accounts")
+            .u64_counter("api.rate_limited_requests")
+            .with_description("Requests rejected because of a rate limit")
+            .init();
+
+        Self {
+            per_client: Arc::new(TokenBuckets::new(config.per_client)),
+            per_account: Arc::new(TokenBuckets::new(config.per_account)),
+            rate_limited,
+        }
+    }
+
+    /// Take a token for the given principal, if any, and account, if any, or return the duration
+    /// until the next one is available.
+    pub fn acquire(
+        &self,
+        principal: Option<&Principal>,
+        account_id: Option<Uuid>,
+    ) -> Result<(), Duration> {
+        let now = Instant::now();
+
+        if let Some(principal) = principal {
+            if let Err(retry_after) = self.per_client.acquire(principal.subject.clone(), now) {
+                debug!(?principal, "client rate limit exceeded");
+                self.rate_limited
+                    .add(1, &[KeyValue::new("limit", "client")]);
+                return Err(retry_after);
+            }
+        }
+
+        if let Some(account_id) = account_id {
+            if let Err(retry_after) = self.per_account.acquire(account_id, now) {
+                debug!(%account_id, "account rate limit exceeded");
+                self.rate_limited
+                    .add(1, &[KeyValue::new("limit", "account")]);
+                return Err(retry_after);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Middleware responding with 429 Too Many Requests and a `Retry-After` header for command
+/// requests exceeding a rate limit. Must be applied after authentication.
+pub async fn rate_limit(
+    State(rate_limiter): State<RateLimiter>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    if matches!(
+        *request.method(),
+        Method::GET | Method::HEAD | Method::OPTIONS
+    ) {
+        return next.run(request).await;
+    }
+
+    let principal = request.extensions().get::<Principal>();
+    let account_id = account_id(request.uri().path());
+    if let Err(retry_after) = rate_limiter.acquire(principal, account_id) {
+        return too_many_requests(retry_after);
+    }
+
+    next.run(request).await
+}
+
+/// The account ID of paths like `/accounts/{id}/...`.
+fn account_id(path: &str) -> Option<Uuid> {
+    let mut segments = path.split('/');
+    segments.find(|segment| *segment == "accounts")?;
+    segments.next()?.parse().ok()
+}
+
+fn too_many_requests(retry_after: Duration) -> Response {
+    let retry_after = retry_after.as_secs_f64().ceil() as u64;
+    (
+        [(header::RETRY_AFTER, retry_after.to_string())],
+        Problem::new(StatusCode::TOO_MANY_REQUESTS),
+    )
+        .into_response()
+}
+
+struct TokenBuckets<K> {
+    config: LimitConfig,
+    buckets: Mutex<HashMap<K, Bucket>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl<K> TokenBuckets<K>
+where
+    K: Eq + Hash,
+{
+    fn new(config: LimitConfig) -> Self {
+        Self {
+            config,
+            buckets: Mutex::default(),
+        }
+    }
+
+    /// Take a token from the bucket for the given key or return the duration until the next one
+    /// is available.
+    fn acquire(&self, key: K, now: Instant) -> Result<(), Duration> {
+        let capacity = self.config.capacity as f64;
+        let refill_per_sec = self.config.refill_per_sec;
+
+        let mut buckets = self.buckets.lock().expect("lock token buckets");
+
+        if buckets.len() >= MAX_BUCKETS {
+            buckets.retain(|_, bucket| {
+                let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+                bucket.tokens + elapsed * refill_per_sec < capacity
+            });
+        }
+
+        let bucket = buckets.entry(key).or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+        });
+
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * refill_per_sec).min(capacity);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - bucket.tokens) / refill_per_sec,
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{account_id, LimitConfig, TokenBuckets};
+    use std::time::Duration;
+    use tokio::time::Instant;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_acquire() {
+        let buckets = TokenBuckets::new(LimitConfig {
+            capacity: 2,
+            refill_per_sec: 0.5,
+        });
+        let now = Instant::now();
+
+        assert!(buckets.acquire("alice", now).is_ok());
+        assert!(buckets.acquire("alice", now).is_ok());
+        assert_eq!(buckets.acquire("alice", now), Err(Duration::from_secs(2)));
+        assert!(buckets.acquire("bob", now).is_ok());
+
+        let now = now + Duration::from_secs(1);
+        assert_eq!(buckets.acquire("alice", now), Err(Duration::from_secs(1)));
+
+        let now = now + Duration::from_secs(1);
+        assert!(buckets.acquire("alice", now).is_ok());
+    }
+
+    #[test]
+    fn test_account_id() {
+        let id = Uuid::now_v7();
+        assert_eq!(account_id(&format!("/accounts/{id}/deposits")), Some(id));
+        assert_eq!(account_id(&format!("/v0/accounts/{id}")), Some(id));
+        assert_eq!(account_id("/accounts"), None);
+        assert_eq!(account_id("/customers"), None);
+    }
+}
@@ -0,0 +1,78 @@
+use crate::{api::problem::Problem, domain::AccountEvent};
+use axum::{
+    http::{HeaderMap, HeaderName, StatusCode},
+    response::sse::{Event, KeepAlive, Sse},
+    BoxError,
+};
+use error_ext::StdErrorExt;
+use futures::{future, Stream, TryStreamExt};
+use std::{error::Error as StdError, num::NonZeroU64};
+use tracing::error;
+
+const LAST_EVENT_ID: HeaderName = HeaderName::from_static("last-event-id");
+
+/// The sequence number to resume streaming from, i.e. the one following the sequence number given
+/// by the `Last-Event-ID` header, if any, or else the first one.
+pub fn resume_from(headers: &HeaderMap) -> Result<NonZeroU64, Problem> {
+    let Some(last_event_id) = headers.get(LAST_EVENT_ID) else {
+        return Ok(NonZeroU64::MIN);
+    };
+
+    last_event_id
+        .to_str()
+        .ok()
+        .and_then(|last_event_id| last_event_id.parse::<u64>().ok())
+        .and_then(|seq_no| seq_no.checked_add(1))
+        .and_then(NonZeroU64::new)
+        .ok_or_else(|| {
+            Problem::new(StatusCode::BAD_REQUEST)
+                .with_invalid_param("Last-Event-ID", "must be a sequence number")
+        })
+}
+
+/// Server-sent events for the given account events, each with its sequence number as ID, so that
+/// clients can resume via `Last-Event-ID`. The response ends on the first error.
+pub fn account_events<S, E>(events: S) -> Sse<impl Stream<Item = Result<Event, BoxError>>>
+where
+    S: Stream<Item = Result<(NonZeroU64, AccountEvent), E>>,
+    E: StdError + Send + Sync + 'static,
+{
+    let events = events
+        .inspect_err(|error| error!(error = error.as_chain(), "cannot get account event"))
+        .map_err(BoxError::from)
+        .and_then(|(seq_no, event)| future::ready(account_event(seq_no, &event)));
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+fn account_event(seq_no: NonZeroU64, event: &AccountEvent) -> Result<Event, BoxError> {
+    let event = Event::default()
+        .id(seq_no.to_string())
+        .event(event.name())
+        .json_data(event)?;
+    Ok(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{resume_from, LAST_EVENT_ID};
+    use axum::http::{HeaderMap, HeaderValue};
+    use std::num::NonZeroU64;
+
+    #[test]
+    fn test_resume_from() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(resume_from(&headers).ok(), Some(NonZeroU64::MIN));
+
+        headers.insert(LAST_EVENT_ID, HeaderValue::from_static("41"));
+        assert_eq!(resume_from(&headers).ok(), NonZeroU64::new(42));
+
+        headers.insert(LAST_EVENT_ID, HeaderValue::from_static("forty-one"));
+        assert!(resume_from(&headers).is_err());
+
+        headers.insert(
+            LAST_EVENT_ID,
+            HeaderValue::from_str(&u64::MAX.to_string()).unwrap(),
+        );
+        assert!(resume_from(&headers).is_err());
+    }
+}
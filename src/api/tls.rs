@@ -0,0 +1,140 @@
+use anyhow::{anyhow, Context, Result};
+use axum_server::tls_rustls::RustlsConfig;
+use rustls::{
+    crypto::{ring, CryptoProvider},
+    server::{danger::ClientCertVerifier, WebPkiClientVerifier},
+    RootCertStore, ServerConfig,
+};
+use serde::Deserialize;
+use std::{
+    fs::{self, File},
+    io::BufReader,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+use tracing::{error, info};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Config {
+    /// PEM file with the certificate chain.
+    cert_path: PathBuf,
+
+    /// PEM file with the private key.
+    key_path: PathBuf,
+
+    /// PEM file with the CA certificates for client certificates; if given, clients must
+    /// authenticate with a certificate (mTLS).
+    client_ca_path: Option<PathBuf>,
+
+    /// Seconds between two checks whether any of the above files have changed.
+    #[serde(default = "reload_interval_secs_default")]
+    reload_interval_secs: u64,
+}
+
+impl Config {
+    pub fn rustls_config(&self) -> Result<RustlsConfig> {
+        let server_config = self.server_config()?;
+        Ok(RustlsConfig::from_config(Arc::new(server_config)))
+    }
+
+    /// Periodically reload certificates and key, if any of their files have changed, e.g. when
+    /// renewed. Invalid files are logged and the current configuration is kept.
+    pub async fn reload(self, rustls_config: RustlsConfig) {
+        let interval = Duration::from_secs(self.reload_interval_secs);
+        let mut last_modified = self.last_modified();
+
+        loop {
+            tokio::time::sleep(interval).await;
+
+            let modified = self.last_modified();
+            if modified == last_modified {
+                continue;
+            }
+            last_modified = modified;
+
+            match self.server_config() {
+                Ok(server_config) => {
+                    rustls_config.reload_from_config(Arc::new(server_config));
+                    info!("reloaded TLS configuration");
+                }
+
+                Err(error) => error!(
+                    error = format!("{error:#}"),
+                    "cannot reload TLS configuration"
+                ),
+            }
+        }
+    }
+
+    fn server_config(&self) -> Result<ServerConfig> {
+        let provider = Arc::new(ring::default_provider());
+
+        let certs = read_pem(&self.cert_path, |reader| {
+            rustls_pemfile::certs(reader).collect::<Result<Vec<_>, _>>()
+        })?;
+        let key = read_pem(&self.key_path, rustls_pemfile::private_key)?
+            .ok_or_else(|| anyhow!("no private key in {}", self.key_path.display()))?;
+
+        let builder = ServerConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()
+            .context("create TLS configuration")?;
+        let builder = match &self.client_ca_path {
+            Some(client_ca_path) => {
+                let client_verifier = client_verifier(client_ca_path, provider)?;
+                builder.with_client_cert_verifier(client_verifier)
+            }
+            None => builder.with_no_client_auth(),
+        };
+
+        let mut server_config = builder
+            .with_single_cert(certs, key)
+            .context("create TLS configuration")?;
+        server_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+        Ok(server_config)
+    }
+
+    fn last_modified(&self) -> Vec<Option<SystemTime>> {
+        [
+            Some(&self.cert_path),
+            Some(&self.key_path),
+            self.client_ca_path.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        .map(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+        .collect()
+    }
+}
+
+fn client_verifier(
+    client_ca_path: &Path,
+    provider: Arc<CryptoProvider>,
+) -> Result<Arc<dyn ClientCertVerifier>> {
+    let client_cas = read_pem(client_ca_path, |reader| {
+        rustls_pemfile::certs(reader).collect::<Result<Vec<_>, _>>()
+    })?;
+
+    let mut roots = RootCertStore::empty();
+    for client_ca in client_cas {
+        roots.add(client_ca).context("add client CA certificate")?;
+    }
+
+    WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider)
+        .build()
+        .context("create client certificate verifier")
+}
+
+fn read_pem<T, F>(path: &Path, f: F) -> Result<T>
+where
+    F: FnOnce(&mut BufReader<File>) -> Result<T, std::io::Error>,
+{
+    let file = File::open(path).with_context(|| format!("open {}", path.display()))?;
+    f(&mut BufReader::new(file)).with_context(|| format!("read PEM file {}", path.display()))
+}
+
+fn reload_interval_secs_default() -> u64 {
+    60
+}
@@ -1,44 +1,258 @@
 use crate::{
-    api::AppState,
+    api::{
+        auth::Role,
+        authorization::{AccountHolder, Admin, Anyone, Authorized, Myself, Teller},
+        graphql,
+        problem::{InvalidParam, Problem},
+        sse, ws, AppState,
+    },
     domain::{
-        Account, AccountEntity, AccountRepository, CreateAccount, CreateAccountError, Deposit,
-        DepositError, Withdraw, WithdrawError,
+        generate_api_key, generate_webhook_secret, replay_account, Account, AccountEntity,
+        AccountEvent, AccountRepository, AccountState, AccountStatus, ApiKey, ApiKeyRepository,
+        ApiKeyScope, AsOf, CancelStandingOrder, CancelStandingOrderError, CaptureHold,
+        CaptureHoldError, ChangeContact, ChangeContactError, CreateAccount, CreateCustomer,
+        CreateCustomerError, CreateStandingOrder, CreateStandingOrderError, Customer,
+        CustomerEntity, CustomerRepository, DayCount, Deposit, Drift, ExecutionFailure,
+        FeeSchedule, GetHolds, GetHoldsError, Hold, Holds, KycStatus, LedgerAccount,
+        LedgerAccountBalance, Operation, PlaceHold, PlaceHoldError, Product, Reconciliation,
+        ReleaseHold, ReleaseHoldError, Reverse, ReverseError, SetAccountStatus,
+        SetAccountStatusError, SetKycStatus, SetKycStatusError, SetWithdrawalLimits,
+        SetWithdrawalLimitsError, StandingOrder, StandingOrderEntity, StandingOrderRepository,
+        StandingOrderStatus, Statement, Transaction, TransactionKind, TrialBalance,
+        UpdateStandingOrder, UpdateStandingOrderError, WaiveFee, WaiveFeeError, WebhookDelivery,
+        WebhookRepository, WebhookSubscription, Withdraw, WithdrawalLimits,
     },
+    scheduler::Reconciler,
 };
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    routing::{get, post},
-    Json, Router,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{
+        sse::{Event, Sse},
+        IntoResponse, Response,
+    },
+    routing::{delete, get, post, put},
+    BoxError, Json, Router,
 };
-use error_ext::{axum::Error, StdErrorExt};
+use error_ext::StdErrorExt;
 use eventsourced::{
     binarize::serde_json::SerdeJsonBinarize, event_log::EventLog,
-    snapshot_store::noop::NoopSnapshotStore, EntityRef, EventSourcedExt,
+    snapshot_store::noop::NoopSnapshotStore, EntityRef, EventSourced, EventSourcedExt,
 };
-use futures::TryStreamExt;
+use futures::{future, Stream, StreamExt, TryStreamExt};
+use reqwest::Url;
 use serde::{Deserialize, Serialize};
-use std::num::NonZeroUsize;
-use tracing::{error, instrument};
-use utoipa::{OpenApi, ToSchema};
+use serde_with::{serde_as, DisplayFromStr};
+use std::{
+    num::{NonZeroU64, NonZeroUsize},
+    pin::pin,
+};
+use thiserror::Error;
+use time::{Date, OffsetDateTime};
+use tracing::{error, instrument, warn};
+use utoipa::{IntoParams, OpenApi, ToSchema};
 use uuid::Uuid;
 
 #[derive(OpenApi)]
 #[openapi(
-    paths(list_accounts, create_accounts, deposit, withdraw),
-    components(schemas(Error, ListAccountsResponse, Account, DepositRequest, WithdrawRequest))
+    paths(
+        list_products,
+        list_accounts,
+        create_accounts,
+        get_account,
+        stream_account_events,
+        stream_all_account_events,
+        deposit,
+        withdraw,
+        set_withdrawal_limits,
+        set_account_status,
+        get_holds,
+        place_hold,
+        capture_hold,
+        release_hold,
+        list_transactions,
+        get_statement,
+        reverse,
+        waive_fee,
+        list_standing_orders,
+        create_standing_order,
+        update_standing_order,
+        cancel_standing_order,
+        get_trial_balance,
+        reconcile,
+        list_api_keys,
+        issue_api_key,
+        revoke_api_key,
+        rotate_api_key,
+        list_webhook_subscriptions,
+        create_webhook_subscription,
+        delete_webhook_subscription,
+        list_dead_webhook_deliveries,
+        list_customers,
+        create_customer,
+        get_customer,
+        list_customer_accounts,
+        change_contact,
+        set_kyc_status
+    ),
+    components(schemas(
+        Problem,
+        InvalidParam,
+        ListProductsResponse,
+        Product,
+        Operation,
+        DayCount,
+        FeeSchedule,
+        ListAccountsResponse,
+        Account,
+        CreateAccountRequest,
+        DepositRequest,
+        WithdrawRequest,
+        WithdrawalLimits,
+        AccountStatus,
+        SetAccountStatusRequest,
+        Holds,
+        Hold,
+        PlaceHoldRequest,
+        ListTransactionsResponse,
+        Transaction,
+        TransactionKind,
+        Statement,
+        StatementFormat,
+        ReverseRequest,
+        WaiveFeeRequest,
+        ListStandingOrdersResponse,
+        StandingOrder,
+        StandingOrderStatus,
+        ExecutionFailure,
+        CreateStandingOrderRequest,
+        UpdateStandingOrderRequest,
+        TrialBalance,
+        LedgerAccountBalance,
+        LedgerAccount,
+        ReconcileRequest,
+        Reconciliation,
+        Drift,
+        ListApiKeysResponse,
+        ApiKey,
+        ApiKeyScope,
+        IssueApiKeyRequest,
+        IssuedApiKey,
+        ListWebhookSubscriptionsResponse,
+        WebhookSubscription,
+        CreateWebhookSubscriptionRequest,
+        CreatedWebhookSubscription,
+        ListDeadWebhookDeliveriesResponse,
+        WebhookDelivery,
+        ListCustomersResponse,
+        Customer,
+        KycStatus,
+        CreateCustomerRequest,
+        ChangeContactRequest,
+        SetKycStatusRequest
+    ))
 )]
 pub struct ApiDoc;
 
-pub fn app<R, E>() -> Router<AppState<R, E>>
+pub fn app<R, C, S, K, W, E>() -> Router<AppState<R, C, S, K, W, E>>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    E: EventLog<Id = Uuid> + Sync,
+{
+    routes().route("/accounts/:id", get(get_account))
+}
+
+/// The routes shared with later versions, i.e. all but the ones superseded by those.
+pub fn routes<R, C, S, K, W, E>() -> Router<AppState<R, C, S, K, W, E>>
 where
     R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
     E: EventLog<Id = Uuid> + Sync,
 {
     Router::new()
+        .route("/products", get(list_products))
         .route("/accounts", get(list_accounts).post(create_accounts))
+        .route("/accounts/:id/events", get(stream_account_events))
+        .route("/account-events", get(stream_all_account_events))
+        .route("/ws", get(ws::connect))
+        .route("/graphql", post(graphql::execute))
+        .route("/graphql/ws", get(graphql::subscribe))
         .route("/accounts/:id/deposits", post(deposit))
         .route("/accounts/:id/withdrawals", post(withdraw))
+        .route(
+            "/accounts/:id/withdrawal-limits",
+            put(set_withdrawal_limits),
+        )
+        .route("/accounts/:id/status", put(set_account_status))
+        .route("/accounts/:id/holds", get(get_holds).post(place_hold))
+        .route("/accounts/:id/holds/:hold_id/capture", post(capture_hold))
+        .route("/accounts/:id/holds/:hold_id/release", post(release_hold))
+        .route("/accounts/:id/transactions", get(list_transactions))
+        .route("/accounts/:id/statements", get(get_statement))
+        .route("/accounts/:id/reversals", post(reverse))
+        .route("/accounts/:id/fee-waivers", post(waive_fee))
+        .route(
+            "/accounts/:id/standing-orders",
+            get(list_standing_orders).post(create_standing_order),
+        )
+        .route(
+            "/accounts/:id/standing-orders/:standing_order_id",
+            put(update_standing_order).delete(cancel_standing_order),
+        )
+        .route("/ledger/trial-balance", get(get_trial_balance))
+        .route("/reconciliations", post(reconcile))
+        .route("/api-keys", get(list_api_keys).post(issue_api_key))
+        .route("/api-keys/:id", delete(revoke_api_key))
+        .route("/api-keys/:id/rotation", post(rotate_api_key))
+        .route(
+            "/webhooks",
+            get(list_webhook_subscriptions).post(create_webhook_subscription),
+        )
+        .route("/webhooks/:id", delete(delete_webhook_subscription))
+        .route("/webhooks/dead-letters", get(list_dead_webhook_deliveries))
+        .route("/customers", get(list_customers).post(create_customer))
+        .route("/customers/:id", get(get_customer))
+        .route("/customers/:id/accounts", get(list_customer_accounts))
+        .route("/customers/:id/contact", put(change_contact))
+        .route("/customers/:id/kyc-status", put(set_kyc_status))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct ListProductsResponse {
+    products: Vec<Product>,
+}
+
+/// List the products accounts can be created for.
+#[utoipa::path(
+    get,
+    path = "/products",
+    responses(
+        (status = 200, description = "A list of products.", body = ListProductsResponse),
+    ),
+    tag = "account",
+)]
+#[instrument(skip(app_state))]
+async fn list_products<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<Anyone>,
+) -> Json<ListProductsResponse>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog,
+{
+    let products = app_state.products.products().to_vec();
+    Json(ListProductsResponse { products })
 }
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -46,7 +260,7 @@ struct ListAccountsResponse {
     accounts: Vec<Account>,
 }
 
-/// List accounts.
+/// List accounts; customers only get their own ones.
 #[utoipa::path(
     get,
     path = "/accounts",
@@ -56,63 +270,431 @@ struct ListAccountsResponse {
     tag = "account",
 )]
 #[instrument(skip(app_state))]
-async fn list_accounts<R, L>(
-    State(app_state): State<AppState<R, L>>,
-) -> Result<Json<ListAccountsResponse>, Error>
+async fn list_accounts<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    principal: Authorized<Anyone>,
+) -> Result<Json<ListAccountsResponse>, Problem>
 where
     R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
     L: EventLog,
 {
-    let accounts = app_state
-        .account_repository
-        .accounts()
-        .await
-        .map_err(|error| {
-            error!(error = error.as_chain(), "cannot list accounts");
-            Error::Internal
-        })?;
+    let principal = principal.0;
 
-    let accounts = accounts.try_collect::<Vec<_>>().await.map_err(|error| {
-        error!(error = error.as_chain(), "cannot list accounts");
-        Error::Internal
-    })?;
+    // Customers only see their own accounts.
+    let accounts = if principal.has_role(Role::Admin) || principal.has_role(Role::Teller) {
+        app_state
+            .account_repository
+            .accounts()
+            .await
+            .map_err(|error| {
+                error!(error = error.as_chain(), "cannot list accounts");
+                Problem::internal()
+            })?
+            .try_collect::<Vec<_>>()
+            .await
+            .map_err(|error| {
+                error!(error = error.as_chain(), "cannot list accounts");
+                Problem::internal()
+            })?
+    } else {
+        let Some(customer_id) = principal.customer_id() else {
+            return Ok(Json(ListAccountsResponse { accounts: vec![] }));
+        };
+        app_state
+            .customer_repository
+            .accounts(customer_id)
+            .await
+            .map_err(|error| {
+                error!(error = error.as_chain(), "cannot list accounts");
+                Problem::internal()
+            })?
+            .try_collect::<Vec<_>>()
+            .await
+            .map_err(|error| {
+                error!(error = error.as_chain(), "cannot list accounts");
+                Problem::internal()
+            })?
+    };
 
     Ok(Json(ListAccountsResponse { accounts }))
 }
 
+#[derive(Debug, Deserialize, ToSchema)]
+struct CreateAccountRequest {
+    /// The IDs of the customers holding the account.
+    holders: Vec<Uuid>,
+
+    /// The name of the product.
+    product: String,
+}
+
 /// Create an account.
 #[utoipa::path(
     post,
     path = "/accounts",
     responses(
         (status = 201, description = "The created account", body = Account),
-        (status = 409, description = "An account with the created ID already exists", body = Error),
+        (status = 409, description = "An account with the created ID already exists", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "The product is unknown, no holders are given or a holder cannot be found", body = Problem, content_type = "application/problem+json"),
     ),
     tag = "account",
 )]
 #[instrument(skip(app_state))]
-async fn create_accounts<R, L>(
-    State(app_state): State<AppState<R, L>>,
-) -> Result<(StatusCode, Json<Account>), Error>
+async fn create_accounts<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<Teller>,
+    Json(CreateAccountRequest { holders, product }): Json<CreateAccountRequest>,
+) -> Result<(StatusCode, Json<Account>), Problem>
 where
     R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog<Id = Uuid>,
+{
+    handle_create_account(&app_state, holders, product)
+        .await
+        .map(|account| (StatusCode::CREATED, Json(account)))
+}
+
+/// Handle a `CreateAccount` command for a new ID after validating the holders and the product,
+/// shared with the gRPC API.
+pub(super) async fn handle_create_account<R, C, S, K, W, L>(
+    app_state: &AppState<R, C, S, K, W, L>,
+    holders: Vec<Uuid>,
+    product: String,
+) -> Result<Account, Problem>
+where
+    C: CustomerRepository,
     L: EventLog<Id = Uuid>,
 {
+    let product = app_state
+        .products
+        .product(&product)
+        .cloned()
+        .ok_or_else(|| Problem::invalid_entity(UnknownProduct(product)))?;
+
+    let existing_customers = app_state
+        .customer_repository
+        .existing_customers(&holders)
+        .await
+        .map_err(|error| {
+            error!(error = error.as_chain(), "cannot get customers");
+            Problem::internal()
+        })?;
+    if let Some(&holder) = holders
+        .iter()
+        .find(|holder| !existing_customers.contains(holder))
+    {
+        return Err(Problem::invalid_entity(UnknownCustomer(holder)));
+    }
+
     let account = spawn_account_entity(Uuid::now_v7(), app_state.event_log.clone()).await?;
     account
-        .handle_command(CreateAccount)
+        .handle_command(CreateAccount::new(holders, product))
         .await
         .map_err(|error| {
             error!(
                 error = error.as_chain(),
                 "cannot handle CreateAccount command"
             );
-            Error::Internal
+            Problem::internal()
         })?
-        .map_err(|error| match error {
-            CreateAccountError::AlreadyExisting(_) => Error::conflict(error),
+        .map_err(Problem::from)
+}
+
+#[serde_as]
+#[derive(Debug, Deserialize, IntoParams)]
+pub(super) struct GetAccountParams {
+    /// Replay the events of the account up to this point: either the sequence number of an event
+    /// or an RFC 3339 timestamp.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    #[param(value_type = Option<String>)]
+    pub(super) as_of: Option<AsOf>,
+}
+
+/// Get an account, either its current state or its state as of a sequence number or timestamp,
+/// e.g. for auditing.
+#[utoipa::path(
+    get,
+    path = "/accounts/{id}",
+    params(GetAccountParams),
+    responses(
+        (status = 200, description = "The account", body = Account),
+        (status = 404, description = "An account with the given ID cannot be found", body = Problem, content_type = "application/problem+json"),
+    ),
+    tag = "account",
+)]
+#[instrument(skip(app_state))]
+async fn get_account<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<AccountHolder>,
+    Path(id): Path<Uuid>,
+    Query(GetAccountParams { as_of }): Query<GetAccountParams>,
+) -> Result<Json<Account>, Problem>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog<Id = Uuid>,
+{
+    let Some(as_of) = as_of else {
+        return app_state
+            .account_repository
+            .account(id)
+            .await
+            .map_err(|error| {
+                error!(error = error.as_chain(), "cannot get account");
+                Problem::internal()
+            })?
+            .ok_or_else(|| Problem::not_found(UnknownAccount(id)).with_account_id(id))
+            .map(Json);
+    };
+
+    let state = account_state(&app_state.event_log, id, as_of).await?;
+    Ok(Json(state.account(id)))
+}
+
+/// The state of the account with the given ID as of the given point, replayed from its events.
+pub(super) async fn account_state<L>(
+    event_log: &L,
+    id: Uuid,
+    as_of: AsOf,
+) -> Result<AccountState, Problem>
+where
+    L: EventLog<Id = Uuid>,
+{
+    let last_seq_no = event_log
+        .last_seq_no(AccountEntity::TYPE_NAME, &id)
+        .await
+        .map_err(|error| {
+            error!(error = error.as_chain(), "cannot get last sequence number");
+            Problem::internal()
+        })?
+        .ok_or_else(|| Problem::not_found(UnknownAccount(id)).with_account_id(id))?;
+
+    let events = event_log
+        .events_by_id::<AccountEvent, _, _>(
+            AccountEntity::TYPE_NAME,
+            &id,
+            NonZeroU64::MIN,
+            |bytes| serde_json::from_slice(&bytes),
+        )
+        .await
+        .map_err(|error| {
+            error!(error = error.as_chain(), "cannot get account events");
+            Problem::internal()
+        })?;
+
+    let account = replay_account(events, last_seq_no, as_of)
+        .await
+        .map_err(|error| {
+            error!(error = error.as_chain(), "cannot replay account events");
+            Problem::internal()
+        })?;
+
+    match account {
+        AccountEntity::Nonexistent => {
+            Err(Problem::not_found(UnknownAccount(id)).with_account_id(id))
+        }
+        AccountEntity::Existing(state) => Ok(state),
+    }
+}
+
+/// All events of the account with the given ID up to the last one, from the event log.
+async fn account_events<L>(event_log: &L, id: Uuid) -> Result<Vec<AccountEvent>, Problem>
+where
+    L: EventLog<Id = Uuid>,
+{
+    let last_seq_no = event_log
+        .last_seq_no(AccountEntity::TYPE_NAME, &id)
+        .await
+        .map_err(|error| {
+            error!(error = error.as_chain(), "cannot get last sequence number");
+            Problem::internal()
+        })?
+        .ok_or_else(|| Problem::not_found(UnknownAccount(id)).with_account_id(id))?;
+
+    let events = event_log
+        .events_by_id::<AccountEvent, _, _>(
+            AccountEntity::TYPE_NAME,
+            &id,
+            NonZeroU64::MIN,
+            |bytes| serde_json::from_slice(&bytes),
+        )
+        .await
+        .map_err(|error| {
+            error!(error = error.as_chain(), "cannot get account events");
+            Problem::internal()
+        })?;
+
+    // The stream does not end with the last event, but waits for further ones.
+    let mut events = pin!(events);
+    let mut account_events = vec![];
+    while let Some(event) = events.next().await {
+        let (seq_no, event) = event.map_err(|error| {
+            error!(error = error.as_chain(), "cannot get account events");
+            Problem::internal()
+        })?;
+        account_events.push(event);
+        if seq_no >= last_seq_no {
+            break;
+        }
+    }
+
+    Ok(account_events)
+}
+
+/// The current balance of the account with the given ID and then every change of it, shared with
+/// the WebSocket and gRPC APIs. The history is replayed from the start, because only some events
+/// carry the balance.
+pub(super) async fn balances<L>(
+    event_log: L,
+    id: Uuid,
+) -> Result<impl Stream<Item = Result<i64, L::Error>> + Send, Problem>
+where
+    L: EventLog<Id = Uuid>,
+{
+    let last_seq_no = event_log
+        .last_seq_no(AccountEntity::TYPE_NAME, &id)
+        .await
+        .map_err(|error| {
+            error!(error = error.as_chain(), "cannot get last sequence number");
+            Problem::internal()
+        })?
+        .ok_or_else(|| Problem::not_found(UnknownAccount(id)).with_account_id(id))?;
+
+    let events = event_log
+        .events_by_id::<AccountEvent, _, _>(
+            AccountEntity::TYPE_NAME,
+            &id,
+            NonZeroU64::MIN,
+            |bytes| serde_json::from_slice(&bytes),
+        )
+        .await
+        .map_err(|error| {
+            error!(error = error.as_chain(), "cannot get account events");
+            Problem::internal()
+        })?;
+
+    let balances = events
+        .scan(0, move |balance, event| {
+            let balance = event.map(|(seq_no, event)| {
+                let changed = match event.balance() {
+                    Some(new_balance) => {
+                        *balance = new_balance;
+                        true
+                    }
+                    None => false,
+                };
+                // Replay silently up to the last event, then emit every change.
+                (seq_no == last_seq_no || (seq_no > last_seq_no && changed)).then_some(*balance)
+            });
+            future::ready(Some(balance))
         })
-        .map(|account| (StatusCode::CREATED, Json(account)))
+        .try_filter_map(|balance| future::ready(Ok(balance)));
+    Ok(balances)
+}
+
+/// Stream the events of an account as server-sent events, resuming after the one given by the
+/// `Last-Event-ID` header, if any.
+#[utoipa::path(
+    get,
+    path = "/accounts/{id}/events",
+    responses(
+        (status = 200, description = "A stream of account events", body = String, content_type = "text/event-stream"),
+        (status = 400, description = "Invalid Last-Event-ID header", body = Problem, content_type = "application/problem+json"),
+        (status = 404, description = "An account with the given ID cannot be found", body = Problem, content_type = "application/problem+json"),
+    ),
+    tag = "account",
+)]
+#[instrument(skip(app_state, headers))]
+async fn stream_account_events<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<AccountHolder>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, BoxError>>>, Problem>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog<Id = Uuid>,
+{
+    let from = sse::resume_from(&headers)?;
+
+    let event_log = app_state.event_log;
+    event_log
+        .last_seq_no(AccountEntity::TYPE_NAME, &id)
+        .await
+        .map_err(|error| {
+            error!(error = error.as_chain(), "cannot get last sequence number");
+            Problem::internal()
+        })?
+        .ok_or_else(|| Problem::not_found(UnknownAccount(id)).with_account_id(id))?;
+
+    let events = event_log
+        .events_by_id::<AccountEvent, _, _>(AccountEntity::TYPE_NAME, &id, from, |bytes| {
+            serde_json::from_slice(&bytes)
+        })
+        .await
+        .map_err(|error| {
+            error!(error = error.as_chain(), "cannot get account events");
+            Problem::internal()
+        })?;
+
+    Ok(sse::account_events(events))
+}
+
+/// Stream the events of all accounts as server-sent events, resuming after the one given by the
+/// `Last-Event-ID` header, if any.
+#[utoipa::path(
+    get,
+    path = "/account-events",
+    responses(
+        (status = 200, description = "A stream of account events", body = String, content_type = "text/event-stream"),
+        (status = 400, description = "Invalid Last-Event-ID header", body = Problem, content_type = "application/problem+json"),
+    ),
+    tag = "account",
+)]
+#[instrument(skip(app_state, headers))]
+async fn stream_all_account_events<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<Teller>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, BoxError>>>, Problem>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog<Id = Uuid>,
+{
+    let from = sse::resume_from(&headers)?;
+
+    let events = app_state
+        .event_log
+        .events_by_type::<AccountEvent, _, _>(AccountEntity::TYPE_NAME, from, |bytes| {
+            serde_json::from_slice(&bytes)
+        })
+        .await
+        .map_err(|error| {
+            error!(error = error.as_chain(), "cannot get account events");
+            Problem::internal()
+        })?;
+
+    Ok(sse::account_events(events))
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
@@ -120,41 +702,55 @@ struct DepositRequest {
     amount: u64,
 }
 
-/// Creates a deposit.
+/// Creates a deposit; tellers only, as customers must not credit their own accounts.
 #[utoipa::path(
     post,
     path = "/accounts/{id}/deposits",
     responses(
         (status = 200, description = "The updated account", body = Account),
-        (status = 404, description = "An account with the given ID cannot be found", body = Error),
+        (status = 404, description = "An account with the given ID cannot be found", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "The product does not allow deposits", body = Problem, content_type = "application/problem+json"),
     ),
     tag = "account",
 )]
 #[instrument(skip(app_state))]
-async fn deposit<R, L>(
-    State(app_state): State<AppState<R, L>>,
+async fn deposit<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<Teller>,
     Path(id): Path<Uuid>,
     Json(DepositRequest { amount }): Json<DepositRequest>,
-) -> Result<Json<Account>, Error>
+) -> Result<Json<Account>, Problem>
 where
     R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
     L: EventLog<Id = Uuid>,
 {
-    let account = spawn_account_entity(id, app_state.event_log.clone()).await?;
+    handle_deposit(app_state.event_log.clone(), id, amount)
+        .await
+        .map(Json)
+}
+
+/// Handle a `Deposit` command, shared with the WebSocket API.
+pub(super) async fn handle_deposit<L>(
+    event_log: L,
+    id: Uuid,
+    amount: u64,
+) -> Result<Account, Problem>
+where
+    L: EventLog<Id = Uuid>,
+{
+    let account = spawn_account_entity(id, event_log).await?;
     account
-        .handle_command(Deposit::from(amount))
+        .handle_command(Deposit::new(amount, OffsetDateTime::now_utc()))
         .await
         .map_err(|error| {
-            error!(
-                error = error.as_chain(),
-                "cannot handle CreateAccount command"
-            );
-            Error::Internal
+            error!(error = error.as_chain(), "cannot handle Deposit command");
+            Problem::internal()
         })?
-        .map_err(|error| match error {
-            DepositError::NotFound(_) => Error::not_found(error),
-        })
-        .map(Json)
+        .map_err(Problem::from)
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
@@ -168,57 +764,1678 @@ struct WithdrawRequest {
     path = "/accounts/{id}/withdrawals",
     responses(
         (status = 200, description = "The updated account", body = Account),
-        (status = 404, description = "An account with the given ID cannot be found", body = Error),
-        (status = 422, description = "An account with the given ID cannot be found", body = Error),
+        (status = 404, description = "An account with the given ID cannot be found", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "The product does not allow withdrawals, the balance is insufficient for the amount and fees or a withdrawal limit would be exceeded", body = Problem, content_type = "application/problem+json"),
     ),
     tag = "account",
 )]
 #[instrument(skip(app_state))]
-async fn withdraw<R, L>(
-    State(app_state): State<AppState<R, L>>,
+async fn withdraw<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<AccountHolder>,
     Path(id): Path<Uuid>,
     Json(WithdrawRequest { amount }): Json<WithdrawRequest>,
-) -> Result<Json<Account>, Error>
+) -> Result<Json<Account>, Problem>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog<Id = Uuid>,
+{
+    handle_withdraw(app_state.event_log.clone(), id, amount)
+        .await
+        .map(Json)
+}
+
+/// Handle a `Withdraw` command, shared with the WebSocket API.
+pub(super) async fn handle_withdraw<L>(
+    event_log: L,
+    id: Uuid,
+    amount: u64,
+) -> Result<Account, Problem>
+where
+    L: EventLog<Id = Uuid>,
+{
+    let account = spawn_account_entity(id, event_log).await?;
+    account
+        .handle_command(Withdraw::new(amount, OffsetDateTime::now_utc()))
+        .await
+        .map_err(|error| {
+            error!(error = error.as_chain(), "cannot handle Withdraw command");
+            Problem::internal()
+        })?
+        .map(|reply| reply.account)
+        .map_err(Problem::from)
+}
+
+/// Sets the daily and monthly withdrawal limits; missing limits mean unlimited. Tellers only.
+#[utoipa::path(
+    put,
+    path = "/accounts/{id}/withdrawal-limits",
+    request_body = WithdrawalLimits,
+    responses(
+        (status = 200, description = "The updated withdrawal limits", body = WithdrawalLimits),
+        (status = 404, description = "An account with the given ID cannot be found", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "The daily limit exceeds the monthly limit", body = Problem, content_type = "application/problem+json"),
+    ),
+    tag = "account",
+)]
+#[instrument(skip(app_state))]
+async fn set_withdrawal_limits<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<Teller>,
+    Path(id): Path<Uuid>,
+    Json(limits): Json<WithdrawalLimits>,
+) -> Result<Json<WithdrawalLimits>, Problem>
 where
     R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
     L: EventLog<Id = Uuid>,
 {
     let account = spawn_account_entity(id, app_state.event_log.clone()).await?;
     account
-        .handle_command(Withdraw::from(amount))
+        .handle_command(SetWithdrawalLimits::from(limits))
         .await
         .map_err(|error| {
             error!(
                 error = error.as_chain(),
-                "cannot handle CreateAccount command"
+                "cannot handle SetWithdrawalLimits command"
             );
-            Error::Internal
+            Problem::internal()
         })?
         .map_err(|error| match error {
-            WithdrawError::NotFound(_) => Error::not_found(error),
-            WithdrawError::InsufficientBalance(_) => Error::invalid_entity(error),
+            SetWithdrawalLimitsError::NotFound(_) => Problem::not_found(error),
+            SetWithdrawalLimitsError::DailyExceedsMonthly(_) => Problem::invalid_entity(error),
         })
         .map(Json)
 }
 
-// In the real-world, entities would be cached.
-async fn spawn_account_entity<L>(id: Uuid, event_log: L) -> Result<EntityRef<AccountEntity>, Error>
+#[derive(Debug, Deserialize, ToSchema)]
+struct SetAccountStatusRequest {
+    status: AccountStatus,
+}
+
+/// Freezes, unfreezes or closes an account; closing is final and requires a zero balance. Admins
+/// only.
+#[utoipa::path(
+    put,
+    path = "/accounts/{id}/status",
+    responses(
+        (status = 200, description = "The updated status", body = AccountStatus),
+        (status = 404, description = "An account with the given ID cannot be found", body = Problem, content_type = "application/problem+json"),
+        (status = 409, description = "The account has already been closed", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "The balance of the account to be closed is not zero", body = Problem, content_type = "application/problem+json"),
+    ),
+    tag = "account",
+)]
+#[instrument(skip(app_state))]
+async fn set_account_status<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<Admin>,
+    Path(id): Path<Uuid>,
+    Json(SetAccountStatusRequest { status }): Json<SetAccountStatusRequest>,
+) -> Result<Json<AccountStatus>, Problem>
 where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
     L: EventLog<Id = Uuid>,
 {
-    AccountEntity::default()
-        .entity()
-        .spawn(
-            id,
-            None,
-            NonZeroUsize::MIN,
-            event_log,
-            NoopSnapshotStore::default(),
-            SerdeJsonBinarize,
-        )
+    let account = spawn_account_entity(id, app_state.event_log.clone()).await?;
+    account
+        .handle_command(SetAccountStatus::new(status, OffsetDateTime::now_utc()))
         .await
         .map_err(|error| {
-            error!(error = error.as_chain(), "cannot spawn AccountEntity");
-            Error::Internal
+            error!(
+                error = error.as_chain(),
+                "cannot handle SetAccountStatus command"
+            );
+            Problem::internal()
+        })?
+        .map_err(|error| match error {
+            SetAccountStatusError::NotFound(_) => Problem::not_found(error),
+            SetAccountStatusError::Closed(_) => Problem::conflict(error),
+            SetAccountStatusError::NonZeroBalance(_) => Problem::invalid_entity(error),
+        })
+        .map(Json)
+}
+
+/// Get the ledger and available balance and the holds which have not yet expired.
+#[utoipa::path(
+    get,
+    path = "/accounts/{id}/holds",
+    responses(
+        (status = 200, description = "The balances and open holds", body = Holds),
+        (status = 404, description = "An account with the given ID cannot be found", body = Problem, content_type = "application/problem+json"),
+    ),
+    tag = "account",
+)]
+#[instrument(skip(app_state))]
+async fn get_holds<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<AccountHolder>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Holds>, Problem>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog<Id = Uuid>,
+{
+    let account = spawn_account_entity(id, app_state.event_log.clone()).await?;
+    account
+        .handle_command(GetHolds::from(OffsetDateTime::now_utc()))
+        .await
+        .map_err(|error| {
+            error!(error = error.as_chain(), "cannot handle GetHolds command");
+            Problem::internal()
+        })?
+        .map_err(|error| match error {
+            GetHoldsError::NotFound(_) => Problem::not_found(error),
+        })
+        .map(Json)
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct PlaceHoldRequest {
+    amount: u64,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    expires_at: OffsetDateTime,
+}
+
+/// Places a hold, reserving the given amount of the available balance until it expires.
+#[utoipa::path(
+    post,
+    path = "/accounts/{id}/holds",
+    responses(
+        (status = 201, description = "The placed hold", body = Hold),
+        (status = 404, description = "An account with the given ID cannot be found", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "The product does not allow holds, the available balance is insufficient or the expiry is not in the future", body = Problem, content_type = "application/problem+json"),
+    ),
+    tag = "account",
+)]
+#[instrument(skip(app_state))]
+async fn place_hold<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<Teller>,
+    Path(id): Path<Uuid>,
+    Json(PlaceHoldRequest { amount, expires_at }): Json<PlaceHoldRequest>,
+) -> Result<(StatusCode, Json<Hold>), Problem>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog<Id = Uuid>,
+{
+    let account = spawn_account_entity(id, app_state.event_log.clone()).await?;
+    let place_hold = PlaceHold::new(
+        Uuid::now_v7(),
+        amount,
+        expires_at,
+        OffsetDateTime::now_utc(),
+    );
+    account
+        .handle_command(place_hold)
+        .await
+        .map_err(|error| {
+            error!(error = error.as_chain(), "cannot handle PlaceHold command");
+            Problem::internal()
+        })?
+        .map_err(|error| match error {
+            PlaceHoldError::NotFound(_) => Problem::not_found(error),
+            PlaceHoldError::NotAllowed(_) => Problem::invalid_entity(error),
+            PlaceHoldError::InsufficientBalance(_) => Problem::invalid_entity(error),
+            PlaceHoldError::InvalidExpiry(_) => Problem::invalid_entity(error),
+            PlaceHoldError::InvalidAmount(_) => Problem::invalid_entity(error),
+            PlaceHoldError::NotOpen(_) => Problem::invalid_entity(error),
+        })
+        .map(|hold| (StatusCode::CREATED, Json(hold)))
+}
+
+/// Captures a hold, withdrawing its amount.
+#[utoipa::path(
+    post,
+    path = "/accounts/{id}/holds/{hold_id}/capture",
+    responses(
+        (status = 200, description = "The updated account", body = Account),
+        (status = 404, description = "An account or hold with the given ID cannot be found", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "The hold has expired", body = Problem, content_type = "application/problem+json"),
+    ),
+    tag = "account",
+)]
+#[instrument(skip(app_state))]
+async fn capture_hold<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<Teller>,
+    Path((id, hold_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<Account>, Problem>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog<Id = Uuid>,
+{
+    let account = spawn_account_entity(id, app_state.event_log.clone()).await?;
+    account
+        .handle_command(CaptureHold::new(hold_id, OffsetDateTime::now_utc()))
+        .await
+        .map_err(|error| {
+            error!(
+                error = error.as_chain(),
+                "cannot handle CaptureHold command"
+            );
+            Problem::internal()
+        })?
+        .map_err(|error| match error {
+            CaptureHoldError::NotFound(_) => Problem::not_found(error),
+            CaptureHoldError::HoldNotFound { .. } => Problem::not_found(error),
+            CaptureHoldError::HoldExpired { .. } => Problem::invalid_entity(error),
+            CaptureHoldError::BalanceOverflow(_) => Problem::invalid_entity(error),
+            CaptureHoldError::NotOpen(_) => Problem::invalid_entity(error),
+        })
+        .map(Json)
+}
+
+/// Releases a hold, making its amount available again.
+#[utoipa::path(
+    post,
+    path = "/accounts/{id}/holds/{hold_id}/release",
+    responses(
+        (status = 204, description = "The hold has been released"),
+        (status = 404, description = "An account or hold with the given ID cannot be found", body = Problem, content_type = "application/problem+json"),
+    ),
+    tag = "account",
+)]
+#[instrument(skip(app_state))]
+async fn release_hold<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<Teller>,
+    Path((id, hold_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, Problem>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog<Id = Uuid>,
+{
+    let account = spawn_account_entity(id, app_state.event_log.clone()).await?;
+    account
+        .handle_command(ReleaseHold::from(hold_id))
+        .await
+        .map_err(|error| {
+            error!(
+                error = error.as_chain(),
+                "cannot handle ReleaseHold command"
+            );
+            Problem::internal()
+        })?
+        .map_err(|error| match error {
+            ReleaseHoldError::NotFound(_) => Problem::not_found(error),
+            ReleaseHoldError::HoldNotFound { .. } => Problem::not_found(error),
+        })
+        .map(|_| StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct ListTransactionsResponse {
+    transactions: Vec<Transaction>,
+}
+
+/// List the transactions of an account; reversals and reversed transactions reference each other
+/// by sequence number.
+#[utoipa::path(
+    get,
+    path = "/accounts/{id}/transactions",
+    responses(
+        (status = 200, description = "A list of transactions.", body = ListTransactionsResponse),
+    ),
+    tag = "account",
+)]
+#[instrument(skip(app_state))]
+async fn list_transactions<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<AccountHolder>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ListTransactionsResponse>, Problem>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog,
+{
+    let transactions = app_state
+        .account_repository
+        .transactions(id)
+        .await
+        .map_err(|error| {
+            error!(error = error.as_chain(), "cannot list transactions");
+            Problem::internal()
+        })?;
+
+    let transactions = transactions
+        .try_collect::<Vec<_>>()
+        .await
+        .map_err(|error| {
+            error!(error = error.as_chain(), "cannot list transactions");
+            Problem::internal()
+        })?;
+
+    Ok(Json(ListTransactionsResponse { transactions }))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+struct StatementParams {
+    /// The first day (UTC) of the statement.
+    #[param(value_type = String, format = Date)]
+    from: Date,
+
+    /// The last day (UTC) of the statement.
+    #[param(value_type = String, format = Date)]
+    to: Date,
+
+    #[serde(default)]
+    format: StatementFormat,
+}
+
+#[derive(Debug, Default, Clone, Copy, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+enum StatementFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+/// Get the statement of an account for a date range, built from the transactions; statements
+/// including today are reconciled against the current balance of the account. If transactions are
+/// missing, e.g. for accounts created before statements existed, the statement is replayed from
+/// the events of the account.
+#[utoipa::path(
+    get,
+    path = "/accounts/{id}/statements",
+    params(StatementParams),
+    responses(
+        (status = 200, description = "The statement as JSON or CSV", body = Statement),
+        (status = 404, description = "An account with the given ID cannot be found", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "The date range is invalid", body = Problem, content_type = "application/problem+json"),
+    ),
+    tag = "account",
+)]
+#[instrument(skip(app_state))]
+async fn get_statement<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<AccountHolder>,
+    Path(id): Path<Uuid>,
+    Query(StatementParams { from, to, format }): Query<StatementParams>,
+) -> Result<Response, Problem>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog<Id = Uuid>,
+{
+    if from > to {
+        let problem = Problem::invalid_entity(InvalidDateRange { from, to })
+            .with_account_id(id)
+            .with_invalid_param("to", "must not be before from");
+        return Err(problem);
+    }
+
+    let (account, statement) = app_state
+        .account_repository
+        .statement(id, from, to)
+        .await
+        .map_err(|error| {
+            error!(error = error.as_chain(), "cannot get statement");
+            Problem::internal()
+        })?
+        .ok_or_else(|| Problem::not_found(UnknownAccount(id)).with_account_id(id))?;
+
+    // Statements up to today must close with the balance of the account and a non-zero balance
+    // requires transactions.
+    let today = OffsetDateTime::now_utc().date();
+    let missing_transactions = (to >= today && statement.closing_balance != account.balance)
+        || (statement.transactions.is_empty()
+            && statement.opening_balance == 0
+            && account.balance != 0);
+    let statement = if missing_transactions {
+        warn!(
+            closing_balance = statement.closing_balance,
+            balance = account.balance,
+            "statement does not reconcile with account balance, replaying events"
+        );
+        let events = account_events(&app_state.event_log, id).await?;
+        let statement = Statement::replay(id, from, to, events);
+
+        // The projection itself might have drifted, which the reconciliation repairs.
+        if to >= today {
+            let replayed = Account {
+                balance: statement.closing_balance,
+                ..account.clone()
+            };
+            if let Some(drift) = Drift::detect(id, Some(&replayed), Some(&account)) {
+                warn!(?drift, "account projection has drifted");
+            }
+        }
+        statement
+    } else {
+        statement
+    };
+
+    let response = match format {
+        StatementFormat::Json => Json(statement).into_response(),
+        StatementFormat::Csv => {
+            ([(header::CONTENT_TYPE, "text/csv")], statement.to_csv()).into_response()
+        }
+    };
+    Ok(response)
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct ReverseRequest {
+    /// The sequence number of the deposit or withdrawal to be reversed.
+    transaction_ref: u64,
+}
+
+/// Reverses a deposit or withdrawal; the fees of a withdrawal are refunded unless already waived.
+#[utoipa::path(
+    post,
+    path = "/accounts/{id}/reversals",
+    responses(
+        (status = 200, description = "The updated account", body = Account),
+        (status = 404, description = "An account or transaction with the given ID cannot be found", body = Problem, content_type = "application/problem+json"),
+        (status = 409, description = "The transaction has already been reversed", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "The balance is insufficient for reversing a deposit", body = Problem, content_type = "application/problem+json"),
+    ),
+    tag = "account",
+)]
+#[instrument(skip(app_state))]
+async fn reverse<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<Teller>,
+    Path(id): Path<Uuid>,
+    Json(ReverseRequest { transaction_ref }): Json<ReverseRequest>,
+) -> Result<Json<Account>, Problem>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog<Id = Uuid>,
+{
+    let account = spawn_account_entity(id, app_state.event_log.clone()).await?;
+    account
+        .handle_command(Reverse::new(transaction_ref, OffsetDateTime::now_utc()))
+        .await
+        .map_err(|error| {
+            error!(error = error.as_chain(), "cannot handle Reverse command");
+            Problem::internal()
+        })?
+        .map_err(|error| match error {
+            ReverseError::NotFound(_) => Problem::not_found(error),
+            ReverseError::TransactionNotFound { .. } => Problem::not_found(error),
+            ReverseError::AlreadyReversed { .. } => Problem::conflict(error),
+            ReverseError::InsufficientBalance(_) => Problem::invalid_entity(error),
+            ReverseError::BalanceOverflow(_) => Problem::invalid_entity(error),
+        })
+        .map(Json)
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct WaiveFeeRequest {
+    /// The sequence number of the transaction the fees of which are to be waived.
+    transaction_ref: u64,
+}
+
+/// Waives the fees charged with a transaction.
+#[utoipa::path(
+    post,
+    path = "/accounts/{id}/fee-waivers",
+    responses(
+        (status = 200, description = "The updated account", body = Account),
+        (status = 404, description = "An account or transaction with fees with the given ID cannot be found", body = Problem, content_type = "application/problem+json"),
+        (status = 409, description = "The fees have already been waived", body = Problem, content_type = "application/problem+json"),
+    ),
+    tag = "account",
+)]
+#[instrument(skip(app_state))]
+async fn waive_fee<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<Admin>,
+    Path(id): Path<Uuid>,
+    Json(WaiveFeeRequest { transaction_ref }): Json<WaiveFeeRequest>,
+) -> Result<Json<Account>, Problem>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog<Id = Uuid>,
+{
+    let account = spawn_account_entity(id, app_state.event_log.clone()).await?;
+    account
+        .handle_command(WaiveFee::new(transaction_ref, OffsetDateTime::now_utc()))
+        .await
+        .map_err(|error| {
+            error!(error = error.as_chain(), "cannot handle WaiveFee command");
+            Problem::internal()
+        })?
+        .map_err(|error| match error {
+            WaiveFeeError::NotFound(_) => Problem::not_found(error),
+            WaiveFeeError::FeeNotFound { .. } => Problem::not_found(error),
+            WaiveFeeError::AlreadyWaived { .. } => Problem::conflict(error),
+            WaiveFeeError::BalanceOverflow(_) => Problem::invalid_entity(error),
+        })
+        .map(Json)
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct ListStandingOrdersResponse {
+    standing_orders: Vec<StandingOrder>,
+}
+
+/// List the standing orders of an account, including the last failure to execute a transfer.
+#[utoipa::path(
+    get,
+    path = "/accounts/{id}/standing-orders",
+    responses(
+        (status = 200, description = "A list of standing orders.", body = ListStandingOrdersResponse),
+    ),
+    tag = "standing-order",
+)]
+#[instrument(skip(app_state))]
+async fn list_standing_orders<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<AccountHolder>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ListStandingOrdersResponse>, Problem>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog,
+{
+    let standing_orders = app_state
+        .standing_order_repository
+        .standing_orders(id)
+        .await
+        .map_err(|error| {
+            error!(error = error.as_chain(), "cannot list standing orders");
+            Problem::internal()
+        })?;
+
+    let standing_orders = standing_orders
+        .try_collect::<Vec<_>>()
+        .await
+        .map_err(|error| {
+            error!(error = error.as_chain(), "cannot list standing orders");
+            Problem::internal()
+        })?;
+
+    Ok(Json(ListStandingOrdersResponse { standing_orders }))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct CreateStandingOrderRequest {
+    /// The ID of the account to transfer to.
+    payee_id: Uuid,
+
+    amount: u64,
+
+    /// Day of the month the transfer is due; for shorter months the last day.
+    day_of_month: u8,
+
+    /// The first possible due date, today if not given.
+    #[schema(value_type = Option<String>, format = Date)]
+    start: Option<Date>,
+}
+
+/// Create a standing order.
+#[utoipa::path(
+    post,
+    path = "/accounts/{id}/standing-orders",
+    responses(
+        (status = 201, description = "The created standing order", body = StandingOrder),
+        (status = 404, description = "An account with the given ID cannot be found", body = Problem, content_type = "application/problem+json"),
+        (status = 409, description = "A standing order with the created ID already exists", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "The payee account cannot be found, the day of month is invalid or the payee is the account itself", body = Problem, content_type = "application/problem+json"),
+    ),
+    tag = "standing-order",
+)]
+#[instrument(skip(app_state))]
+async fn create_standing_order<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<AccountHolder>,
+    Path(id): Path<Uuid>,
+    Json(CreateStandingOrderRequest {
+        payee_id,
+        amount,
+        day_of_month,
+        start,
+    }): Json<CreateStandingOrderRequest>,
+) -> Result<(StatusCode, Json<StandingOrder>), Problem>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog<Id = Uuid>,
+{
+    app_state
+        .account_repository
+        .account(id)
+        .await
+        .map_err(|error| {
+            error!(error = error.as_chain(), "cannot get account");
+            Problem::internal()
+        })?
+        .ok_or_else(|| Problem::not_found(UnknownAccount(id)).with_account_id(id))?;
+    app_state
+        .account_repository
+        .account(payee_id)
+        .await
+        .map_err(|error| {
+            error!(error = error.as_chain(), "cannot get payee account");
+            Problem::internal()
+        })?
+        .ok_or_else(|| {
+            Problem::invalid_entity(UnknownAccount(payee_id))
+                .with_account_id(id)
+                .with_invalid_param("payee_id", "must be the ID of an existing account")
+        })?;
+
+    let start = start.unwrap_or_else(|| OffsetDateTime::now_utc().date());
+
+    let standing_order =
+        spawn_standing_order_entity(Uuid::now_v7(), app_state.event_log.clone()).await?;
+    standing_order
+        .handle_command(CreateStandingOrder::new(
+            id,
+            payee_id,
+            amount,
+            day_of_month,
+            start,
+        ))
+        .await
+        .map_err(|error| {
+            error!(
+                error = error.as_chain(),
+                "cannot handle CreateStandingOrder command"
+            );
+            Problem::internal()
+        })?
+        .map_err(|error| match error {
+            CreateStandingOrderError::AlreadyExisting(_) => Problem::conflict(error),
+            CreateStandingOrderError::InvalidDayOfMonth(_) => Problem::invalid_entity(error),
+            CreateStandingOrderError::SameAccount(_) => Problem::invalid_entity(error),
+        })
+        .map(|standing_order| (StatusCode::CREATED, Json(standing_order)))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct UpdateStandingOrderRequest {
+    amount: u64,
+
+    /// Day of the month the transfer is due; for shorter months the last day.
+    day_of_month: u8,
+}
+
+/// Update a standing order.
+#[utoipa::path(
+    put,
+    path = "/accounts/{id}/standing-orders/{standing_order_id}",
+    responses(
+        (status = 200, description = "The updated standing order", body = StandingOrder),
+        (status = 404, description = "A standing order with the given ID cannot be found for the account", body = Problem, content_type = "application/problem+json"),
+        (status = 409, description = "The standing order has been cancelled", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "The day of month is invalid", body = Problem, content_type = "application/problem+json"),
+    ),
+    tag = "standing-order",
+)]
+#[instrument(skip(app_state))]
+async fn update_standing_order<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<AccountHolder>,
+    Path((id, standing_order_id)): Path<(Uuid, Uuid)>,
+    Json(UpdateStandingOrderRequest {
+        amount,
+        day_of_month,
+    }): Json<UpdateStandingOrderRequest>,
+) -> Result<Json<StandingOrder>, Problem>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog<Id = Uuid>,
+{
+    let today = OffsetDateTime::now_utc().date();
+
+    let standing_order =
+        spawn_standing_order_entity(standing_order_id, app_state.event_log.clone()).await?;
+    standing_order
+        .handle_command(UpdateStandingOrder::new(id, amount, day_of_month, today))
+        .await
+        .map_err(|error| {
+            error!(
+                error = error.as_chain(),
+                "cannot handle UpdateStandingOrder command"
+            );
+            Problem::internal()
+        })?
+        .map_err(|error| match error {
+            UpdateStandingOrderError::NotFound(_) => Problem::not_found(error),
+            UpdateStandingOrderError::Cancelled(_) => Problem::conflict(error),
+            UpdateStandingOrderError::InvalidDayOfMonth(_) => Problem::invalid_entity(error),
+        })
+        .map(Json)
+}
+
+/// Cancel a standing order; cancelling an already cancelled standing order has no effect.
+#[utoipa::path(
+    delete,
+    path = "/accounts/{id}/standing-orders/{standing_order_id}",
+    responses(
+        (status = 204, description = "The standing order has been cancelled"),
+        (status = 404, description = "A standing order with the given ID cannot be found for the account", body = Problem, content_type = "application/problem+json"),
+    ),
+    tag = "standing-order",
+)]
+#[instrument(skip(app_state))]
+async fn cancel_standing_order<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<AccountHolder>,
+    Path((id, standing_order_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, Problem>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog<Id = Uuid>,
+{
+    let standing_order =
+        spawn_standing_order_entity(standing_order_id, app_state.event_log.clone()).await?;
+    standing_order
+        .handle_command(CancelStandingOrder::from(id))
+        .await
+        .map_err(|error| {
+            error!(
+                error = error.as_chain(),
+                "cannot handle CancelStandingOrder command"
+            );
+            Problem::internal()
+        })?
+        .map_err(|error| match error {
+            CancelStandingOrderError::NotFound(_) => Problem::not_found(error),
+        })
+        .map(|_| StatusCode::NO_CONTENT)
+}
+
+/// Get the debit and credit totals per ledger account of the general ledger.
+#[utoipa::path(
+    get,
+    path = "/ledger/trial-balance",
+    responses(
+        (status = 200, description = "The trial balance", body = TrialBalance),
+    ),
+    tag = "ledger",
+)]
+#[instrument(skip(app_state))]
+async fn get_trial_balance<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<Admin>,
+) -> Result<Json<TrialBalance>, Problem>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog,
+{
+    app_state
+        .account_repository
+        .trial_balance()
+        .await
+        .map_err(|error| {
+            error!(error = error.as_chain(), "cannot get trial balance");
+            Problem::internal()
+        })
+        .map(Json)
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct ReconcileRequest {
+    /// Accounts to be reconciled in addition to the ones of the projection, e.g. to detect
+    /// missing rows.
+    #[serde(default)]
+    account_ids: Vec<Uuid>,
+
+    /// Whether to repair missing rows and wrong balances.
+    #[serde(default)]
+    repair: bool,
+}
+
+/// Reconcile the account projection with the event log by replaying the events of all accounts,
+/// reporting missing rows, wrong balances and orphan rows and optionally repairing them.
+#[utoipa::path(
+    post,
+    path = "/reconciliations",
+    responses(
+        (status = 200, description = "The reconciliation report", body = Reconciliation),
+    ),
+    tag = "ledger",
+)]
+#[instrument(skip(app_state))]
+async fn reconcile<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<Admin>,
+    Json(ReconcileRequest {
+        account_ids,
+        repair,
+    }): Json<ReconcileRequest>,
+) -> Result<Json<Reconciliation>, Problem>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog<Id = Uuid> + Sync,
+{
+    Reconciler::new(app_state.account_repository, app_state.event_log)
+        .reconcile(account_ids, repair)
+        .await
+        .map_err(|error| {
+            error!(error = format!("{error:#}"), "cannot reconcile accounts");
+            Problem::internal()
+        })
+        .map(Json)
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct ListApiKeysResponse {
+    api_keys: Vec<ApiKey>,
+}
+
+/// List the API keys, including revoked ones.
+#[utoipa::path(
+    get,
+    path = "/api-keys",
+    responses(
+        (status = 200, description = "A list of API keys", body = ListApiKeysResponse),
+    ),
+    tag = "api-key",
+)]
+#[instrument(skip(app_state))]
+async fn list_api_keys<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<Admin>,
+) -> Result<Json<ListApiKeysResponse>, Problem>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog,
+{
+    let api_keys = app_state
+        .api_key_repository
+        .api_keys()
+        .await
+        .map_err(|error| {
+            error!(error = error.as_chain(), "cannot list API keys");
+            Problem::internal()
+        })?;
+
+    Ok(Json(ListApiKeysResponse { api_keys }))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct IssueApiKeyRequest {
+    /// A name identifying the client.
+    name: String,
+
+    scopes: Vec<ApiKeyScope>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct IssuedApiKey {
+    api_key: ApiKey,
+
+    /// The key to be sent in the `X-Api-Key` header; it cannot be retrieved later.
+    key: String,
+}
+
+/// Issue an API key for a service-to-service client.
+#[utoipa::path(
+    post,
+    path = "/api-keys",
+    responses(
+        (status = 201, description = "The issued API key", body = IssuedApiKey),
+    ),
+    tag = "api-key",
+)]
+#[instrument(skip(app_state))]
+async fn issue_api_key<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<Admin>,
+    Json(IssueApiKeyRequest { name, scopes }): Json<IssueApiKeyRequest>,
+) -> Result<(StatusCode, Json<IssuedApiKey>), Problem>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog,
+{
+    let id = Uuid::now_v7();
+    let (key, hash) = generate_api_key(id);
+    let api_key = ApiKey {
+        id,
+        name,
+        scopes,
+        created_at: OffsetDateTime::now_utc(),
+        rotated_at: None,
+        revoked_at: None,
+    };
+
+    app_state
+        .api_key_repository
+        .insert_api_key(&api_key, hash)
+        .await
+        .map_err(|error| {
+            error!(error = error.as_chain(), "cannot insert API key");
+            Problem::internal()
+        })?;
+
+    Ok((StatusCode::CREATED, Json(IssuedApiKey { api_key, key })))
+}
+
+/// Revoke an API key; revoking an already revoked API key has no effect.
+#[utoipa::path(
+    delete,
+    path = "/api-keys/{id}",
+    responses(
+        (status = 204, description = "The API key has been revoked"),
+        (status = 404, description = "An API key with the given ID cannot be found", body = Problem, content_type = "application/problem+json"),
+    ),
+    tag = "api-key",
+)]
+#[instrument(skip(app_state))]
+async fn revoke_api_key<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<Admin>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, Problem>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog,
+{
+    app_state
+        .api_key_repository
+        .revoke_api_key(id, OffsetDateTime::now_utc())
+        .await
+        .map_err(|error| {
+            error!(error = error.as_chain(), "cannot revoke API key");
+            Problem::internal()
+        })?
+        .ok_or_else(|| Problem::not_found(UnknownApiKey(id)))
+        .map(|_| StatusCode::NO_CONTENT)
+}
+
+/// Rotate an API key, i.e. replace its secret; the old key is invalid immediately.
+#[utoipa::path(
+    post,
+    path = "/api-keys/{id}/rotation",
+    responses(
+        (status = 200, description = "The rotated API key", body = IssuedApiKey),
+        (status = 404, description = "A non-revoked API key with the given ID cannot be found", body = Problem, content_type = "application/problem+json"),
+    ),
+    tag = "api-key",
+)]
+#[instrument(skip(app_state))]
+async fn rotate_api_key<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<Admin>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<IssuedApiKey>, Problem>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog,
+{
+    let (key, hash) = generate_api_key(id);
+    let api_key = app_state
+        .api_key_repository
+        .rotate_api_key(id, hash, OffsetDateTime::now_utc())
+        .await
+        .map_err(|error| {
+            error!(error = error.as_chain(), "cannot rotate API key");
+            Problem::internal()
+        })?
+        .ok_or_else(|| Problem::not_found(UnknownApiKey(id)))?;
+
+    Ok(Json(IssuedApiKey { api_key, key }))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct ListWebhookSubscriptionsResponse {
+    subscriptions: Vec<WebhookSubscription>,
+}
+
+/// List the webhook subscriptions.
+#[utoipa::path(
+    get,
+    path = "/webhooks",
+    responses(
+        (status = 200, description = "A list of webhook subscriptions", body = ListWebhookSubscriptionsResponse),
+    ),
+    tag = "webhook",
+)]
+#[instrument(skip(app_state))]
+async fn list_webhook_subscriptions<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<Admin>,
+) -> Result<Json<ListWebhookSubscriptionsResponse>, Problem>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog,
+{
+    let subscriptions = app_state
+        .webhook_repository
+        .webhook_subscriptions()
+        .await
+        .map_err(|error| {
+            error!(
+                error = error.as_chain(),
+                "cannot list webhook subscriptions"
+            );
+            Problem::internal()
+        })?;
+
+    Ok(Json(ListWebhookSubscriptionsResponse { subscriptions }))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct CreateWebhookSubscriptionRequest {
+    /// The HTTP(S) URL the account events are POSTed to.
+    url: String,
+
+    /// Names of the account events to be pushed, e.g. `Deposited`; empty or missing means all.
+    #[serde(default)]
+    event_types: Vec<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct CreatedWebhookSubscription {
+    subscription: WebhookSubscription,
+
+    /// The secret for verifying the `X-Webhook-Signature` header; it cannot be retrieved later.
+    secret: String,
+}
+
+/// Subscribe an endpoint to account events.
+#[utoipa::path(
+    post,
+    path = "/webhooks",
+    request_body = CreateWebhookSubscriptionRequest,
+    responses(
+        (status = 201, description = "The created webhook subscription", body = CreatedWebhookSubscription),
+        (status = 422, description = "The URL or an event type is invalid", body = Problem, content_type = "application/problem+json"),
+    ),
+    tag = "webhook",
+)]
+#[instrument(skip(app_state))]
+async fn create_webhook_subscription<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<Admin>,
+    Json(CreateWebhookSubscriptionRequest { url, event_types }): Json<
+        CreateWebhookSubscriptionRequest,
+    >,
+) -> Result<(StatusCode, Json<CreatedWebhookSubscription>), Problem>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog,
+{
+    if !Url::parse(&url).is_ok_and(|url| matches!(url.scheme(), "http" | "https")) {
+        return Err(Problem::invalid_entity(InvalidWebhookUrl(url))
+            .with_invalid_param("url", "must be an HTTP(S) URL"));
+    }
+    if let Some(event_type) = event_types
+        .iter()
+        .find(|event_type| !AccountEvent::NAMES.contains(&event_type.as_str()))
+    {
+        return Err(
+            Problem::invalid_entity(UnknownEventType(event_type.to_owned()))
+                .with_invalid_param("event_types", "must be names of account events"),
+        );
+    }
+
+    let subscription = WebhookSubscription {
+        id: Uuid::now_v7(),
+        url,
+        event_types,
+        created_at: OffsetDateTime::now_utc(),
+    };
+    let secret = generate_webhook_secret();
+
+    app_state
+        .webhook_repository
+        .insert_webhook_subscription(&subscription, &secret)
+        .await
+        .map_err(|error| {
+            error!(
+                error = error.as_chain(),
+                "cannot insert webhook subscription"
+            );
+            Problem::internal()
+        })?;
+
+    let subscription = CreatedWebhookSubscription {
+        subscription,
+        secret,
+    };
+    Ok((StatusCode::CREATED, Json(subscription)))
+}
+
+/// Delete a webhook subscription together with its pending and dead deliveries.
+#[utoipa::path(
+    delete,
+    path = "/webhooks/{id}",
+    responses(
+        (status = 204, description = "The webhook subscription has been deleted"),
+        (status = 404, description = "A webhook subscription with the given ID cannot be found", body = Problem, content_type = "application/problem+json"),
+    ),
+    tag = "webhook",
+)]
+#[instrument(skip(app_state))]
+async fn delete_webhook_subscription<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<Admin>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, Problem>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog,
+{
+    let deleted = app_state
+        .webhook_repository
+        .delete_webhook_subscription(id)
+        .await
+        .map_err(|error| {
+            error!(
+                error = error.as_chain(),
+                "cannot delete webhook subscription"
+            );
+            Problem::internal()
+        })?;
+
+    if deleted {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(Problem::not_found(UnknownWebhookSubscription(id)))
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct ListDeadWebhookDeliveriesResponse {
+    deliveries: Vec<WebhookDelivery>,
+}
+
+/// List the webhook deliveries which have been given up after exhausting their attempts, newest
+/// first.
+#[utoipa::path(
+    get,
+    path = "/webhooks/dead-letters",
+    responses(
+        (status = 200, description = "A list of dead webhook deliveries", body = ListDeadWebhookDeliveriesResponse),
+    ),
+    tag = "webhook",
+)]
+#[instrument(skip(app_state))]
+async fn list_dead_webhook_deliveries<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<Admin>,
+) -> Result<Json<ListDeadWebhookDeliveriesResponse>, Problem>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog,
+{
+    let deliveries = app_state
+        .webhook_repository
+        .dead_webhook_deliveries()
+        .await
+        .map_err(|error| {
+            error!(
+                error = error.as_chain(),
+                "cannot list dead webhook deliveries"
+            );
+            Problem::internal()
+        })?;
+
+    Ok(Json(ListDeadWebhookDeliveriesResponse { deliveries }))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct ListCustomersResponse {
+    customers: Vec<Customer>,
+}
+
+/// List customers.
+#[utoipa::path(
+    get,
+    path = "/customers",
+    responses(
+        (status = 200, description = "A list of customers.", body = ListCustomersResponse),
+    ),
+    tag = "customer",
+)]
+#[instrument(skip(app_state))]
+async fn list_customers<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<Teller>,
+) -> Result<Json<ListCustomersResponse>, Problem>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog,
+{
+    let customers = app_state
+        .customer_repository
+        .customers()
+        .await
+        .map_err(|error| {
+            error!(error = error.as_chain(), "cannot list customers");
+            Problem::internal()
+        })?;
+
+    let customers = customers.try_collect::<Vec<_>>().await.map_err(|error| {
+        error!(error = error.as_chain(), "cannot list customers");
+        Problem::internal()
+    })?;
+
+    Ok(Json(ListCustomersResponse { customers }))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct CreateCustomerRequest {
+    name: String,
+    email: String,
+}
+
+/// Create a customer.
+#[utoipa::path(
+    post,
+    path = "/customers",
+    responses(
+        (status = 201, description = "The created customer", body = Customer),
+        (status = 409, description = "A customer with the created ID already exists", body = Problem, content_type = "application/problem+json"),
+    ),
+    tag = "customer",
+)]
+#[instrument(skip(app_state))]
+async fn create_customer<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<Teller>,
+    Json(CreateCustomerRequest { name, email }): Json<CreateCustomerRequest>,
+) -> Result<(StatusCode, Json<Customer>), Problem>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog<Id = Uuid>,
+{
+    let customer = spawn_customer_entity(Uuid::now_v7(), app_state.event_log.clone()).await?;
+    customer
+        .handle_command(CreateCustomer::new(name, email))
+        .await
+        .map_err(|error| {
+            error!(
+                error = error.as_chain(),
+                "cannot handle CreateCustomer command"
+            );
+            Problem::internal()
+        })?
+        .map_err(|error| match error {
+            CreateCustomerError::AlreadyExisting(_) => Problem::conflict(error),
+        })
+        .map(|customer| (StatusCode::CREATED, Json(customer)))
+}
+
+/// Get a customer.
+#[utoipa::path(
+    get,
+    path = "/customers/{id}",
+    responses(
+        (status = 200, description = "The customer", body = Customer),
+        (status = 404, description = "A customer with the given ID cannot be found", body = Problem, content_type = "application/problem+json"),
+    ),
+    tag = "customer",
+)]
+#[instrument(skip(app_state))]
+async fn get_customer<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<Myself>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Customer>, Problem>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog,
+{
+    app_state
+        .customer_repository
+        .customer(id)
+        .await
+        .map_err(|error| {
+            error!(error = error.as_chain(), "cannot get customer");
+            Problem::internal()
+        })?
+        .ok_or_else(|| Problem::not_found(UnknownCustomer(id)))
+        .map(Json)
+}
+
+/// List the accounts a customer is a holder of.
+#[utoipa::path(
+    get,
+    path = "/customers/{id}/accounts",
+    responses(
+        (status = 200, description = "A list of accounts.", body = ListAccountsResponse),
+    ),
+    tag = "customer",
+)]
+#[instrument(skip(app_state))]
+async fn list_customer_accounts<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<Myself>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ListAccountsResponse>, Problem>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog,
+{
+    let accounts = app_state
+        .customer_repository
+        .accounts(id)
+        .await
+        .map_err(|error| {
+            error!(error = error.as_chain(), "cannot list accounts of customer");
+            Problem::internal()
+        })?;
+
+    let accounts = accounts.try_collect::<Vec<_>>().await.map_err(|error| {
+        error!(error = error.as_chain(), "cannot list accounts of customer");
+        Problem::internal()
+    })?;
+
+    Ok(Json(ListAccountsResponse { accounts }))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct ChangeContactRequest {
+    email: String,
+}
+
+/// Change the contact of a customer.
+#[utoipa::path(
+    put,
+    path = "/customers/{id}/contact",
+    responses(
+        (status = 200, description = "The updated customer", body = Customer),
+        (status = 404, description = "A customer with the given ID cannot be found", body = Problem, content_type = "application/problem+json"),
+    ),
+    tag = "customer",
+)]
+#[instrument(skip(app_state))]
+async fn change_contact<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<Myself>,
+    Path(id): Path<Uuid>,
+    Json(ChangeContactRequest { email }): Json<ChangeContactRequest>,
+) -> Result<Json<Customer>, Problem>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog<Id = Uuid>,
+{
+    let customer = spawn_customer_entity(id, app_state.event_log.clone()).await?;
+    customer
+        .handle_command(ChangeContact::from(email))
+        .await
+        .map_err(|error| {
+            error!(
+                error = error.as_chain(),
+                "cannot handle ChangeContact command"
+            );
+            Problem::internal()
+        })?
+        .map_err(|error| match error {
+            ChangeContactError::NotFound(_) => Problem::not_found(error),
+        })
+        .map(Json)
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct SetKycStatusRequest {
+    kyc_status: KycStatus,
+}
+
+/// Set the KYC status of a customer.
+#[utoipa::path(
+    put,
+    path = "/customers/{id}/kyc-status",
+    responses(
+        (status = 200, description = "The updated customer", body = Customer),
+        (status = 404, description = "A customer with the given ID cannot be found", body = Problem, content_type = "application/problem+json"),
+    ),
+    tag = "customer",
+)]
+#[instrument(skip(app_state))]
+async fn set_kyc_status<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<Teller>,
+    Path(id): Path<Uuid>,
+    Json(SetKycStatusRequest { kyc_status }): Json<SetKycStatusRequest>,
+) -> Result<Json<Customer>, Problem>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog<Id = Uuid>,
+{
+    let customer = spawn_customer_entity(id, app_state.event_log.clone()).await?;
+    customer
+        .handle_command(SetKycStatus::from(kyc_status))
+        .await
+        .map_err(|error| {
+            error!(
+                error = error.as_chain(),
+                "cannot handle SetKycStatus command"
+            );
+            Problem::internal()
+        })?
+        .map_err(|error| match error {
+            SetKycStatusError::NotFound(_) => Problem::not_found(error),
+        })
+        .map(Json)
+}
+
+// In the real-world, entities would be cached.
+async fn spawn_standing_order_entity<L>(
+    id: Uuid,
+    event_log: L,
+) -> Result<EntityRef<StandingOrderEntity>, Problem>
+where
+    L: EventLog<Id = Uuid>,
+{
+    StandingOrderEntity::default()
+        .entity()
+        .spawn(
+            id,
+            None,
+            NonZeroUsize::MIN,
+            event_log,
+            NoopSnapshotStore::default(),
+            SerdeJsonBinarize,
+        )
+        .await
+        .map_err(|error| {
+            error!(error = error.as_chain(), "cannot spawn StandingOrderEntity");
+            Problem::internal()
+        })
+}
+
+#[derive(Debug, Error)]
+#[error("customer with ID {0} not found")]
+struct UnknownCustomer(Uuid);
+
+#[derive(Debug, Error)]
+#[error("product {0} not found")]
+struct UnknownProduct(String);
+
+#[derive(Debug, Error)]
+#[error("account with ID {0} not found")]
+struct UnknownAccount(Uuid);
+
+#[derive(Debug, Error)]
+#[error("API key with ID {0} not found")]
+struct UnknownApiKey(Uuid);
+
+#[derive(Debug, Error)]
+#[error("webhook subscription with ID {0} not found")]
+struct UnknownWebhookSubscription(Uuid);
+
+#[derive(Debug, Error)]
+#[error("invalid webhook URL {0}")]
+struct InvalidWebhookUrl(String);
+
+#[derive(Debug, Error)]
+#[error("unknown account event type {0}")]
+struct UnknownEventType(String);
+
+#[derive(Debug, Error)]
+#[error("date range from {from} to {to} is invalid")]
+struct InvalidDateRange {
+    from: Date,
+    to: Date,
+}
+
+// In the real-world, entities would be cached.
+async fn spawn_account_entity<L>(
+    id: Uuid,
+    event_log: L,
+) -> Result<EntityRef<AccountEntity>, Problem>
+where
+    L: EventLog<Id = Uuid>,
+{
+    AccountEntity::default()
+        .entity()
+        .spawn(
+            id,
+            None,
+            NonZeroUsize::MIN,
+            event_log,
+            NoopSnapshotStore::default(),
+            SerdeJsonBinarize,
+        )
+        .await
+        .map_err(|error| {
+            error!(error = error.as_chain(), "cannot spawn AccountEntity");
+            Problem::internal()
+        })
+}
+
+// In the real-world, entities would be cached.
+async fn spawn_customer_entity<L>(
+    id: Uuid,
+    event_log: L,
+) -> Result<EntityRef<CustomerEntity>, Problem>
+where
+    L: EventLog<Id = Uuid>,
+{
+    CustomerEntity::default()
+        .entity()
+        .spawn(
+            id,
+            None,
+            NonZeroUsize::MIN,
+            event_log,
+            NoopSnapshotStore::default(),
+            SerdeJsonBinarize,
+        )
+        .await
+        .map_err(|error| {
+            error!(error = error.as_chain(), "cannot spawn CustomerEntity");
+            Problem::internal()
         })
 }
@@ -0,0 +1,163 @@
+use crate::{
+    api::{
+        authorization::{AccountHolder, Authorized},
+        problem::{InvalidParam, Problem},
+        v0::{self, GetAccountParams},
+        AppState,
+    },
+    domain::{
+        AccountRepository, AccountState, ApiKeyRepository, AsOf, CustomerRepository,
+        StandingOrderRepository, WebhookRepository,
+    },
+};
+use axum::{
+    extract::{Path, Query, State},
+    routing::get,
+    Json, Router,
+};
+use eventsourced::event_log::EventLog;
+use serde::Serialize;
+use tracing::instrument;
+use utoipa::{OpenApi, ToSchema};
+use uuid::Uuid;
+
+#[derive(Debug, OpenApi)]
+#[openapi(
+    paths(get_account),
+    components(schemas(
+        Problem,
+        InvalidParam,
+        AccountResource,
+        AccountStatus,
+        AccountLinks,
+        Link
+    ))
+)]
+pub struct ApiDoc;
+
+/// All routes of v0 except for the ones superseded by resource oriented ones.
+pub fn app<R, C, S, K, W, E>() -> Router<AppState<R, C, S, K, W, E>>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    E: EventLog<Id = Uuid> + Sync,
+{
+    v0::routes().route("/accounts/:id", get(get_account))
+}
+
+/// An account with its status, currency and version as well as links to related resources.
+#[derive(Debug, Serialize, ToSchema)]
+struct AccountResource {
+    id: Uuid,
+    status: AccountStatus,
+    product: String,
+    currency: String,
+    balance: i64,
+    holders: Vec<Uuid>,
+
+    /// The sequence number of the last event of the account, i.e. increasing with every change.
+    version: u64,
+
+    links: AccountLinks,
+}
+
+impl AccountResource {
+    fn new(id: Uuid, state: AccountState) -> Self {
+        let status = if state.balance < 0 {
+            AccountStatus::Overdrawn
+        } else {
+            AccountStatus::Open
+        };
+
+        Self {
+            id,
+            status,
+            product: state.product.name,
+            currency: state.product.currency,
+            balance: state.balance,
+            holders: state.holders,
+            version: state.seq_no,
+            links: AccountLinks::new(id),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+enum AccountStatus {
+    Open,
+
+    /// The balance is negative, i.e. the overdraft is used.
+    Overdrawn,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct AccountLinks {
+    #[serde(rename = "self")]
+    this: Link,
+    deposits: Link,
+    withdrawals: Link,
+    holds: Link,
+    transactions: Link,
+    statements: Link,
+    standing_orders: Link,
+}
+
+impl AccountLinks {
+    fn new(id: Uuid) -> Self {
+        let link = |suffix: &str| Link {
+            href: format!("/v1/accounts/{id}{suffix}"),
+        };
+
+        Self {
+            this: link(""),
+            deposits: link("/deposits"),
+            withdrawals: link("/withdrawals"),
+            holds: link("/holds"),
+            transactions: link("/transactions"),
+            statements: link("/statements"),
+            standing_orders: link("/standing-orders"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct Link {
+    href: String,
+}
+
+/// Get an account resource, either its current state or its state as of a sequence number or
+/// timestamp, e.g. for auditing.
+#[utoipa::path(
+    get,
+    path = "/accounts/{id}",
+    params(GetAccountParams),
+    responses(
+        (status = 200, description = "The account", body = AccountResource),
+        (status = 404, description = "An account with the given ID cannot be found", body = Problem, content_type = "application/problem+json"),
+    ),
+    tag = "account",
+)]
+#[instrument(skip(app_state))]
+async fn get_account<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    _authorized: Authorized<AccountHolder>,
+    Path(id): Path<Uuid>,
+    Query(GetAccountParams { as_of }): Query<GetAccountParams>,
+) -> Result<Json<AccountResource>, Problem>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog<Id = Uuid>,
+{
+    // The projection has no version, hence always replay.
+    let as_of = as_of.unwrap_or(AsOf::SeqNo(u64::MAX));
+    let state = v0::account_state(&app_state.event_log, id, as_of).await?;
+    Ok(Json(AccountResource::new(id, state)))
+}
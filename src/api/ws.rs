@@ -0,0 +1,389 @@
+use crate::{
+    api::{
+        auth::{Principal, Role},
+        authorization::{self, Anyone, Authorized},
+        problem::Problem,
+        v0, AppState,
+    },
+    domain::{
+        Account, AccountRepository, ApiKeyRepository, CustomerRepository, StandingOrderRepository,
+        WebhookRepository,
+    },
+};
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    http::StatusCode,
+    response::Response,
+};
+use error_ext::StdErrorExt;
+use eventsourced::event_log::EventLog;
+use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, pin::pin};
+use tokio::{sync::mpsc, task::JoinHandle};
+use tracing::{debug, error, instrument};
+use uuid::Uuid;
+
+/// Maximum size of client messages in bytes.
+const MAX_MESSAGE_SIZE: usize = 64 * 1024;
+
+/// Maximum number of accounts a single connection can subscribe to.
+const MAX_SUBSCRIPTIONS: usize = 100;
+
+/// Number of server messages buffered per connection.
+const BUFFER_SIZE: usize = 64;
+
+/// Messages sent by clients, each with a correlation ID which is echoed in the reply.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+enum ClientMessage {
+    /// Tellers and admins only, like for the REST API.
+    Deposit {
+        correlation_id: String,
+        account_id: Uuid,
+        amount: u64,
+    },
+
+    Withdraw {
+        correlation_id: String,
+        account_id: Uuid,
+        amount: u64,
+    },
+
+    /// Push the current balance of the account and then every change of it.
+    Subscribe {
+        correlation_id: String,
+        account_id: Uuid,
+    },
+
+    Unsubscribe {
+        correlation_id: String,
+        account_id: Uuid,
+    },
+}
+
+impl ClientMessage {
+    fn correlation_id(&self) -> &str {
+        match self {
+            ClientMessage::Deposit { correlation_id, .. }
+            | ClientMessage::Withdraw { correlation_id, .. }
+            | ClientMessage::Subscribe { correlation_id, .. }
+            | ClientMessage::Unsubscribe { correlation_id, .. } => correlation_id,
+        }
+    }
+
+    fn account_id(&self) -> Uuid {
+        match self {
+            ClientMessage::Deposit { account_id, .. }
+            | ClientMessage::Withdraw { account_id, .. }
+            | ClientMessage::Subscribe { account_id, .. }
+            | ClientMessage::Unsubscribe { account_id, .. } => *account_id,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+enum ServerMessage {
+    /// The updated account after a deposit or withdrawal.
+    Accepted {
+        correlation_id: String,
+        account: Account,
+    },
+
+    Subscribed {
+        correlation_id: String,
+        account_id: Uuid,
+    },
+
+    Unsubscribed {
+        correlation_id: String,
+        account_id: Uuid,
+    },
+
+    /// A rejected client message; without correlation ID if it cannot be parsed.
+    Rejected {
+        correlation_id: Option<String>,
+        problem: Problem,
+    },
+
+    /// The balance of a subscribed account.
+    Balance { account_id: Uuid, balance: i64 },
+}
+
+/// Upgrade to a WebSocket connection accepting deposit and withdraw commands and pushing balance
+/// updates for subscribed accounts. Each client message is authorized and rate limited like the
+/// respective REST request.
+#[instrument(skip(app_state, ws))]
+pub async fn connect<R, C, S, K, W, L>(
+    State(app_state): State<AppState<R, C, S, K, W, L>>,
+    authorized: Authorized<Anyone>,
+    ws: WebSocketUpgrade,
+) -> Response
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog<Id = Uuid> + Sync,
+{
+    let principal = authorized.0;
+    ws.max_message_size(MAX_MESSAGE_SIZE)
+        .on_upgrade(move |socket| handle_socket(socket, app_state, principal))
+}
+
+async fn handle_socket<R, C, S, K, W, L>(
+    socket: WebSocket,
+    app_state: AppState<R, C, S, K, W, L>,
+    principal: Principal,
+) where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog<Id = Uuid> + Sync,
+{
+    let (mut sink, mut stream) = socket.split();
+    let (messages, mut outgoing) = mpsc::channel::<ServerMessage>(BUFFER_SIZE);
+
+    let writer = tokio::spawn(async move {
+        while let Some(message) = outgoing.recv().await {
+            let message = match serde_json::to_string(&message) {
+                Ok(message) => message,
+                Err(error) => {
+                    error!(error = error.as_chain(), "cannot serialize ServerMessage");
+                    continue;
+                }
+            };
+            if sink.send(Message::Text(message)).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut subscriptions = HashMap::<Uuid, JoinHandle<()>>::new();
+
+    while let Some(Ok(message)) = stream.next().await {
+        let message = match message {
+            Message::Text(message) => message,
+            Message::Close(_) => break,
+            // Pings are answered automatically.
+            _ => continue,
+        };
+
+        let reply = match serde_json::from_str::<ClientMessage>(&message) {
+            Ok(message) => {
+                let correlation_id = message.correlation_id().to_string();
+                handle_message(
+                    message,
+                    &app_state,
+                    &principal,
+                    &messages,
+                    &mut subscriptions,
+                )
+                .await
+                .unwrap_or_else(|problem| ServerMessage::Rejected {
+                    correlation_id: Some(correlation_id),
+                    problem,
+                })
+            }
+
+            Err(error) => {
+                debug!(error = error.as_chain(), "cannot parse ClientMessage");
+                ServerMessage::Rejected {
+                    correlation_id: None,
+                    problem: Problem::new(StatusCode::BAD_REQUEST).with_detail(error),
+                }
+            }
+        };
+
+        if messages.send(reply).await.is_err() {
+            break;
+        }
+    }
+
+    for subscription in subscriptions.into_values() {
+        subscription.abort();
+    }
+    writer.abort();
+}
+
+async fn handle_message<R, C, S, K, W, L>(
+    message: ClientMessage,
+    app_state: &AppState<R, C, S, K, W, L>,
+    principal: &Principal,
+    messages: &mpsc::Sender<ServerMessage>,
+    subscriptions: &mut HashMap<Uuid, JoinHandle<()>>,
+) -> Result<ServerMessage, Problem>
+where
+    R: AccountRepository,
+    C: CustomerRepository,
+    S: StandingOrderRepository,
+    K: ApiKeyRepository,
+    W: WebhookRepository,
+    L: EventLog<Id = Uuid> + Sync,
+{
+    let account_id = message.account_id();
+    if !authorization::may_access_account(app_state, principal, account_id).await? {
+        debug!(?principal, %account_id, "principal does not own account");
+        return Err(Problem::new(StatusCode::FORBIDDEN).with_account_id(account_id));
+    }
+
+    match message {
+        ClientMessage::Deposit {
+            correlation_id,
+            account_id,
+            amount,
+        } => {
+            if !principal.has_role(Role::Admin) && !principal.has_role(Role::Teller) {
+                debug!(?principal, "principal not authorized to deposit");
+                return Err(Problem::new(StatusCode::FORBIDDEN).with_account_id(account_id));
+            }
+            rate_limit(app_state, principal, account_id)?;
+            let account =
+                v0::handle_deposit(app_state.event_log.clone(), account_id, amount).await?;
+            Ok(ServerMessage::Accepted {
+                correlation_id,
+                account,
+            })
+        }
+
+        ClientMessage::Withdraw {
+            correlation_id,
+            account_id,
+            amount,
+        } => {
+            rate_limit(app_state, principal, account_id)?;
+            let account =
+                v0::handle_withdraw(app_state.event_log.clone(), account_id, amount).await?;
+            Ok(ServerMessage::Accepted {
+                correlation_id,
+                account,
+            })
+        }
+
+        ClientMessage::Subscribe {
+            correlation_id,
+            account_id,
+        } => {
+            if !subscriptions.contains_key(&account_id) {
+                if subscriptions.len() >= MAX_SUBSCRIPTIONS {
+                    return Err(Problem::new(StatusCode::UNPROCESSABLE_ENTITY)
+                        .with_invalid_param("account_id", "too many subscriptions"));
+                }
+                let subscription =
+                    subscribe(app_state.event_log.clone(), account_id, messages.clone()).await?;
+                subscriptions.insert(account_id, subscription);
+            }
+
+            Ok(ServerMessage::Subscribed {
+                correlation_id,
+                account_id,
+            })
+        }
+
+        ClientMessage::Unsubscribe {
+            correlation_id,
+            account_id,
+        } => {
+            if let Some(subscription) = subscriptions.remove(&account_id) {
+                subscription.abort();
+            }
+
+            Ok(ServerMessage::Unsubscribed {
+                correlation_id,
+                account_id,
+            })
+        }
+    }
+}
+
+fn rate_limit<R, C, S, K, W, L>(
+    app_state: &AppState<R, C, S, K, W, L>,
+    principal: &Principal,
+    account_id: Uuid,
+) -> Result<(), Problem> {
+    app_state
+        .rate_limiter
+        .acquire(Some(principal), Some(account_id))
+        .map_err(|_| Problem::new(StatusCode::TOO_MANY_REQUESTS).with_account_id(account_id))
+}
+
+/// Spawn a task pushing the current balance of the account with the given ID and then every
+/// change of it.
+async fn subscribe<L>(
+    event_log: L,
+    account_id: Uuid,
+    messages: mpsc::Sender<ServerMessage>,
+) -> Result<JoinHandle<()>, Problem>
+where
+    L: EventLog<Id = Uuid>,
+{
+    let balances = v0::balances(event_log, account_id).await?;
+
+    let subscription = tokio::spawn(async move {
+        let mut balances = pin!(balances);
+
+        while let Some(balance) = balances.next().await {
+            let balance = match balance {
+                Ok(balance) => balance,
+                Err(error) => {
+                    error!(error = error.as_chain(), %account_id, "cannot get account event");
+                    break;
+                }
+            };
+
+            let message = ServerMessage::Balance {
+                account_id,
+                balance,
+            };
+            if messages.send(message).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    Ok(subscription)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClientMessage, ServerMessage};
+    use serde_json::json;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_messages() {
+        let account_id = Uuid::now_v7();
+
+        let message = serde_json::from_value::<ClientMessage>(json!({
+            "type": "deposit",
+            "correlation_id": "42",
+            "account_id": account_id,
+            "amount": 100
+        }));
+        assert_eq!(
+            message.ok(),
+            Some(ClientMessage::Deposit {
+                correlation_id: "42".to_string(),
+                account_id,
+                amount: 100
+            })
+        );
+
+        let message = serde_json::to_value(ServerMessage::Balance {
+            account_id,
+            balance: 100,
+        })
+        .unwrap();
+        assert_eq!(
+            message,
+            json!({ "type": "balance", "account_id": account_id, "balance": 100 })
+        );
+    }
+}
@@ -1,7 +1,45 @@
 mod account;
 mod account_entity;
+mod account_filter;
+mod account_history;
 mod account_repository;
+mod api_key;
+mod api_key_repository;
+mod customer;
+mod customer_entity;
+mod customer_repository;
+mod fee;
+mod interest;
+mod ledger;
+mod product;
+mod reconciliation;
+mod standing_order;
+mod standing_order_entity;
+mod standing_order_repository;
+mod statement;
+mod transaction;
+mod webhook;
+mod webhook_repository;
 
 pub use account::*;
 pub use account_entity::*;
+pub use account_filter::*;
+pub use account_history::*;
 pub use account_repository::*;
+pub use api_key::*;
+pub use api_key_repository::*;
+pub use customer::*;
+pub use customer_entity::*;
+pub use customer_repository::*;
+pub use fee::*;
+pub use interest::*;
+pub use ledger::*;
+pub use product::*;
+pub use reconciliation::*;
+pub use standing_order::*;
+pub use standing_order_entity::*;
+pub use standing_order_repository::*;
+pub use statement::*;
+pub use transaction::*;
+pub use webhook::*;
+pub use webhook_repository::*;
@@ -5,5 +5,6 @@ use uuid::Uuid;
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 pub struct Account {
     pub id: Uuid,
-    pub balance: u64,
+    pub product: String,
+    pub balance: i64,
 }
@@ -1,17 +1,209 @@
-use crate::domain::account::Account;
+use crate::domain::{
+    account::Account, Fee, FeeKind, Operation, Product, Transaction, TransactionKind,
+    MICROS_PER_UNIT,
+};
 use eventsourced::{Command, CommandEffect, EventSourced};
 use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display};
 use thiserror::Error;
+use time::{Date, Duration, OffsetDateTime, UtcOffset};
+use utoipa::ToSchema;
 use uuid::Uuid;
 
+/// The largest amount of a single transaction, such that it can be represented as a balance.
+pub const MAX_AMOUNT: u64 = i64::MAX as u64;
+
+/// How long deposits and withdrawals can be reversed and fees can be waived.
+pub const REVERSAL_WINDOW: Duration = Duration::days(90);
+
+/// Whether the given amount of a deposit, withdrawal or hold is positive and not larger than
+/// [MAX_AMOUNT].
+pub fn is_valid_amount(amount: u64) -> bool {
+    (1..=MAX_AMOUNT).contains(&amount)
+}
+
 #[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AccountEntity {
     #[default]
     Nonexistent,
 
-    Existing {
-        balance: u64,
-    },
+    Existing(AccountState),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountState {
+    /// Sequence number of the last event of this account, starting with 1 for `Created`.
+    pub seq_no: u64,
+    pub holders: Vec<Uuid>,
+    pub product: Product,
+    pub balance: i64,
+    pub withdrawal_limits: WithdrawalLimits,
+
+    /// Withdrawals still counting against a withdrawal limit, oldest first.
+    pub recent_withdrawals: Vec<Withdrawal>,
+
+    /// Holds which have been neither captured nor released; expired holds are dropped when the
+    /// next hold is placed.
+    pub holds: Vec<Hold>,
+
+    /// Deposits and withdrawals which can be referenced by a reversal.
+    pub reversible_transactions: Vec<ReversibleTransaction>,
+
+    /// The balances at the end of the (UTC) days with transactions since the last day interest
+    /// has been accrued for, including the last one before, oldest first.
+    #[serde(default)]
+    pub closing_balances: Vec<ClosingBalance>,
+
+    /// Interest accrued but not yet posted, in millionths of the smallest currency unit.
+    pub accrued_interest: u64,
+
+    /// The last date interest has been accrued for.
+    pub accrued_through: Option<Date>,
+
+    /// Transactions with fees which can be referenced by a fee waiver.
+    pub waivable_fees: Vec<ChargedFee>,
+
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_maintenance_fee_at: Option<OffsetDateTime>,
+
+    #[serde(default)]
+    pub status: AccountStatus,
+}
+
+impl AccountState {
+    /// The ledger balance minus the amounts of all holds which have not yet expired at the given
+    /// time.
+    pub fn available_balance(&self, at: OffsetDateTime) -> i64 {
+        let held = self
+            .holds
+            .iter()
+            .filter(|hold| hold.expires_at > at)
+            .fold(0u64, |held, hold| held.saturating_add(hold.amount));
+        self.balance
+            .saturating_sub(i64::try_from(held).unwrap_or(i64::MAX))
+    }
+
+    /// Whether the given amount can be debited at the given time without exceeding the overdraft
+    /// of the product.
+    pub fn can_debit(&self, amount: u64, at: OffsetDateTime) -> bool {
+        let overdraft = i64::try_from(self.product.overdraft).unwrap_or(i64::MAX);
+        i64::try_from(amount)
+            .ok()
+            .and_then(|amount| self.available_balance(at).checked_sub(amount))
+            .is_some_and(|balance| balance >= -overdraft)
+    }
+
+    /// The balance after crediting the given amount, `None` on overflow.
+    fn credited(&self, amount: u64) -> Option<i64> {
+        i64::try_from(amount)
+            .ok()
+            .and_then(|amount| self.balance.checked_add(amount))
+    }
+
+    /// The balance after debiting the given amount, `None` on overflow.
+    fn debited(&self, amount: u64) -> Option<i64> {
+        i64::try_from(amount)
+            .ok()
+            .and_then(|amount| self.balance.checked_sub(amount))
+    }
+
+    /// The balance at the end of the given (UTC) date, as long as it is not before the last day
+    /// interest has been accrued for.
+    pub fn end_of_day_balance(&self, date: Date) -> i64 {
+        self.closing_balances
+            .iter()
+            .rev()
+            .find(|closing_balance| closing_balance.date <= date)
+            .map(|closing_balance| closing_balance.balance)
+            .unwrap_or_default()
+    }
+
+    /// The given sequence number or, for legacy events, the one following the last one.
+    fn resolve_seq_no(&self, seq_no: u64) -> u64 {
+        if seq_no == LEGACY_SEQ_NO {
+            self.seq_no + 1
+        } else {
+            seq_no
+        }
+    }
+
+    /// Set the balance resulting from a transaction at the given time.
+    fn book(&mut self, balance: i64, at: OffsetDateTime) {
+        let date = at.to_offset(UtcOffset::UTC).date();
+        match self.closing_balances.last_mut() {
+            Some(closing_balance) if closing_balance.date == date => {
+                closing_balance.balance = balance
+            }
+            _ => self.closing_balances.push(ClosingBalance { date, balance }),
+        }
+        self.balance = balance;
+
+        // Transactions and fees outside of the reversal window can no longer be referenced.
+        self.reversible_transactions
+            .retain(|transaction| at - transaction.at < REVERSAL_WINDOW);
+        self.waivable_fees
+            .retain(|fee| at - fee.at < REVERSAL_WINDOW);
+    }
+
+    pub fn account(&self, id: Uuid) -> Account {
+        Account {
+            id,
+            product: self.product.name.clone(),
+            balance: self.balance,
+        }
+    }
+
+    /// Whether the maintenance fee has already been charged for the (UTC) calendar month of the
+    /// given time.
+    pub fn maintenance_fee_charged(&self, at: OffsetDateTime) -> bool {
+        let at = at.to_offset(UtcOffset::UTC);
+        self.last_maintenance_fee_at.is_some_and(|last| {
+            let last = last.to_offset(UtcOffset::UTC);
+            (last.year(), last.month()) == (at.year(), at.month())
+        })
+    }
+
+    fn hold(&self, hold_id: Uuid) -> Option<&Hold> {
+        self.holds.iter().find(|hold| hold.id == hold_id)
+    }
+
+    /// The fee charged with the given sequence number, as long as still within the reversal
+    /// window at the given time.
+    fn waivable_fee(&self, seq_no: u64, at: OffsetDateTime) -> Option<&ChargedFee> {
+        self.waivable_fees
+            .iter()
+            .find(|fee| fee.seq_no == seq_no && at - fee.at < REVERSAL_WINDOW)
+    }
+
+    /// The transaction with the given sequence number, as long as still within the reversal
+    /// window at the given time.
+    fn reversible_transaction(
+        &self,
+        seq_no: u64,
+        at: OffsetDateTime,
+    ) -> Option<&ReversibleTransaction> {
+        self.reversible_transactions.iter().find(|transaction| {
+            transaction.seq_no == seq_no && at - transaction.at < REVERSAL_WINDOW
+        })
+    }
+
+    /// The part of the limit for the given period which has not yet been used up at the given time,
+    /// `None` meaning unlimited.
+    pub fn remaining_allowance(&self, period: LimitPeriod, at: OffsetDateTime) -> Option<u64> {
+        let limit = match period {
+            LimitPeriod::Daily => self.withdrawal_limits.daily,
+            LimitPeriod::Monthly => self.withdrawal_limits.monthly,
+        }?;
+
+        let withdrawn = self
+            .recent_withdrawals
+            .iter()
+            .filter(|withdrawal| period.includes(withdrawal.at, at))
+            .map(|withdrawal| withdrawal.amount)
+            .sum::<u64>();
+
+        Some(limit.saturating_sub(withdrawn))
+    }
 }
 
 impl EventSourced for AccountEntity {
@@ -23,21 +215,240 @@ impl EventSourced for AccountEntity {
     fn handle_event(self, event: Self::Event) -> Self {
         match self {
             AccountEntity::Nonexistent => match event {
-                AccountEvent::Created { .. } => AccountEntity::Existing { balance: 0 },
-                AccountEvent::Deposited { .. } => {
-                    panic!("invalid event {event:?} in state Deleted")
-                }
-                AccountEvent::Withdrawn { .. } => {
+                AccountEvent::Created {
+                    holders, product, ..
+                } => AccountEntity::Existing(AccountState {
+                    seq_no: 1,
+                    holders,
+                    product,
+                    balance: 0,
+                    withdrawal_limits: WithdrawalLimits::default(),
+                    recent_withdrawals: vec![],
+                    holds: vec![],
+                    reversible_transactions: vec![],
+                    closing_balances: vec![],
+                    accrued_interest: 0,
+                    accrued_through: None,
+                    waivable_fees: vec![],
+                    last_maintenance_fee_at: None,
+                    status: AccountStatus::Open,
+                }),
+                AccountEvent::Deposited { .. }
+                | AccountEvent::Withdrawn { .. }
+                | AccountEvent::WithdrawalLimitsSet { .. }
+                | AccountEvent::HoldPlaced { .. }
+                | AccountEvent::HoldCaptured { .. }
+                | AccountEvent::HoldReleased { .. }
+                | AccountEvent::Reversed { .. }
+                | AccountEvent::InterestAccrued { .. }
+                | AccountEvent::InterestPosted { .. }
+                | AccountEvent::FeeCharged { .. }
+                | AccountEvent::FeeWaived { .. }
+                | AccountEvent::StatusChanged { .. } => {
                     panic!("invalid event {event:?} in state Deleted")
                 }
             },
 
-            AccountEntity::Existing { .. } => match event {
+            AccountEntity::Existing(mut state) => match event {
                 AccountEvent::Created { .. } => panic!("invalid event {event:?} in state Deleted"),
 
-                AccountEvent::Deposited { balance, .. } => AccountEntity::Existing { balance },
+                AccountEvent::Deposited {
+                    seq_no,
+                    amount,
+                    balance,
+                    at,
+                    ..
+                } => {
+                    let seq_no = state.resolve_seq_no(seq_no);
+                    state.seq_no = seq_no;
+                    state.reversible_transactions.push(ReversibleTransaction {
+                        seq_no,
+                        kind: TransactionKind::Deposit,
+                        amount,
+                        reversed: false,
+                        at,
+                    });
+                    state.book(balance, at);
+                    AccountEntity::Existing(state)
+                }
+
+                AccountEvent::Withdrawn {
+                    seq_no,
+                    amount,
+                    fees,
+                    balance,
+                    at,
+                    ..
+                } => {
+                    let seq_no = state.resolve_seq_no(seq_no);
+                    state.seq_no = seq_no;
+                    state.recent_withdrawals.retain(|withdrawal| {
+                        LimitPeriod::Daily.includes(withdrawal.at, at)
+                            || LimitPeriod::Monthly.includes(withdrawal.at, at)
+                    });
+                    state
+                        .recent_withdrawals
+                        .push(Withdrawal { seq_no, at, amount });
+                    state.reversible_transactions.push(ReversibleTransaction {
+                        seq_no,
+                        kind: TransactionKind::Withdrawal,
+                        amount,
+                        reversed: false,
+                        at,
+                    });
+                    if !fees.is_empty() {
+                        state.waivable_fees.push(ChargedFee {
+                            seq_no,
+                            amount: fees.iter().map(|fee| fee.amount).sum(),
+                            waived: false,
+                            at,
+                        });
+                    }
+                    state.book(balance, at);
+                    AccountEntity::Existing(state)
+                }
+
+                AccountEvent::WithdrawalLimitsSet { limits, .. } => {
+                    state.seq_no += 1;
+                    state.withdrawal_limits = limits;
+                    AccountEntity::Existing(state)
+                }
+
+                AccountEvent::HoldPlaced {
+                    hold_id,
+                    amount,
+                    expires_at,
+                    at,
+                    ..
+                } => {
+                    state.seq_no += 1;
+                    state.holds.retain(|hold| hold.expires_at > at);
+                    state.holds.push(Hold {
+                        id: hold_id,
+                        amount,
+                        expires_at,
+                    });
+                    AccountEntity::Existing(state)
+                }
+
+                AccountEvent::HoldCaptured {
+                    seq_no,
+                    hold_id,
+                    balance,
+                    at,
+                    ..
+                } => {
+                    state.seq_no = seq_no;
+                    state.holds.retain(|hold| hold.id != hold_id);
+                    state.book(balance, at);
+                    AccountEntity::Existing(state)
+                }
+
+                AccountEvent::HoldReleased { hold_id, .. } => {
+                    state.seq_no += 1;
+                    state.holds.retain(|hold| hold.id != hold_id);
+                    AccountEntity::Existing(state)
+                }
+
+                AccountEvent::Reversed {
+                    seq_no,
+                    transaction_ref,
+                    fee,
+                    balance,
+                    at,
+                    ..
+                } => {
+                    state.seq_no = seq_no;
+                    state
+                        .reversible_transactions
+                        .iter_mut()
+                        .filter(|transaction| transaction.seq_no == transaction_ref)
+                        .for_each(|transaction| transaction.reversed = true);
+                    state
+                        .recent_withdrawals
+                        .retain(|withdrawal| withdrawal.seq_no != transaction_ref);
+                    if fee > 0 {
+                        state
+                            .waivable_fees
+                            .iter_mut()
+                            .filter(|charged| charged.seq_no == transaction_ref)
+                            .for_each(|charged| charged.waived = true);
+                    }
+                    state.book(balance, at);
+                    AccountEntity::Existing(state)
+                }
+
+                AccountEvent::InterestAccrued { date, amount, .. } => {
+                    state.seq_no += 1;
+                    state.accrued_interest += amount;
+                    state.accrued_through = Some(date);
+                    // Only the closing balance of the accrued date is needed for the next ones.
+                    if let Some(n) = state
+                        .closing_balances
+                        .iter()
+                        .rposition(|closing_balance| closing_balance.date <= date)
+                    {
+                        state.closing_balances.drain(..n);
+                    }
+                    AccountEntity::Existing(state)
+                }
+
+                AccountEvent::InterestPosted {
+                    seq_no,
+                    amount,
+                    balance,
+                    at,
+                    ..
+                } => {
+                    state.seq_no = seq_no;
+                    state.accrued_interest -= amount * MICROS_PER_UNIT;
+                    state.book(balance, at);
+                    AccountEntity::Existing(state)
+                }
+
+                AccountEvent::FeeCharged {
+                    seq_no,
+                    fee,
+                    balance,
+                    at,
+                    ..
+                } => {
+                    state.seq_no = seq_no;
+                    state.waivable_fees.push(ChargedFee {
+                        seq_no,
+                        amount: fee.amount,
+                        waived: false,
+                        at,
+                    });
+                    if fee.kind == FeeKind::MonthlyMaintenance {
+                        state.last_maintenance_fee_at = Some(at);
+                    }
+                    state.book(balance, at);
+                    AccountEntity::Existing(state)
+                }
+
+                AccountEvent::FeeWaived {
+                    seq_no,
+                    transaction_ref,
+                    balance,
+                    at,
+                    ..
+                } => {
+                    state.seq_no = seq_no;
+                    state
+                        .waivable_fees
+                        .iter_mut()
+                        .filter(|fee| fee.seq_no == transaction_ref)
+                        .for_each(|fee| fee.waived = true);
+                    state.book(balance, at);
+                    AccountEntity::Existing(state)
+                }
 
-                AccountEvent::Withdrawn { balance, .. } => AccountEntity::Existing { balance },
+                AccountEvent::StatusChanged { status, .. } => {
+                    state.seq_no += 1;
+                    state.status = status;
+                    AccountEntity::Existing(state)
+                }
             },
         }
     }
@@ -45,15 +456,407 @@ impl EventSourced for AccountEntity {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub enum AccountEvent {
-    Created { id: Uuid },
-    Deposited { id: Uuid, amount: u64, balance: u64 },
-    Withdrawn { id: Uuid, amount: u64, balance: u64 },
+    /// Accounts created before holders were introduced have none, i.e. they can only be
+    /// accessed by tellers; those created before products were introduced get the
+    /// [Product::legacy] one.
+    Created {
+        id: Uuid,
+        #[serde(default)]
+        holders: Vec<Uuid>,
+        #[serde(default = "Product::legacy")]
+        product: Product,
+    },
+    /// Events written before sequence numbers and timestamps were introduced lack `seq_no` and
+    /// `at`, see [LEGACY_SEQ_NO] and [legacy_at].
+    Deposited {
+        id: Uuid,
+        #[serde(default)]
+        seq_no: u64,
+        amount: u64,
+        balance: i64,
+        #[serde(default = "legacy_at", with = "time::serde::rfc3339")]
+        at: OffsetDateTime,
+    },
+    /// The fees charged with the withdrawal are included in `balance`. Like for `Deposited`,
+    /// legacy events lack `seq_no` and `at`.
+    Withdrawn {
+        id: Uuid,
+        #[serde(default)]
+        seq_no: u64,
+        amount: u64,
+        #[serde(default)]
+        fees: Vec<Fee>,
+        balance: i64,
+        #[serde(default = "legacy_at", with = "time::serde::rfc3339")]
+        at: OffsetDateTime,
+    },
+    WithdrawalLimitsSet {
+        id: Uuid,
+        limits: WithdrawalLimits,
+    },
+    HoldPlaced {
+        id: Uuid,
+        hold_id: Uuid,
+        amount: u64,
+        #[serde(with = "time::serde::rfc3339")]
+        expires_at: OffsetDateTime,
+        #[serde(with = "time::serde::rfc3339")]
+        at: OffsetDateTime,
+    },
+    HoldCaptured {
+        id: Uuid,
+        seq_no: u64,
+        hold_id: Uuid,
+        amount: u64,
+        balance: i64,
+        #[serde(with = "time::serde::rfc3339")]
+        at: OffsetDateTime,
+    },
+    HoldReleased {
+        id: Uuid,
+        hold_id: Uuid,
+    },
+    /// Compensates the deposit or withdrawal with the sequence number `transaction_ref`; `fee` is
+    /// the refunded fees of a withdrawal, included in `balance`.
+    Reversed {
+        id: Uuid,
+        seq_no: u64,
+        transaction_ref: u64,
+        amount: u64,
+        #[serde(default)]
+        fee: u64,
+        balance: i64,
+        #[serde(with = "time::serde::rfc3339")]
+        at: OffsetDateTime,
+    },
+    /// Interest for the given date; `amount` is in millionths of the smallest currency unit and
+    /// might be zero, because every date is accrued for exactly once.
+    InterestAccrued {
+        id: Uuid,
+        date: Date,
+        amount: u64,
+    },
+    /// Credits the whole units of the accrued interest; the remainder stays accrued.
+    InterestPosted {
+        id: Uuid,
+        seq_no: u64,
+        amount: u64,
+        balance: i64,
+        #[serde(with = "time::serde::rfc3339")]
+        at: OffsetDateTime,
+    },
+    FeeCharged {
+        id: Uuid,
+        seq_no: u64,
+        fee: Fee,
+        balance: i64,
+        #[serde(with = "time::serde::rfc3339")]
+        at: OffsetDateTime,
+    },
+    /// Refunds all fees charged with the transaction with the sequence number `transaction_ref`.
+    FeeWaived {
+        id: Uuid,
+        seq_no: u64,
+        transaction_ref: u64,
+        amount: u64,
+        balance: i64,
+        #[serde(with = "time::serde::rfc3339")]
+        at: OffsetDateTime,
+    },
+
+    StatusChanged {
+        id: Uuid,
+        status: AccountStatus,
+        #[serde(with = "time::serde::rfc3339")]
+        at: OffsetDateTime,
+    },
+}
+
+/// The sequence number of legacy `Deposited` and `Withdrawn` events, standing for the one
+/// following the sequence number of the previous event. As only `Created`, `Deposited` and
+/// `Withdrawn` existed back then, it can be derived by counting.
+pub const LEGACY_SEQ_NO: u64 = 0;
+
+/// The time of legacy `Deposited` and `Withdrawn` events, which is unknown.
+pub fn legacy_at() -> OffsetDateTime {
+    OffsetDateTime::UNIX_EPOCH
+}
+
+impl AccountEvent {
+    /// The names of all variants.
+    pub const NAMES: [&'static str; 13] = [
+        "Created",
+        "Deposited",
+        "Withdrawn",
+        "WithdrawalLimitsSet",
+        "HoldPlaced",
+        "HoldCaptured",
+        "HoldReleased",
+        "Reversed",
+        "InterestAccrued",
+        "InterestPosted",
+        "FeeCharged",
+        "FeeWaived",
+        "StatusChanged",
+    ];
+
+    /// The ID of the account.
+    pub fn id(&self) -> Uuid {
+        match self {
+            AccountEvent::Created { id, .. }
+            | AccountEvent::Deposited { id, .. }
+            | AccountEvent::Withdrawn { id, .. }
+            | AccountEvent::WithdrawalLimitsSet { id, .. }
+            | AccountEvent::HoldPlaced { id, .. }
+            | AccountEvent::HoldCaptured { id, .. }
+            | AccountEvent::HoldReleased { id, .. }
+            | AccountEvent::Reversed { id, .. }
+            | AccountEvent::InterestAccrued { id, .. }
+            | AccountEvent::InterestPosted { id, .. }
+            | AccountEvent::FeeCharged { id, .. }
+            | AccountEvent::FeeWaived { id, .. }
+            | AccountEvent::StatusChanged { id, .. } => *id,
+        }
+    }
+
+    /// The name of the variant, which is also used as tag when serializing.
+    pub fn name(&self) -> &'static str {
+        match self {
+            AccountEvent::Created { .. } => "Created",
+            AccountEvent::Deposited { .. } => "Deposited",
+            AccountEvent::Withdrawn { .. } => "Withdrawn",
+            AccountEvent::WithdrawalLimitsSet { .. } => "WithdrawalLimitsSet",
+            AccountEvent::HoldPlaced { .. } => "HoldPlaced",
+            AccountEvent::HoldCaptured { .. } => "HoldCaptured",
+            AccountEvent::HoldReleased { .. } => "HoldReleased",
+            AccountEvent::Reversed { .. } => "Reversed",
+            AccountEvent::InterestAccrued { .. } => "InterestAccrued",
+            AccountEvent::InterestPosted { .. } => "InterestPosted",
+            AccountEvent::FeeCharged { .. } => "FeeCharged",
+            AccountEvent::FeeWaived { .. } => "FeeWaived",
+            AccountEvent::StatusChanged { .. } => "StatusChanged",
+        }
+    }
+
+    /// The time of the event, if it has one.
+    pub fn at(&self) -> Option<OffsetDateTime> {
+        match self {
+            AccountEvent::Deposited { at, .. }
+            | AccountEvent::Withdrawn { at, .. }
+            | AccountEvent::HoldPlaced { at, .. }
+            | AccountEvent::HoldCaptured { at, .. }
+            | AccountEvent::Reversed { at, .. }
+            | AccountEvent::InterestPosted { at, .. }
+            | AccountEvent::FeeCharged { at, .. }
+            | AccountEvent::FeeWaived { at, .. }
+            | AccountEvent::StatusChanged { at, .. } => Some(*at),
+
+            AccountEvent::Created { .. }
+            | AccountEvent::WithdrawalLimitsSet { .. }
+            | AccountEvent::HoldReleased { .. }
+            | AccountEvent::InterestAccrued { .. } => None,
+        }
+    }
+
+    /// The balance after the event, if it changes the balance.
+    pub fn balance(&self) -> Option<i64> {
+        match self {
+            AccountEvent::Deposited { balance, .. }
+            | AccountEvent::Withdrawn { balance, .. }
+            | AccountEvent::HoldCaptured { balance, .. }
+            | AccountEvent::Reversed { balance, .. }
+            | AccountEvent::InterestPosted { balance, .. }
+            | AccountEvent::FeeCharged { balance, .. }
+            | AccountEvent::FeeWaived { balance, .. } => Some(*balance),
+
+            AccountEvent::Created { .. }
+            | AccountEvent::WithdrawalLimitsSet { .. }
+            | AccountEvent::HoldPlaced { .. }
+            | AccountEvent::HoldReleased { .. }
+            | AccountEvent::InterestAccrued { .. }
+            | AccountEvent::StatusChanged { .. } => None,
+        }
+    }
+
+    /// The transaction of the event, if it changes the balance, like in the account projection;
+    /// `reversed_by` is never set.
+    pub fn transaction(&self, seq_no: u64) -> Option<Transaction> {
+        let (kind, amount, fee, reverses) = match self {
+            AccountEvent::Deposited { amount, .. } => (TransactionKind::Deposit, *amount, 0, None),
+            AccountEvent::Withdrawn { amount, fees, .. } => (
+                TransactionKind::Withdrawal,
+                *amount,
+                fees.iter().map(|fee| fee.amount).sum(),
+                None,
+            ),
+            AccountEvent::HoldCaptured { amount, .. } => {
+                (TransactionKind::HoldCapture, *amount, 0, None)
+            }
+            // Refunded fees are part of the amount of the reversal.
+            AccountEvent::Reversed {
+                transaction_ref,
+                amount,
+                fee,
+                ..
+            } => (
+                TransactionKind::Reversal,
+                amount + fee,
+                0,
+                Some(*transaction_ref),
+            ),
+            AccountEvent::InterestPosted { amount, .. } => {
+                (TransactionKind::Interest, *amount, 0, None)
+            }
+            AccountEvent::FeeCharged { fee, .. } => (TransactionKind::Fee, fee.amount, 0, None),
+            AccountEvent::FeeWaived {
+                transaction_ref,
+                amount,
+                ..
+            } => (
+                TransactionKind::FeeWaiver,
+                *amount,
+                0,
+                Some(*transaction_ref),
+            ),
+
+            AccountEvent::Created { .. }
+            | AccountEvent::WithdrawalLimitsSet { .. }
+            | AccountEvent::HoldPlaced { .. }
+            | AccountEvent::HoldReleased { .. }
+            | AccountEvent::InterestAccrued { .. }
+            | AccountEvent::StatusChanged { .. } => return None,
+        };
+
+        Some(Transaction {
+            seq_no,
+            kind,
+            amount,
+            fee,
+            balance: self.balance()?,
+            at: self.at()?,
+            reverses,
+            reversed_by: None,
+        })
+    }
+}
+
+/// Caps for the sum of withdrawals within a day and a calendar month, see [LimitPeriod], `None`
+/// meaning unlimited.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct WithdrawalLimits {
+    pub daily: Option<u64>,
+    pub monthly: Option<u64>,
+}
+
+/// An amount reserved until it is captured, released or expires.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct Hold {
+    pub id: Uuid,
+    pub amount: u64,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    pub expires_at: OffsetDateTime,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReversibleTransaction {
+    pub seq_no: u64,
+    pub kind: TransactionKind,
+    pub amount: u64,
+    pub reversed: bool,
+    #[serde(with = "time::serde::rfc3339")]
+    pub at: OffsetDateTime,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChargedFee {
+    pub seq_no: u64,
+    pub amount: u64,
+
+    /// Whether the fee has been waived or refunded with the reversal of its withdrawal.
+    pub waived: bool,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub at: OffsetDateTime,
+}
+
+/// The balance at the end of a (UTC) day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClosingBalance {
+    pub date: Date,
+    pub balance: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Withdrawal {
+    #[serde(default)]
+    pub seq_no: u64,
+    #[serde(with = "time::serde::rfc3339")]
+    pub at: OffsetDateTime,
+    pub amount: u64,
+}
+
+/// The period of a withdrawal limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitPeriod {
+    /// The rolling 24 hours ending at the time of the withdrawal.
+    Daily,
+
+    /// The (UTC) calendar month of the withdrawal.
+    Monthly,
+}
+
+impl LimitPeriod {
+    /// Whether a withdrawal at the given time counts against the limit for one at `at`.
+    fn includes(self, withdrawn_at: OffsetDateTime, at: OffsetDateTime) -> bool {
+        match self {
+            LimitPeriod::Daily => withdrawn_at > at - Duration::days(1),
+            LimitPeriod::Monthly => {
+                let withdrawn_at = withdrawn_at.to_offset(UtcOffset::UTC);
+                let at = at.to_offset(UtcOffset::UTC);
+                (withdrawn_at.year(), withdrawn_at.month()) == (at.year(), at.month())
+            }
+        }
+    }
+}
+
+impl Display for LimitPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitPeriod::Daily => write!(f, "daily"),
+            LimitPeriod::Monthly => write!(f, "monthly"),
+        }
+    }
+}
+
+/// Whether money can be moved into or out of an account.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountStatus {
+    #[default]
+    Open,
+
+    /// Neither deposits nor withdrawals, incl. holds, are possible; fees are still charged.
+    Frozen,
+
+    /// Like frozen, but final and without fees; requires a zero balance.
+    Closed,
 }
 
 // Command: CreateAccount ==========================================================================
 
 #[derive(Debug)]
-pub struct CreateAccount;
+pub struct CreateAccount {
+    holders: Vec<Uuid>,
+    product: Product,
+}
+
+impl CreateAccount {
+    /// Create an account for the given product held by the customers with the given IDs.
+    pub fn new(holders: Vec<Uuid>, product: Product) -> Self {
+        Self { holders, product }
+    }
+}
 
 impl Command<AccountEntity> for CreateAccount {
     type Reply = Account;
@@ -67,12 +870,29 @@ impl Command<AccountEntity> for CreateAccount {
         let id = *id;
 
         match state {
+            AccountEntity::Nonexistent if self.holders.is_empty() => {
+                CommandEffect::reject(CreateAccountError::NoHolders(id))
+            }
+
             AccountEntity::Nonexistent => {
-                let event = AccountEvent::Created { id };
-                CommandEffect::emit_and_reply(event, move |_| Account { id, balance: 0 })
+                let mut holders = self.holders;
+                holders.sort();
+                holders.dedup();
+                let event = AccountEvent::Created {
+                    id,
+                    holders,
+                    product: self.product,
+                };
+                CommandEffect::emit_and_reply(event, move |state| match state {
+                    AccountEntity::Nonexistent => {
+                        panic!("invalid command CreateAccount in state Nonexistent")
+                    }
+
+                    AccountEntity::Existing(state) => state.account(id),
+                })
             }
 
-            AccountEntity::Existing { .. } => {
+            AccountEntity::Existing(_) => {
                 CommandEffect::reject(CreateAccountError::AlreadyExisting(id))
             }
         }
@@ -83,6 +903,9 @@ impl Command<AccountEntity> for CreateAccount {
 pub enum CreateAccountError {
     #[error("account with ID {0} already exists")]
     AlreadyExisting(Uuid),
+
+    #[error("account with ID {0} must have at least one holder")]
+    NoHolders(Uuid),
 }
 
 // Command: Deposit ================================================================================
@@ -90,11 +913,12 @@ pub enum CreateAccountError {
 #[derive(Debug)]
 pub struct Deposit {
     amount: u64,
+    at: OffsetDateTime,
 }
 
-impl From<u64> for Deposit {
-    fn from(amount: u64) -> Self {
-        Self { amount }
+impl Deposit {
+    pub fn new(amount: u64, at: OffsetDateTime) -> Self {
+        Self { amount, at }
     }
 }
 
@@ -112,11 +936,28 @@ impl Command<AccountEntity> for Deposit {
         match state {
             AccountEntity::Nonexistent => CommandEffect::reject(DepositError::NotFound(id)),
 
-            AccountEntity::Existing { balance } => {
+            AccountEntity::Existing(_) if !is_valid_amount(self.amount) => {
+                CommandEffect::reject(DepositError::InvalidAmount(id))
+            }
+
+            AccountEntity::Existing(state) if state.status != AccountStatus::Open => {
+                CommandEffect::reject(DepositError::NotOpen(id))
+            }
+
+            AccountEntity::Existing(state) if !state.product.allows(Operation::Deposit) => {
+                CommandEffect::reject(DepositError::NotAllowed(id))
+            }
+
+            AccountEntity::Existing(state) => {
+                let Some(balance) = state.credited(self.amount) else {
+                    return CommandEffect::reject(DepositError::BalanceOverflow(id));
+                };
                 let event = AccountEvent::Deposited {
                     id,
+                    seq_no: state.seq_no + 1,
                     amount: self.amount,
-                    balance: balance + self.amount,
+                    balance,
+                    at: self.at,
                 };
 
                 CommandEffect::emit_and_reply(event, move |state| match state {
@@ -124,10 +965,7 @@ impl Command<AccountEntity> for Deposit {
                         panic!("invalid command Deposit in state Nonexistent")
                     }
 
-                    AccountEntity::Existing { balance } => Account {
-                        id,
-                        balance: *balance,
-                    },
+                    AccountEntity::Existing(state) => state.account(id),
                 })
             }
         }
@@ -138,6 +976,21 @@ impl Command<AccountEntity> for Deposit {
 pub enum DepositError {
     #[error("account with ID {0} not found")]
     NotFound(Uuid),
+
+    #[error("product of account with ID {0} does not allow deposits")]
+    NotAllowed(Uuid),
+
+    #[error(
+        "amount for account with ID {0} must be positive and at most {}",
+        MAX_AMOUNT
+    )]
+    InvalidAmount(Uuid),
+
+    #[error("deposit would overflow balance of account with ID {0}")]
+    BalanceOverflow(Uuid),
+
+    #[error("account with ID {0} is frozen or closed")]
+    NotOpen(Uuid),
 }
 
 // Command: Withdraw ===============================================================================
@@ -145,16 +998,20 @@ pub enum DepositError {
 #[derive(Debug)]
 pub struct Withdraw {
     amount: u64,
+    at: OffsetDateTime,
 }
 
-impl From<u64> for Withdraw {
-    fn from(amount: u64) -> Self {
-        Self { amount }
+impl Withdraw {
+    /// Withdraw the given amount at the given time. The fees charged with the withdrawal, including
+    /// the overdraft fee, are debited together with the amount, hence the balance after deducting
+    /// both must not exceed the overdraft of the product.
+    pub fn new(amount: u64, at: OffsetDateTime) -> Self {
+        Self { amount, at }
     }
 }
 
 impl Command<AccountEntity> for Withdraw {
-    type Reply = Account;
+    type Reply = WithdrawReply;
     type Error = WithdrawError;
 
     fn handle_command(
@@ -167,24 +1024,64 @@ impl Command<AccountEntity> for Withdraw {
         match state {
             AccountEntity::Nonexistent => CommandEffect::reject(WithdrawError::NotFound(id)),
 
-            AccountEntity::Existing { balance } if self.amount > *balance => {
-                CommandEffect::reject(WithdrawError::InsufficientBalance(id))
+            AccountEntity::Existing(_) if !is_valid_amount(self.amount) => {
+                CommandEffect::reject(WithdrawError::InvalidAmount(id))
+            }
+
+            AccountEntity::Existing(state) if state.status != AccountStatus::Open => {
+                CommandEffect::reject(WithdrawError::NotOpen(id))
             }
 
-            AccountEntity::Existing { balance } => {
+            AccountEntity::Existing(state) if !state.product.allows(Operation::Withdraw) => {
+                CommandEffect::reject(WithdrawError::NotAllowed(id))
+            }
+
+            AccountEntity::Existing(state) => {
+                let fees = state
+                    .product
+                    .fees
+                    .withdrawal_fees(state.balance, self.amount);
+                let balance = fees
+                    .iter()
+                    .try_fold(self.amount, |debit, fee| debit.checked_add(fee.amount))
+                    .filter(|debit| state.can_debit(*debit, self.at))
+                    .and_then(|debit| state.debited(debit));
+                let Some(balance) = balance else {
+                    return CommandEffect::reject(WithdrawError::InsufficientBalance(id));
+                };
+
+                let exceeded = [LimitPeriod::Daily, LimitPeriod::Monthly]
+                    .into_iter()
+                    .filter_map(|period| {
+                        state
+                            .remaining_allowance(period, self.at)
+                            .map(|remaining| (period, remaining))
+                    })
+                    .find(|(_, remaining)| self.amount > *remaining);
+                if let Some((period, remaining)) = exceeded {
+                    return CommandEffect::reject(WithdrawError::LimitExceeded {
+                        id,
+                        period,
+                        remaining,
+                    });
+                }
+
                 let event = AccountEvent::Withdrawn {
                     id,
+                    seq_no: state.seq_no + 1,
                     amount: self.amount,
-                    balance: balance - self.amount,
+                    fees,
+                    balance,
+                    at: self.at,
                 };
                 CommandEffect::emit_and_reply(event, move |state| match state {
                     AccountEntity::Nonexistent => {
                         panic!("invalid command Withdraw in state Nonexistent")
                     }
 
-                    AccountEntity::Existing { balance } => Account {
-                        id,
-                        balance: *balance,
+                    AccountEntity::Existing(state) => WithdrawReply {
+                        account: state.account(id),
+                        seq_no: state.seq_no,
                     },
                 })
             }
@@ -192,11 +1089,1660 @@ impl Command<AccountEntity> for Withdraw {
     }
 }
 
+/// The account after a withdrawal and the sequence number of the withdrawal, e.g. to reverse it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WithdrawReply {
+    pub account: Account,
+    pub seq_no: u64,
+}
+
 #[derive(Debug, Error)]
 pub enum WithdrawError {
     #[error("account with ID {0} not found")]
     NotFound(Uuid),
 
+    #[error("product of account with ID {0} does not allow withdrawals")]
+    NotAllowed(Uuid),
+
+    #[error(
+        "amount for account with ID {0} must be positive and at most {}",
+        MAX_AMOUNT
+    )]
+    InvalidAmount(Uuid),
+
     #[error("account with ID {0} has insufficient balance for withdrawal")]
     InsufficientBalance(Uuid),
+
+    #[error("account with ID {0} is frozen or closed")]
+    NotOpen(Uuid),
+
+    #[error(
+        "withdrawal exceeds {period} limit of account with ID {id}, remaining allowance is \
+         {remaining}"
+    )]
+    LimitExceeded {
+        id: Uuid,
+        period: LimitPeriod,
+        remaining: u64,
+    },
+}
+
+// Command: SetWithdrawalLimits ====================================================================
+
+#[derive(Debug)]
+pub struct SetWithdrawalLimits {
+    limits: WithdrawalLimits,
+}
+
+impl From<WithdrawalLimits> for SetWithdrawalLimits {
+    fn from(limits: WithdrawalLimits) -> Self {
+        Self { limits }
+    }
+}
+
+impl Command<AccountEntity> for SetWithdrawalLimits {
+    type Reply = WithdrawalLimits;
+    type Error = SetWithdrawalLimitsError;
+
+    fn handle_command(
+        self,
+        id: &Uuid,
+        state: &AccountEntity,
+    ) -> CommandEffect<AccountEntity, Self::Reply, Self::Error> {
+        let id = *id;
+        let limits = self.limits;
+
+        match state {
+            AccountEntity::Nonexistent => {
+                CommandEffect::reject(SetWithdrawalLimitsError::NotFound(id))
+            }
+
+            AccountEntity::Existing(_) => match (limits.daily, limits.monthly) {
+                (Some(daily), Some(monthly)) if daily > monthly => {
+                    CommandEffect::reject(SetWithdrawalLimitsError::DailyExceedsMonthly(id))
+                }
+
+                _ => {
+                    let event = AccountEvent::WithdrawalLimitsSet { id, limits };
+                    CommandEffect::emit_and_reply(event, move |_| limits)
+                }
+            },
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SetWithdrawalLimitsError {
+    #[error("account with ID {0} not found")]
+    NotFound(Uuid),
+
+    #[error("daily withdrawal limit for account with ID {0} must not exceed monthly limit")]
+    DailyExceedsMonthly(Uuid),
+}
+
+// Command: PlaceHold ==============================================================================
+
+#[derive(Debug)]
+pub struct PlaceHold {
+    hold_id: Uuid,
+    amount: u64,
+    expires_at: OffsetDateTime,
+    at: OffsetDateTime,
+}
+
+impl PlaceHold {
+    pub fn new(hold_id: Uuid, amount: u64, expires_at: OffsetDateTime, at: OffsetDateTime) -> Self {
+        Self {
+            hold_id,
+            amount,
+            expires_at,
+            at,
+        }
+    }
+}
+
+impl Command<AccountEntity> for PlaceHold {
+    type Reply = Hold;
+    type Error = PlaceHoldError;
+
+    fn handle_command(
+        self,
+        id: &Uuid,
+        state: &AccountEntity,
+    ) -> CommandEffect<AccountEntity, Self::Reply, Self::Error> {
+        let id = *id;
+
+        match state {
+            AccountEntity::Nonexistent => CommandEffect::reject(PlaceHoldError::NotFound(id)),
+
+            AccountEntity::Existing(_) if !is_valid_amount(self.amount) => {
+                CommandEffect::reject(PlaceHoldError::InvalidAmount(id))
+            }
+
+            AccountEntity::Existing(state) if state.status != AccountStatus::Open => {
+                CommandEffect::reject(PlaceHoldError::NotOpen(id))
+            }
+
+            AccountEntity::Existing(_) if self.expires_at <= self.at => {
+                CommandEffect::reject(PlaceHoldError::InvalidExpiry(id))
+            }
+
+            AccountEntity::Existing(state) if !state.product.allows(Operation::Withdraw) => {
+                CommandEffect::reject(PlaceHoldError::NotAllowed(id))
+            }
+
+            AccountEntity::Existing(state) if !state.can_debit(self.amount, self.at) => {
+                CommandEffect::reject(PlaceHoldError::InsufficientBalance(id))
+            }
+
+            AccountEntity::Existing(_) => {
+                let hold = Hold {
+                    id: self.hold_id,
+                    amount: self.amount,
+                    expires_at: self.expires_at,
+                };
+                let event = AccountEvent::HoldPlaced {
+                    id,
+                    hold_id: self.hold_id,
+                    amount: self.amount,
+                    expires_at: self.expires_at,
+                    at: self.at,
+                };
+                CommandEffect::emit_and_reply(event, move |_| hold)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum PlaceHoldError {
+    #[error("account with ID {0} not found")]
+    NotFound(Uuid),
+
+    #[error("product of account with ID {0} does not allow holds")]
+    NotAllowed(Uuid),
+
+    #[error("account with ID {0} has insufficient available balance for hold")]
+    InsufficientBalance(Uuid),
+
+    #[error("hold for account with ID {0} must expire in the future")]
+    InvalidExpiry(Uuid),
+
+    #[error("account with ID {0} is frozen or closed")]
+    NotOpen(Uuid),
+
+    #[error(
+        "amount for account with ID {0} must be positive and at most {}",
+        MAX_AMOUNT
+    )]
+    InvalidAmount(Uuid),
+}
+
+// Command: CaptureHold ============================================================================
+
+#[derive(Debug)]
+pub struct CaptureHold {
+    hold_id: Uuid,
+    at: OffsetDateTime,
+}
+
+impl CaptureHold {
+    pub fn new(hold_id: Uuid, at: OffsetDateTime) -> Self {
+        Self { hold_id, at }
+    }
+}
+
+impl Command<AccountEntity> for CaptureHold {
+    type Reply = Account;
+    type Error = CaptureHoldError;
+
+    fn handle_command(
+        self,
+        id: &Uuid,
+        state: &AccountEntity,
+    ) -> CommandEffect<AccountEntity, Self::Reply, Self::Error> {
+        let id = *id;
+        let hold_id = self.hold_id;
+
+        match state {
+            AccountEntity::Nonexistent => CommandEffect::reject(CaptureHoldError::NotFound(id)),
+
+            AccountEntity::Existing(state) if state.status != AccountStatus::Open => {
+                CommandEffect::reject(CaptureHoldError::NotOpen(id))
+            }
+
+            AccountEntity::Existing(state) => match state.hold(hold_id) {
+                None => CommandEffect::reject(CaptureHoldError::HoldNotFound { id, hold_id }),
+
+                Some(hold) if hold.expires_at <= self.at => {
+                    CommandEffect::reject(CaptureHoldError::HoldExpired { id, hold_id })
+                }
+
+                Some(hold) => {
+                    let Some(balance) = state.debited(hold.amount) else {
+                        return CommandEffect::reject(CaptureHoldError::BalanceOverflow(id));
+                    };
+                    let event = AccountEvent::HoldCaptured {
+                        id,
+                        seq_no: state.seq_no + 1,
+                        hold_id,
+                        amount: hold.amount,
+                        balance,
+                        at: self.at,
+                    };
+                    CommandEffect::emit_and_reply(event, move |state| match state {
+                        AccountEntity::Nonexistent => {
+                            panic!("invalid command CaptureHold in state Nonexistent")
+                        }
+
+                        AccountEntity::Existing(state) => state.account(id),
+                    })
+                }
+            },
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum CaptureHoldError {
+    #[error("account with ID {0} not found")]
+    NotFound(Uuid),
+
+    #[error("hold with ID {hold_id} for account with ID {id} not found")]
+    HoldNotFound { id: Uuid, hold_id: Uuid },
+
+    #[error("hold with ID {hold_id} for account with ID {id} has expired")]
+    HoldExpired { id: Uuid, hold_id: Uuid },
+
+    #[error("capture would overflow balance of account with ID {0}")]
+    BalanceOverflow(Uuid),
+
+    #[error("account with ID {0} is frozen or closed")]
+    NotOpen(Uuid),
+}
+
+// Command: ReleaseHold ============================================================================
+
+#[derive(Debug)]
+pub struct ReleaseHold {
+    hold_id: Uuid,
+}
+
+impl From<Uuid> for ReleaseHold {
+    fn from(hold_id: Uuid) -> Self {
+        Self { hold_id }
+    }
+}
+
+impl Command<AccountEntity> for ReleaseHold {
+    type Reply = ();
+    type Error = ReleaseHoldError;
+
+    fn handle_command(
+        self,
+        id: &Uuid,
+        state: &AccountEntity,
+    ) -> CommandEffect<AccountEntity, Self::Reply, Self::Error> {
+        let id = *id;
+        let hold_id = self.hold_id;
+
+        match state {
+            AccountEntity::Nonexistent => CommandEffect::reject(ReleaseHoldError::NotFound(id)),
+
+            AccountEntity::Existing(state) if state.hold(hold_id).is_none() => {
+                CommandEffect::reject(ReleaseHoldError::HoldNotFound { id, hold_id })
+            }
+
+            AccountEntity::Existing(_) => {
+                let event = AccountEvent::HoldReleased { id, hold_id };
+                CommandEffect::emit_and_reply(event, |_| ())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ReleaseHoldError {
+    #[error("account with ID {0} not found")]
+    NotFound(Uuid),
+
+    #[error("hold with ID {hold_id} for account with ID {id} not found")]
+    HoldNotFound { id: Uuid, hold_id: Uuid },
+}
+
+// Command: Reverse ================================================================================
+
+#[derive(Debug)]
+pub struct Reverse {
+    transaction_ref: u64,
+    at: OffsetDateTime,
+}
+
+impl Reverse {
+    /// Reverse the deposit or withdrawal with the given sequence number, as long as it is within
+    /// the [REVERSAL_WINDOW]. Reversing a withdrawal refunds its fees, unless already waived, and
+    /// frees its amount from the withdrawal limits.
+    pub fn new(transaction_ref: u64, at: OffsetDateTime) -> Self {
+        Self {
+            transaction_ref,
+            at,
+        }
+    }
+}
+
+impl Command<AccountEntity> for Reverse {
+    type Reply = Account;
+    type Error = ReverseError;
+
+    fn handle_command(
+        self,
+        id: &Uuid,
+        state: &AccountEntity,
+    ) -> CommandEffect<AccountEntity, Self::Reply, Self::Error> {
+        let id = *id;
+        let transaction_ref = self.transaction_ref;
+
+        match state {
+            AccountEntity::Nonexistent => CommandEffect::reject(ReverseError::NotFound(id)),
+
+            AccountEntity::Existing(state) => {
+                match state.reversible_transaction(transaction_ref, self.at) {
+                    None => CommandEffect::reject(ReverseError::TransactionNotFound {
+                        id,
+                        transaction_ref,
+                    }),
+
+                    Some(transaction) if transaction.reversed => {
+                        CommandEffect::reject(ReverseError::AlreadyReversed {
+                            id,
+                            transaction_ref,
+                        })
+                    }
+
+                    Some(transaction)
+                        if transaction.kind == TransactionKind::Deposit
+                            && !state.can_debit(transaction.amount, self.at) =>
+                    {
+                        CommandEffect::reject(ReverseError::InsufficientBalance(id))
+                    }
+
+                    Some(transaction) => {
+                        // Fees of a withdrawal are refunded unless already waived.
+                        let fee = state
+                            .waivable_fee(transaction_ref, self.at)
+                            .filter(|fee| !fee.waived)
+                            .map(|fee| fee.amount)
+                            .unwrap_or_default();
+                        let balance = if transaction.kind == TransactionKind::Deposit {
+                            state.debited(transaction.amount)
+                        } else {
+                            transaction
+                                .amount
+                                .checked_add(fee)
+                                .and_then(|amount| state.credited(amount))
+                        };
+                        let Some(balance) = balance else {
+                            return CommandEffect::reject(ReverseError::BalanceOverflow(id));
+                        };
+                        let event = AccountEvent::Reversed {
+                            id,
+                            seq_no: state.seq_no + 1,
+                            transaction_ref,
+                            amount: transaction.amount,
+                            fee,
+                            balance,
+                            at: self.at,
+                        };
+                        CommandEffect::emit_and_reply(event, move |state| match state {
+                            AccountEntity::Nonexistent => {
+                                panic!("invalid command Reverse in state Nonexistent")
+                            }
+
+                            AccountEntity::Existing(state) => state.account(id),
+                        })
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ReverseError {
+    #[error("account with ID {0} not found")]
+    NotFound(Uuid),
+
+    #[error(
+        "deposit or withdrawal with sequence number {transaction_ref} for account with ID {id} \
+         not found"
+    )]
+    TransactionNotFound { id: Uuid, transaction_ref: u64 },
+
+    #[error(
+        "transaction with sequence number {transaction_ref} for account with ID {id} has already \
+         been reversed"
+    )]
+    AlreadyReversed { id: Uuid, transaction_ref: u64 },
+
+    #[error("account with ID {0} has insufficient balance for reversal of deposit")]
+    InsufficientBalance(Uuid),
+
+    #[error("reversal would overflow balance of account with ID {0}")]
+    BalanceOverflow(Uuid),
+}
+
+// Command: GetHolds ===============================================================================
+
+#[derive(Debug)]
+pub struct GetHolds {
+    at: OffsetDateTime,
+}
+
+impl From<OffsetDateTime> for GetHolds {
+    fn from(at: OffsetDateTime) -> Self {
+        Self { at }
+    }
+}
+
+/// Ledger and available balance together with the holds which have not yet expired.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct Holds {
+    pub balance: i64,
+    pub available_balance: i64,
+    pub holds: Vec<Hold>,
+}
+
+impl Command<AccountEntity> for GetHolds {
+    type Reply = Holds;
+    type Error = GetHoldsError;
+
+    fn handle_command(
+        self,
+        id: &Uuid,
+        state: &AccountEntity,
+    ) -> CommandEffect<AccountEntity, Self::Reply, Self::Error> {
+        match state {
+            AccountEntity::Nonexistent => CommandEffect::reject(GetHoldsError::NotFound(*id)),
+
+            AccountEntity::Existing(state) => {
+                let holds = state
+                    .holds
+                    .iter()
+                    .filter(|hold| hold.expires_at > self.at)
+                    .cloned()
+                    .collect();
+                CommandEffect::reply(Holds {
+                    balance: state.balance,
+                    available_balance: state.available_balance(self.at),
+                    holds,
+                })
+            }
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum GetHoldsError {
+    #[error("account with ID {0} not found")]
+    NotFound(Uuid),
+}
+
+// Command: AccrueInterest =========================================================================
+
+#[derive(Debug)]
+pub struct AccrueInterest {
+    through: Date,
+}
+
+impl From<Date> for AccrueInterest {
+    /// Accrue interest on the end-of-day balance of the (UTC) date following the last one interest
+    /// has been accrued for, as long as not after the given date, which is used for accounts which
+    /// have never accrued interest. Hence accruing repeatedly catches up day by day.
+    fn from(through: Date) -> Self {
+        Self { through }
+    }
+}
+
+/// The interest accrued for a date in millionths of the smallest currency unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Accrual {
+    pub date: Date,
+    pub amount: u64,
+}
+
+impl Command<AccountEntity> for AccrueInterest {
+    type Reply = Accrual;
+    type Error = AccrueInterestError;
+
+    fn handle_command(
+        self,
+        id: &Uuid,
+        state: &AccountEntity,
+    ) -> CommandEffect<AccountEntity, Self::Reply, Self::Error> {
+        let id = *id;
+
+        match state {
+            AccountEntity::Nonexistent => CommandEffect::reject(AccrueInterestError::NotFound(id)),
+
+            AccountEntity::Existing(state) => {
+                let date = match state.accrued_through {
+                    Some(accrued_through) => accrued_through.next_day().expect("valid date"),
+                    None => self.through,
+                };
+                if date > self.through {
+                    return CommandEffect::reject(AccrueInterestError::AlreadyAccrued {
+                        id,
+                        date: self.through,
+                    });
+                }
+
+                let amount = state.product.day_count.daily_interest(
+                    state.end_of_day_balance(date),
+                    state.product.interest_rate_bps,
+                    date,
+                );
+                let event = AccountEvent::InterestAccrued { id, date, amount };
+                CommandEffect::emit_and_reply(event, move |_| Accrual { date, amount })
+            }
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum AccrueInterestError {
+    #[error("account with ID {0} not found")]
+    NotFound(Uuid),
+
+    #[error("interest for account with ID {id} already accrued for {date}")]
+    AlreadyAccrued { id: Uuid, date: Date },
+}
+
+// Command: PostInterest ===========================================================================
+
+#[derive(Debug)]
+pub struct PostInterest {
+    at: OffsetDateTime,
+}
+
+impl From<OffsetDateTime> for PostInterest {
+    fn from(at: OffsetDateTime) -> Self {
+        Self { at }
+    }
+}
+
+impl Command<AccountEntity> for PostInterest {
+    type Reply = Account;
+    type Error = PostInterestError;
+
+    fn handle_command(
+        self,
+        id: &Uuid,
+        state: &AccountEntity,
+    ) -> CommandEffect<AccountEntity, Self::Reply, Self::Error> {
+        let id = *id;
+
+        match state {
+            AccountEntity::Nonexistent => CommandEffect::reject(PostInterestError::NotFound(id)),
+
+            AccountEntity::Existing(state) => {
+                let amount = state.accrued_interest / MICROS_PER_UNIT;
+                if amount == 0 {
+                    return CommandEffect::reply(state.account(id));
+                }
+
+                let Some(balance) = state.credited(amount) else {
+                    return CommandEffect::reject(PostInterestError::BalanceOverflow(id));
+                };
+                let event = AccountEvent::InterestPosted {
+                    id,
+                    seq_no: state.seq_no + 1,
+                    amount,
+                    balance,
+                    at: self.at,
+                };
+                CommandEffect::emit_and_reply(event, move |state| match state {
+                    AccountEntity::Nonexistent => {
+                        panic!("invalid command PostInterest in state Nonexistent")
+                    }
+
+                    AccountEntity::Existing(state) => state.account(id),
+                })
+            }
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum PostInterestError {
+    #[error("account with ID {0} not found")]
+    NotFound(Uuid),
+
+    #[error("interest would overflow balance of account with ID {0}")]
+    BalanceOverflow(Uuid),
+}
+
+// Command: ChargeMaintenanceFee ===================================================================
+
+#[derive(Debug)]
+pub struct ChargeMaintenanceFee {
+    at: OffsetDateTime,
+}
+
+impl From<OffsetDateTime> for ChargeMaintenanceFee {
+    /// Charge the monthly maintenance fee, at most once per (UTC) calendar month of `at`. The fee
+    /// is always charged in full, even if the balance thereby exceeds the overdraft of the
+    /// product, because it is owed for keeping the account, not for moving money.
+    fn from(at: OffsetDateTime) -> Self {
+        Self { at }
+    }
+}
+
+impl Command<AccountEntity> for ChargeMaintenanceFee {
+    type Reply = Account;
+    type Error = ChargeMaintenanceFeeError;
+
+    fn handle_command(
+        self,
+        id: &Uuid,
+        state: &AccountEntity,
+    ) -> CommandEffect<AccountEntity, Self::Reply, Self::Error> {
+        let id = *id;
+
+        match state {
+            AccountEntity::Nonexistent => {
+                CommandEffect::reject(ChargeMaintenanceFeeError::NotFound(id))
+            }
+
+            AccountEntity::Existing(state) if state.maintenance_fee_charged(self.at) => {
+                CommandEffect::reject(ChargeMaintenanceFeeError::AlreadyCharged(id))
+            }
+
+            AccountEntity::Existing(state)
+                if state.product.fees.monthly_maintenance == 0
+                    || state.status == AccountStatus::Closed =>
+            {
+                CommandEffect::reply(state.account(id))
+            }
+
+            AccountEntity::Existing(state) => {
+                let fee = Fee {
+                    kind: FeeKind::MonthlyMaintenance,
+                    amount: state.product.fees.monthly_maintenance,
+                };
+                let Some(balance) = state.debited(fee.amount) else {
+                    return CommandEffect::reject(ChargeMaintenanceFeeError::BalanceOverflow(id));
+                };
+                let event = AccountEvent::FeeCharged {
+                    id,
+                    seq_no: state.seq_no + 1,
+                    fee,
+                    balance,
+                    at: self.at,
+                };
+                CommandEffect::emit_and_reply(event, move |state| match state {
+                    AccountEntity::Nonexistent => {
+                        panic!("invalid command ChargeMaintenanceFee in state Nonexistent")
+                    }
+
+                    AccountEntity::Existing(state) => state.account(id),
+                })
+            }
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ChargeMaintenanceFeeError {
+    #[error("account with ID {0} not found")]
+    NotFound(Uuid),
+
+    #[error("maintenance fee for account with ID {0} already charged for this month")]
+    AlreadyCharged(Uuid),
+
+    #[error("maintenance fee would overflow balance of account with ID {0}")]
+    BalanceOverflow(Uuid),
+}
+
+// Command: WaiveFee ===============================================================================
+
+#[derive(Debug)]
+pub struct WaiveFee {
+    transaction_ref: u64,
+    at: OffsetDateTime,
+}
+
+impl WaiveFee {
+    /// Refund the fees charged with the transaction with the given sequence number, as long as it
+    /// is within the [REVERSAL_WINDOW].
+    pub fn new(transaction_ref: u64, at: OffsetDateTime) -> Self {
+        Self {
+            transaction_ref,
+            at,
+        }
+    }
+}
+
+impl Command<AccountEntity> for WaiveFee {
+    type Reply = Account;
+    type Error = WaiveFeeError;
+
+    fn handle_command(
+        self,
+        id: &Uuid,
+        state: &AccountEntity,
+    ) -> CommandEffect<AccountEntity, Self::Reply, Self::Error> {
+        let id = *id;
+        let transaction_ref = self.transaction_ref;
+
+        match state {
+            AccountEntity::Nonexistent => CommandEffect::reject(WaiveFeeError::NotFound(id)),
+
+            AccountEntity::Existing(state) => match state.waivable_fee(transaction_ref, self.at) {
+                None => CommandEffect::reject(WaiveFeeError::FeeNotFound {
+                    id,
+                    transaction_ref,
+                }),
+
+                Some(fee) if fee.waived => CommandEffect::reject(WaiveFeeError::AlreadyWaived {
+                    id,
+                    transaction_ref,
+                }),
+
+                Some(fee) => {
+                    let Some(balance) = state.credited(fee.amount) else {
+                        return CommandEffect::reject(WaiveFeeError::BalanceOverflow(id));
+                    };
+                    let event = AccountEvent::FeeWaived {
+                        id,
+                        seq_no: state.seq_no + 1,
+                        transaction_ref,
+                        amount: fee.amount,
+                        balance,
+                        at: self.at,
+                    };
+                    CommandEffect::emit_and_reply(event, move |state| match state {
+                        AccountEntity::Nonexistent => {
+                            panic!("invalid command WaiveFee in state Nonexistent")
+                        }
+
+                        AccountEntity::Existing(state) => state.account(id),
+                    })
+                }
+            },
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum WaiveFeeError {
+    #[error("account with ID {0} not found")]
+    NotFound(Uuid),
+
+    #[error("fees with sequence number {transaction_ref} for account with ID {id} not found")]
+    FeeNotFound { id: Uuid, transaction_ref: u64 },
+
+    #[error("fees with sequence number {transaction_ref} for account with ID {id} already waived")]
+    AlreadyWaived { id: Uuid, transaction_ref: u64 },
+
+    #[error("waiver would overflow balance of account with ID {0}")]
+    BalanceOverflow(Uuid),
+}
+
+// Command: SetAccountStatus =======================================================================
+
+#[derive(Debug)]
+pub struct SetAccountStatus {
+    status: AccountStatus,
+    at: OffsetDateTime,
+}
+
+impl SetAccountStatus {
+    /// Freeze, unfreeze or close the account; closing is final and requires a zero balance.
+    pub fn new(status: AccountStatus, at: OffsetDateTime) -> Self {
+        Self { status, at }
+    }
+}
+
+impl Command<AccountEntity> for SetAccountStatus {
+    type Reply = AccountStatus;
+    type Error = SetAccountStatusError;
+
+    fn handle_command(
+        self,
+        id: &Uuid,
+        state: &AccountEntity,
+    ) -> CommandEffect<AccountEntity, Self::Reply, Self::Error> {
+        let id = *id;
+        let status = self.status;
+
+        match state {
+            AccountEntity::Nonexistent => {
+                CommandEffect::reject(SetAccountStatusError::NotFound(id))
+            }
+
+            AccountEntity::Existing(state) if state.status == status => {
+                CommandEffect::reply(status)
+            }
+
+            AccountEntity::Existing(state) if state.status == AccountStatus::Closed => {
+                CommandEffect::reject(SetAccountStatusError::Closed(id))
+            }
+
+            AccountEntity::Existing(state)
+                if status == AccountStatus::Closed && state.balance != 0 =>
+            {
+                CommandEffect::reject(SetAccountStatusError::NonZeroBalance(id))
+            }
+
+            AccountEntity::Existing(_) => {
+                let event = AccountEvent::StatusChanged {
+                    id,
+                    status,
+                    at: self.at,
+                };
+                CommandEffect::emit_and_reply(event, move |_| status)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SetAccountStatusError {
+    #[error("account with ID {0} not found")]
+    NotFound(Uuid),
+
+    #[error("account with ID {0} is closed")]
+    Closed(Uuid),
+
+    #[error("account with ID {0} cannot be closed with a non-zero balance")]
+    NonZeroBalance(Uuid),
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{
+        AccountEntity, AccountEvent, AccountState, AccountStatus, CaptureHold, CaptureHoldError,
+        CreateAccount, DayCount, Deposit, DepositError, Fee, FeeKind, FeeSchedule, LimitPeriod,
+        Operation, PlaceHold, PlaceHoldError, Product, ReleaseHold, ReleaseHoldError, Reverse,
+        ReverseError, SetAccountStatus, SetAccountStatusError, SetWithdrawalLimits,
+        SetWithdrawalLimitsError, TransactionKind, Withdraw, WithdrawError, WithdrawalLimits,
+        MAX_AMOUNT, REVERSAL_WINDOW,
+    };
+    use eventsourced::{
+        binarize::serde_json::SerdeJsonBinarize, event_log::test::TestEventLog,
+        snapshot_store::noop::NoopSnapshotStore, EntityRef, EventSourced, EventSourcedExt,
+    };
+    use serde_json::json;
+    use std::num::NonZeroUsize;
+    use time::{macros::datetime, Duration, OffsetDateTime};
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_invalid_amount() {
+        let at = datetime!(2024-07-01 12:00 UTC);
+        let account = spawn(checking()).await;
+
+        for amount in [0, MAX_AMOUNT + 1] {
+            let reply = account
+                .handle_command(Deposit::new(amount, at))
+                .await
+                .unwrap();
+            assert!(matches!(reply, Err(DepositError::InvalidAmount(_))));
+
+            let reply = account
+                .handle_command(Withdraw::new(amount, at))
+                .await
+                .unwrap();
+            assert!(matches!(reply, Err(WithdrawError::InvalidAmount(_))));
+
+            let place_hold = PlaceHold::new(Uuid::now_v7(), amount, at + Duration::hours(1), at);
+            let reply = account.handle_command(place_hold).await.unwrap();
+            assert!(matches!(reply, Err(PlaceHoldError::InvalidAmount(_))));
+        }
+
+        let reply = account
+            .handle_command(Deposit::new(MAX_AMOUNT, at))
+            .await
+            .unwrap();
+        assert!(reply.is_ok_and(|account| account.balance == i64::MAX));
+
+        // Neither the balance overflows nor the withdrawal including its fees.
+        let reply = account.handle_command(Deposit::new(1, at)).await.unwrap();
+        assert!(matches!(reply, Err(DepositError::BalanceOverflow(_))));
+
+        let fees = FeeSchedule {
+            withdrawal: 10,
+            ..Default::default()
+        };
+        let account = spawn(Product { fees, ..checking() }).await;
+        let reply = account
+            .handle_command(Withdraw::new(MAX_AMOUNT, at))
+            .await
+            .unwrap();
+        assert!(matches!(reply, Err(WithdrawError::InsufficientBalance(_))));
+    }
+
+    #[test]
+    fn test_seq_no() {
+        let id = Uuid::now_v7();
+        let hold_id = Uuid::now_v7();
+        let at = datetime!(2024-07-01 12:00 UTC);
+        let events = [
+            created(id),
+            AccountEvent::Deposited {
+                id,
+                seq_no: 2,
+                amount: 100,
+                balance: 100,
+                at,
+            },
+            AccountEvent::WithdrawalLimitsSet {
+                id,
+                limits: WithdrawalLimits::default(),
+            },
+            AccountEvent::HoldPlaced {
+                id,
+                hold_id,
+                amount: 10,
+                expires_at: at + Duration::hours(1),
+                at,
+            },
+            AccountEvent::HoldReleased { id, hold_id },
+            AccountEvent::Withdrawn {
+                id,
+                seq_no: 6,
+                amount: 30,
+                fees: vec![],
+                balance: 70,
+                at,
+            },
+        ];
+
+        let state = replay(events);
+        assert_eq!(state.seq_no, 6);
+        assert_eq!(state.balance, 70);
+        assert!(state.holds.is_empty());
+        let transactions = state
+            .reversible_transactions
+            .iter()
+            .map(|transaction| transaction.seq_no)
+            .collect::<Vec<_>>();
+        assert_eq!(transactions, vec![2, 6]);
+    }
+
+    #[test]
+    fn test_withdrawal_limit_windows() {
+        let id = Uuid::now_v7();
+        let at = datetime!(2024-07-01 12:00 UTC);
+        let events = [
+            created(id),
+            AccountEvent::Deposited {
+                id,
+                seq_no: 2,
+                amount: 1_000,
+                balance: 1_000,
+                at,
+            },
+            AccountEvent::WithdrawalLimitsSet {
+                id,
+                limits: WithdrawalLimits {
+                    daily: Some(100),
+                    monthly: Some(150),
+                },
+            },
+            AccountEvent::Withdrawn {
+                id,
+                seq_no: 4,
+                amount: 60,
+                fees: vec![],
+                balance: 940,
+                at,
+            },
+            AccountEvent::Withdrawn {
+                id,
+                seq_no: 5,
+                amount: 50,
+                fees: vec![],
+                balance: 890,
+                at: at + Duration::days(2),
+            },
+        ];
+
+        let state = replay(events);
+        let daily = |at| state.remaining_allowance(LimitPeriod::Daily, at);
+        let monthly = |at| state.remaining_allowance(LimitPeriod::Monthly, at);
+        assert_eq!(daily(at + Duration::hours(1)), Some(40));
+        assert_eq!(daily(at + Duration::days(1)), Some(100));
+        assert_eq!(daily(at + Duration::days(2)), Some(50));
+        assert_eq!(monthly(at + Duration::days(2)), Some(40));
+        assert_eq!(monthly(datetime!(2024-07-31 23:59 UTC)), Some(40));
+        assert_eq!(monthly(datetime!(2024-08-01 00:00 UTC)), Some(150));
+        assert_eq!(monthly(datetime!(2024-08-01 01:00 +02:00)), Some(40));
+
+        // A reversed withdrawal no longer counts against the limits.
+        let state = AccountEntity::Existing(state).handle_event(AccountEvent::Reversed {
+            id,
+            seq_no: 6,
+            transaction_ref: 5,
+            amount: 50,
+            fee: 0,
+            balance: 940,
+            at: at + Duration::days(2),
+        });
+        let AccountEntity::Existing(state) = state else {
+            panic!("account must exist");
+        };
+        assert_eq!(
+            state.remaining_allowance(LimitPeriod::Monthly, at + Duration::days(2)),
+            Some(90)
+        );
+    }
+
+    #[test]
+    fn test_hold_expiry() {
+        let id = Uuid::now_v7();
+        let at = datetime!(2024-07-01 12:00 UTC);
+        let expires_at = at + Duration::hours(1);
+        let events = [
+            created(id),
+            AccountEvent::Deposited {
+                id,
+                seq_no: 2,
+                amount: 100,
+                balance: 100,
+                at,
+            },
+            AccountEvent::HoldPlaced {
+                id,
+                hold_id: Uuid::now_v7(),
+                amount: 30,
+                expires_at,
+                at,
+            },
+        ];
+
+        let state = replay(events);
+        assert_eq!(state.available_balance(at), 70);
+        assert!(!state.can_debit(80, at));
+        assert_eq!(state.available_balance(expires_at), 100);
+        assert!(state.can_debit(80, expires_at));
+
+        // Expired holds are dropped when the next hold is placed.
+        let hold_id = Uuid::now_v7();
+        let state = AccountEntity::Existing(state).handle_event(AccountEvent::HoldPlaced {
+            id,
+            hold_id,
+            amount: 10,
+            expires_at: expires_at + Duration::hours(1),
+            at: expires_at,
+        });
+        let AccountEntity::Existing(state) = state else {
+            panic!("account must exist");
+        };
+        assert_eq!(
+            state.holds.iter().map(|hold| hold.id).collect::<Vec<_>>(),
+            vec![hold_id]
+        );
+    }
+
+    #[test]
+    fn test_reversal() {
+        let id = Uuid::now_v7();
+        let at = datetime!(2024-07-01 12:00 UTC);
+        let events = [
+            created(id),
+            AccountEvent::Deposited {
+                id,
+                seq_no: 2,
+                amount: 100,
+                balance: 100,
+                at,
+            },
+            AccountEvent::Withdrawn {
+                id,
+                seq_no: 3,
+                amount: 50,
+                fees: vec![Fee {
+                    kind: FeeKind::Withdrawal,
+                    amount: 5,
+                }],
+                balance: 45,
+                at,
+            },
+        ];
+
+        let state = replay(events);
+        assert!(state
+            .reversible_transaction(3, at)
+            .is_some_and(|transaction| !transaction.reversed));
+        assert!(state.waivable_fee(3, at).is_some_and(|fee| !fee.waived));
+
+        let state = AccountEntity::Existing(state).handle_event(AccountEvent::Reversed {
+            id,
+            seq_no: 4,
+            transaction_ref: 3,
+            amount: 50,
+            fee: 5,
+            balance: 100,
+            at,
+        });
+        let AccountEntity::Existing(state) = state else {
+            panic!("account must exist");
+        };
+        assert_eq!(state.seq_no, 4);
+        assert_eq!(state.balance, 100);
+        assert!(state.recent_withdrawals.is_empty());
+
+        // Neither the withdrawal can be reversed again nor its refunded fees be waived.
+        assert!(state
+            .reversible_transaction(3, at)
+            .is_some_and(|transaction| transaction.reversed));
+        assert!(state.waivable_fee(3, at).is_some_and(|fee| fee.waived));
+        assert!(state
+            .reversible_transaction(2, at)
+            .is_some_and(|transaction| !transaction.reversed));
+    }
+
+    #[test]
+    fn test_reversal_window() {
+        let id = Uuid::now_v7();
+        let at = datetime!(2024-07-01 12:00 UTC);
+        let events = [
+            created(id),
+            AccountEvent::Deposited {
+                id,
+                seq_no: 2,
+                amount: 100,
+                balance: 100,
+                at,
+            },
+            AccountEvent::Withdrawn {
+                id,
+                seq_no: 3,
+                amount: 50,
+                fees: vec![Fee {
+                    kind: FeeKind::Withdrawal,
+                    amount: 5,
+                }],
+                balance: 45,
+                at,
+            },
+        ];
+
+        let state = replay(events);
+        let expired_at = at + REVERSAL_WINDOW;
+        assert!(state
+            .reversible_transaction(3, expired_at - Duration::seconds(1))
+            .is_some());
+        assert!(state.reversible_transaction(3, expired_at).is_none());
+        assert!(state.waivable_fee(3, expired_at).is_none());
+
+        // Expired transactions and fees are dropped with the next transaction.
+        let state = AccountEntity::Existing(state).handle_event(AccountEvent::Deposited {
+            id,
+            seq_no: 4,
+            amount: 10,
+            balance: 55,
+            at: expired_at,
+        });
+        let AccountEntity::Existing(state) = state else {
+            panic!("account must exist");
+        };
+        assert_eq!(
+            state
+                .reversible_transactions
+                .iter()
+                .map(|transaction| transaction.seq_no)
+                .collect::<Vec<_>>(),
+            vec![4]
+        );
+        assert!(state.waivable_fees.is_empty());
+    }
+
+    #[test]
+    fn test_maintenance_fee_charged() {
+        let id = Uuid::now_v7();
+        let events = [
+            AccountEvent::Created {
+                id,
+                holders: vec![],
+                product: checking(),
+            },
+            // August 1 in UTC.
+            AccountEvent::FeeCharged {
+                id,
+                seq_no: 2,
+                fee: Fee {
+                    kind: FeeKind::MonthlyMaintenance,
+                    amount: 500,
+                },
+                balance: -500,
+                at: datetime!(2024-07-31 23:30 -01:00),
+            },
+        ];
+        let state = replay(events);
+
+        assert!(!state.maintenance_fee_charged(datetime!(2024-07-31 12:00 UTC)));
+        assert!(state.maintenance_fee_charged(datetime!(2024-08-31 23:00 UTC)));
+        assert!(state.maintenance_fee_charged(datetime!(2024-09-01 00:30 +02:00)));
+        assert!(!state.maintenance_fee_charged(datetime!(2024-09-01 00:00 UTC)));
+    }
+
+    #[test]
+    fn test_legacy_created() {
+        let id = Uuid::now_v7();
+        let created =
+            serde_json::from_value::<AccountEvent>(json!({ "Created": { "id": id } })).unwrap();
+
+        let entity = AccountEntity::default().handle_event(created);
+        let AccountEntity::Existing(state) = entity else {
+            panic!("account must exist");
+        };
+        assert!(state.holders.is_empty());
+        assert_eq!(state.product, Product::legacy());
+    }
+
+    #[test]
+    fn test_legacy_events() {
+        let id = Uuid::now_v7();
+        let created = AccountEvent::Created {
+            id,
+            holders: vec![Uuid::now_v7()],
+            product: checking(),
+        };
+        let deposited = serde_json::from_value::<AccountEvent>(
+            json!({ "Deposited": { "id": id, "amount": 100, "balance": 100 } }),
+        )
+        .unwrap();
+        let withdrawn = serde_json::from_value::<AccountEvent>(
+            json!({ "Withdrawn": { "id": id, "amount": 30, "balance": 70 } }),
+        )
+        .unwrap();
+        assert!(matches!(
+            withdrawn,
+            AccountEvent::Withdrawn { seq_no: 0, ref fees, at, .. }
+                if fees.is_empty() && at == OffsetDateTime::UNIX_EPOCH
+        ));
+
+        let entity = [created, deposited, withdrawn]
+            .into_iter()
+            .fold(AccountEntity::default(), AccountEntity::handle_event);
+        let AccountEntity::Existing(state) = entity else {
+            panic!("account must exist");
+        };
+        assert_eq!(state.seq_no, 3);
+        assert_eq!(state.balance, 70);
+        let transactions = state
+            .reversible_transactions
+            .iter()
+            .map(|transaction| (transaction.seq_no, transaction.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            transactions,
+            vec![
+                (2, TransactionKind::Deposit),
+                (3, TransactionKind::Withdrawal)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_withdraw_limit_exceeded() {
+        let at = datetime!(2024-07-01 12:00 UTC);
+        let account = spawn(checking()).await;
+        account
+            .handle_command(Deposit::new(1_000, at))
+            .await
+            .unwrap()
+            .unwrap();
+        let limits = WithdrawalLimits {
+            daily: Some(100),
+            monthly: Some(150),
+        };
+        account
+            .handle_command(SetWithdrawalLimits::from(limits))
+            .await
+            .unwrap()
+            .unwrap();
+
+        // Up to the daily limit.
+        let reply = account
+            .handle_command(Withdraw::new(100, at))
+            .await
+            .unwrap();
+        assert!(reply.is_ok());
+        let reply = account
+            .handle_command(Withdraw::new(1, at + Duration::hours(23)))
+            .await
+            .unwrap();
+        assert!(matches!(
+            reply,
+            Err(WithdrawError::LimitExceeded {
+                period: LimitPeriod::Daily,
+                remaining: 0,
+                ..
+            })
+        ));
+
+        // The daily window has passed, but only 50 remain for the month.
+        let reply = account
+            .handle_command(Withdraw::new(51, at + Duration::days(1)))
+            .await
+            .unwrap();
+        assert!(matches!(
+            reply,
+            Err(WithdrawError::LimitExceeded {
+                period: LimitPeriod::Monthly,
+                remaining: 50,
+                ..
+            })
+        ));
+        let reply = account
+            .handle_command(Withdraw::new(50, at + Duration::days(1)))
+            .await
+            .unwrap();
+        assert!(reply.is_ok());
+
+        // The monthly limit is per calendar month.
+        let reply = account
+            .handle_command(Withdraw::new(1, datetime!(2024-07-31 23:59 UTC)))
+            .await
+            .unwrap();
+        assert!(matches!(
+            reply,
+            Err(WithdrawError::LimitExceeded {
+                period: LimitPeriod::Monthly,
+                remaining: 0,
+                ..
+            })
+        ));
+        let reply = account
+            .handle_command(Withdraw::new(100, datetime!(2024-08-01 00:00 UTC)))
+            .await
+            .unwrap();
+        assert!(reply.is_ok_and(|reply| reply.account.balance == 750));
+    }
+
+    #[tokio::test]
+    async fn test_set_withdrawal_limits() {
+        let account = spawn(checking()).await;
+
+        let limits = WithdrawalLimits {
+            daily: Some(200),
+            monthly: Some(100),
+        };
+        let reply = account
+            .handle_command(SetWithdrawalLimits::from(limits))
+            .await
+            .unwrap();
+        assert!(matches!(
+            reply,
+            Err(SetWithdrawalLimitsError::DailyExceedsMonthly(_))
+        ));
+
+        for limits in [
+            WithdrawalLimits {
+                daily: Some(100),
+                monthly: Some(100),
+            },
+            WithdrawalLimits {
+                daily: Some(200),
+                monthly: None,
+            },
+            WithdrawalLimits {
+                daily: None,
+                monthly: Some(100),
+            },
+            WithdrawalLimits::default(),
+        ] {
+            let reply = account
+                .handle_command(SetWithdrawalLimits::from(limits))
+                .await
+                .unwrap();
+            assert!(reply.is_ok_and(|reply| reply == limits));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hold_errors() {
+        let at = datetime!(2024-07-01 12:00 UTC);
+        let account = spawn(checking()).await;
+        account
+            .handle_command(Deposit::new(100, at))
+            .await
+            .unwrap()
+            .unwrap();
+        let hold = account
+            .handle_command(PlaceHold::new(
+                Uuid::now_v7(),
+                50,
+                at + Duration::hours(1),
+                at,
+            ))
+            .await
+            .unwrap()
+            .unwrap();
+
+        let unknown_hold_id = Uuid::now_v7();
+        let reply = account
+            .handle_command(CaptureHold::new(unknown_hold_id, at))
+            .await
+            .unwrap();
+        assert!(matches!(
+            reply,
+            Err(CaptureHoldError::HoldNotFound { hold_id, .. }) if hold_id == unknown_hold_id
+        ));
+        let reply = account
+            .handle_command(ReleaseHold::from(unknown_hold_id))
+            .await
+            .unwrap();
+        assert!(matches!(
+            reply,
+            Err(ReleaseHoldError::HoldNotFound { hold_id, .. }) if hold_id == unknown_hold_id
+        ));
+
+        // A hold cannot be captured once it expires.
+        let reply = account
+            .handle_command(CaptureHold::new(hold.id, at + Duration::hours(1)))
+            .await
+            .unwrap();
+        assert!(matches!(
+            reply,
+            Err(CaptureHoldError::HoldExpired { hold_id, .. }) if hold_id == hold.id
+        ));
+        let reply = account
+            .handle_command(CaptureHold::new(hold.id, at + Duration::minutes(59)))
+            .await
+            .unwrap();
+        assert!(reply.is_ok_and(|account| account.balance == 50));
+    }
+
+    #[tokio::test]
+    async fn test_already_reversed() {
+        let at = datetime!(2024-07-01 12:00 UTC);
+        let account = spawn(checking()).await;
+        account
+            .handle_command(Deposit::new(100, at))
+            .await
+            .unwrap()
+            .unwrap();
+        let withdrawal = account
+            .handle_command(Withdraw::new(30, at))
+            .await
+            .unwrap()
+            .unwrap();
+
+        let reply = account
+            .handle_command(Reverse::new(withdrawal.seq_no, at))
+            .await
+            .unwrap();
+        assert!(reply.is_ok_and(|account| account.balance == 100));
+        let reply = account
+            .handle_command(Reverse::new(withdrawal.seq_no, at))
+            .await
+            .unwrap();
+        assert!(matches!(
+            reply,
+            Err(ReverseError::AlreadyReversed { transaction_ref, .. })
+                if transaction_ref == withdrawal.seq_no
+        ));
+        let reply = account.handle_command(Reverse::new(42, at)).await.unwrap();
+        assert!(matches!(
+            reply,
+            Err(ReverseError::TransactionNotFound {
+                transaction_ref: 42,
+                ..
+            })
+        ));
+
+        // Transactions outside of the reversal window cannot be reversed.
+        let withdrawal = account
+            .handle_command(Withdraw::new(10, at))
+            .await
+            .unwrap()
+            .unwrap();
+        let reply = account
+            .handle_command(Reverse::new(withdrawal.seq_no, at + REVERSAL_WINDOW))
+            .await
+            .unwrap();
+        assert!(matches!(
+            reply,
+            Err(ReverseError::TransactionNotFound { transaction_ref, .. })
+                if transaction_ref == withdrawal.seq_no
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_account_status() {
+        let at = datetime!(2024-07-01 12:00 UTC);
+        let account = spawn(checking()).await;
+        account
+            .handle_command(Deposit::new(100, at))
+            .await
+            .unwrap()
+            .unwrap();
+
+        let reply = account
+            .handle_command(SetAccountStatus::new(AccountStatus::Frozen, at))
+            .await
+            .unwrap();
+        assert!(reply.is_ok_and(|status| status == AccountStatus::Frozen));
+        let reply = account.handle_command(Deposit::new(10, at)).await.unwrap();
+        assert!(matches!(reply, Err(DepositError::NotOpen(_))));
+        let reply = account.handle_command(Withdraw::new(10, at)).await.unwrap();
+        assert!(matches!(reply, Err(WithdrawError::NotOpen(_))));
+
+        let reply = account
+            .handle_command(SetAccountStatus::new(AccountStatus::Closed, at))
+            .await
+            .unwrap();
+        assert!(matches!(
+            reply,
+            Err(SetAccountStatusError::NonZeroBalance(_))
+        ));
+
+        account
+            .handle_command(SetAccountStatus::new(AccountStatus::Open, at))
+            .await
+            .unwrap()
+            .unwrap();
+        account
+            .handle_command(Withdraw::new(100, at))
+            .await
+            .unwrap()
+            .unwrap();
+        let reply = account
+            .handle_command(SetAccountStatus::new(AccountStatus::Closed, at))
+            .await
+            .unwrap();
+        assert!(reply.is_ok_and(|status| status == AccountStatus::Closed));
+
+        // Closing is final.
+        let reply = account
+            .handle_command(SetAccountStatus::new(AccountStatus::Open, at))
+            .await
+            .unwrap();
+        assert!(matches!(reply, Err(SetAccountStatusError::Closed(_))));
+        let reply = account.handle_command(Deposit::new(10, at)).await.unwrap();
+        assert!(matches!(reply, Err(DepositError::NotOpen(_))));
+    }
+
+    #[tokio::test]
+    async fn test_overdraft_fee_within_overdraft() {
+        let at = datetime!(2024-07-01 12:00 UTC);
+        let fees = FeeSchedule {
+            overdraft: 25,
+            ..Default::default()
+        };
+        let account = spawn(Product {
+            overdraft: 100,
+            fees,
+            ..checking()
+        })
+        .await;
+
+        // The overdraft fee is debited together with the amount, hence both must fit into the
+        // overdraft.
+        let reply = account
+            .handle_command(Withdraw::new(100, at))
+            .await
+            .unwrap();
+        assert!(matches!(reply, Err(WithdrawError::InsufficientBalance(_))));
+
+        let reply = account.handle_command(Withdraw::new(75, at)).await.unwrap();
+        assert!(reply.is_ok_and(|reply| reply.account.balance == -100));
+    }
+
+    fn created(id: Uuid) -> AccountEvent {
+        AccountEvent::Created {
+            id,
+            holders: vec![Uuid::now_v7()],
+            product: checking(),
+        }
+    }
+
+    /// Spawn an account entity backed by an in-memory event log and create it for the given
+    /// product.
+    async fn spawn(product: Product) -> EntityRef<AccountEntity> {
+        let account = AccountEntity::default()
+            .entity()
+            .spawn(
+                Uuid::now_v7(),
+                None,
+                NonZeroUsize::MIN,
+                TestEventLog::<Uuid>::default(),
+                NoopSnapshotStore::default(),
+                SerdeJsonBinarize,
+            )
+            .await
+            .unwrap();
+        account
+            .handle_command(CreateAccount::new(vec![Uuid::now_v7()], product))
+            .await
+            .unwrap()
+            .unwrap();
+        account
+    }
+
+    fn replay(events: impl IntoIterator<Item = AccountEvent>) -> AccountState {
+        let entity = events
+            .into_iter()
+            .fold(AccountEntity::default(), AccountEntity::handle_event);
+        let AccountEntity::Existing(state) = entity else {
+            panic!("account must exist");
+        };
+        state
+    }
+
+    fn checking() -> Product {
+        Product {
+            name: "checking".to_string(),
+            allowed_operations: vec![Operation::Deposit, Operation::Withdraw],
+            currency: "EUR".to_string(),
+            overdraft: 0,
+            interest_rate_bps: 0,
+            day_count: DayCount::default(),
+            fees: FeeSchedule::default(),
+        }
+    }
 }
@@ -0,0 +1,26 @@
+use crate::domain::TransactionKind;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// Criteria for selecting accounts; all given ones must match.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccountFilter {
+    pub product: Option<String>,
+    pub min_balance: Option<i64>,
+    pub max_balance: Option<i64>,
+
+    /// Only accounts held by the customer with this ID.
+    pub holder: Option<Uuid>,
+}
+
+/// Criteria for selecting the transactions of an account; all given ones must match.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransactionFilter {
+    pub kind: Option<TransactionKind>,
+
+    /// Only transactions at or after this time.
+    pub from: Option<OffsetDateTime>,
+
+    /// Only transactions before this time.
+    pub to: Option<OffsetDateTime>,
+}
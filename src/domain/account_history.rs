@@ -0,0 +1,215 @@
+use crate::domain::{AccountEntity, AccountEvent};
+use eventsourced::EventSourced;
+use futures::{Stream, StreamExt};
+use std::{
+    fmt::{self, Display},
+    iter,
+    num::NonZeroU64,
+    pin::pin,
+    str::FromStr,
+};
+use thiserror::Error;
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+/// A point in the history of an account: either the (entity) sequence number of an event, e.g.
+/// the one of a transaction, or a time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AsOf {
+    SeqNo(u64),
+    Time(OffsetDateTime),
+}
+
+impl FromStr for AsOf {
+    type Err = ParseAsOfError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.parse::<u64>() {
+            Ok(seq_no) => Ok(AsOf::SeqNo(seq_no)),
+            Err(_) => OffsetDateTime::parse(s, &Rfc3339)
+                .map(AsOf::Time)
+                .map_err(|_| ParseAsOfError(s.to_string())),
+        }
+    }
+}
+
+impl Display for AsOf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AsOf::SeqNo(seq_no) => write!(f, "{seq_no}"),
+            AsOf::Time(at) => {
+                let at = at.format(&Rfc3339).map_err(|_| fmt::Error)?;
+                write!(f, "{at}")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{0} is neither a sequence number nor an RFC 3339 timestamp")]
+pub struct ParseAsOfError(String);
+
+/// Replay the given events of an account, each with its sequence number in the event log, up to
+/// the given point without touching any live entity. The events must end with the one with the
+/// given last sequence number of the event log.
+///
+/// Events without a time, e.g. `Created`, are only known to have happened before the next event
+/// with a time, hence up to a time they are only replayed if followed by an event at or before
+/// it; in particular the account does not exist before its first transaction.
+pub async fn replay_account<S, E>(
+    events: S,
+    last_seq_no: NonZeroU64,
+    as_of: AsOf,
+) -> Result<AccountEntity, E>
+where
+    S: Stream<Item = Result<(NonZeroU64, AccountEvent), E>>,
+{
+    let mut events = pin!(events);
+    let mut account = AccountEntity::default();
+    let mut seq_no = 0;
+    let mut untimed = vec![];
+
+    while let Some(event) = events.next().await {
+        let (log_seq_no, event) = event?;
+        seq_no += 1;
+
+        match as_of {
+            AsOf::SeqNo(as_of) if seq_no > as_of => break,
+
+            AsOf::SeqNo(_) => account = account.handle_event(event),
+
+            AsOf::Time(as_of) => match event.at() {
+                Some(at) if at > as_of => break,
+
+                Some(_) => {
+                    account = untimed
+                        .drain(..)
+                        .chain(iter::once(event))
+                        .fold(account, AccountEntity::handle_event)
+                }
+
+                None => untimed.push(event),
+            },
+        }
+
+        if log_seq_no >= last_seq_no {
+            break;
+        }
+    }
+
+    Ok(account)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{
+        replay_account, AccountEntity, AccountEvent, AsOf, DayCount, FeeSchedule, Operation,
+        Product, WithdrawalLimits,
+    };
+    use futures::stream;
+    use std::{convert::Infallible, num::NonZeroU64};
+    use time::macros::datetime;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_parse_as_of() {
+        assert_eq!("42".parse::<AsOf>().ok(), Some(AsOf::SeqNo(42)));
+        assert_eq!(
+            "2024-03-31T23:59:59Z".parse::<AsOf>().ok(),
+            Some(AsOf::Time(datetime!(2024-03-31 23:59:59 UTC)))
+        );
+        assert!("yesterday".parse::<AsOf>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_replay_account() {
+        let id = Uuid::now_v7();
+        let events = vec![
+            AccountEvent::Created {
+                id,
+                holders: vec![Uuid::now_v7()],
+                product: Product {
+                    name: "checking".to_string(),
+                    allowed_operations: vec![Operation::Deposit, Operation::Withdraw],
+                    currency: "EUR".to_string(),
+                    overdraft: 0,
+                    interest_rate_bps: 0,
+                    day_count: DayCount::default(),
+                    fees: FeeSchedule::default(),
+                },
+            },
+            AccountEvent::Deposited {
+                id,
+                seq_no: 2,
+                amount: 100,
+                balance: 100,
+                at: datetime!(2024-03-30 12:00 UTC),
+            },
+            AccountEvent::WithdrawalLimitsSet {
+                id,
+                limits: WithdrawalLimits {
+                    daily: Some(100),
+                    monthly: None,
+                },
+            },
+            AccountEvent::Deposited {
+                id,
+                seq_no: 4,
+                amount: 50,
+                balance: 150,
+                at: datetime!(2024-04-01 12:00 UTC),
+            },
+        ];
+        let events = || {
+            let events = events
+                .iter()
+                .map(|event| serde_json::to_value(event).unwrap())
+                .map(|event| serde_json::from_value::<AccountEvent>(event).unwrap())
+                .enumerate()
+                .map(|(n, event)| {
+                    Ok::<_, Infallible>((NonZeroU64::new(n as u64 * 10 + 1).unwrap(), event))
+                })
+                .collect::<Vec<_>>();
+            stream::iter(events)
+        };
+        let last_seq_no = NonZeroU64::new(31).unwrap();
+
+        let balance = |account: AccountEntity| match account {
+            AccountEntity::Nonexistent => None,
+            AccountEntity::Existing(state) => Some(state.balance),
+        };
+        let daily_limit = |account: AccountEntity| match account {
+            AccountEntity::Nonexistent => None,
+            AccountEntity::Existing(state) => state.withdrawal_limits.daily,
+        };
+
+        // The account was created at some point before the first deposit.
+        let as_of = AsOf::Time(datetime!(2024-03-30 11:59:59 UTC));
+        let account = replay_account(events(), last_seq_no, as_of).await.unwrap();
+        assert_eq!(balance(account), None);
+
+        // The limits were set at some point between the deposits.
+        let as_of = AsOf::Time(datetime!(2024-03-31 23:59:59 UTC));
+        let account = replay_account(events(), last_seq_no, as_of).await.unwrap();
+        assert_eq!(daily_limit(account), None);
+        let account = replay_account(events(), last_seq_no, as_of).await.unwrap();
+        assert_eq!(balance(account), Some(100));
+
+        let as_of = AsOf::Time(datetime!(2024-04-01 12:00 UTC));
+        let account = replay_account(events(), last_seq_no, as_of).await.unwrap();
+        assert_eq!(daily_limit(account), Some(100));
+
+        let account = replay_account(events(), last_seq_no, AsOf::SeqNo(1))
+            .await
+            .unwrap();
+        assert_eq!(balance(account), Some(0));
+
+        let account = replay_account(events(), last_seq_no, AsOf::SeqNo(42))
+            .await
+            .unwrap();
+        assert_eq!(balance(account), Some(150));
+        let account = replay_account(events(), last_seq_no, AsOf::SeqNo(2))
+            .await
+            .unwrap();
+        assert_eq!(daily_limit(account), None);
+    }
+}
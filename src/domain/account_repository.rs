@@ -1,6 +1,10 @@
-use crate::domain::Account;
+use crate::domain::{
+    Account, AccountFilter, Statement, Transaction, TransactionFilter, TrialBalance,
+};
 use futures::Stream;
 use std::error::Error as StdError;
+use time::Date;
+use uuid::Uuid;
 
 #[trait_variant::make(Send)]
 pub trait AccountRepository
@@ -13,4 +17,49 @@ where
     async fn accounts(
         &self,
     ) -> Result<impl Stream<Item = Result<Account, Self::Error>> + Send, Self::Error>;
+
+    async fn account(&self, id: Uuid) -> Result<Option<Account>, Self::Error>;
+
+    /// At most `limit` accounts matching the given filter with IDs greater than `after`, if
+    /// given, ordered by ID.
+    async fn accounts_page(
+        &self,
+        filter: &AccountFilter,
+        after: Option<Uuid>,
+        limit: u32,
+    ) -> Result<Vec<Account>, Self::Error>;
+
+    /// The transactions of the account with the given ID, ordered by sequence number.
+    async fn transactions(
+        &self,
+        id: Uuid,
+    ) -> Result<impl Stream<Item = Result<Transaction, Self::Error>> + Send, Self::Error>;
+
+    /// At most `limit` transactions of the account with the given ID matching the given filter
+    /// with sequence numbers greater than `after`, if given, ordered by sequence number.
+    async fn transactions_page(
+        &self,
+        id: Uuid,
+        filter: &TransactionFilter,
+        after: Option<u64>,
+        limit: u32,
+    ) -> Result<Vec<Transaction>, Self::Error>;
+
+    /// The account with the given ID together with its statement for the given (UTC) date range,
+    /// both read from the same snapshot, hence a statement up to today closes with the balance of
+    /// the account.
+    async fn statement(
+        &self,
+        id: Uuid,
+        from: Date,
+        to: Date,
+    ) -> Result<Option<(Account, Statement)>, Self::Error>;
+
+    /// Insert the given account with the given holders or, if already existing, overwrite its
+    /// product and balance.
+    async fn repair_account(&self, account: Account, holders: Vec<Uuid>)
+        -> Result<(), Self::Error>;
+
+    /// Debit and credit totals per ledger account of the general ledger.
+    async fn trial_balance(&self) -> Result<TrialBalance, Self::Error>;
 }
@@ -0,0 +1,89 @@
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt::Write;
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// A key authenticating service-to-service clients, e.g. batch systems. Only the hash of the
+/// secret is stored; the key itself, `<id>.<secret>`, is only handed out when issued or rotated.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub name: String,
+    pub scopes: Vec<ApiKeyScope>,
+
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    pub created_at: OffsetDateTime,
+
+    #[serde(with = "time::serde::rfc3339::option")]
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub rotated_at: Option<OffsetDateTime>,
+
+    #[serde(with = "time::serde::rfc3339::option")]
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub revoked_at: Option<OffsetDateTime>,
+}
+
+impl ApiKey {
+    pub fn is_revoked(&self) -> bool {
+        self.revoked_at.is_some()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiKeyScope {
+    Teller,
+    Admin,
+}
+
+/// Generate a new random secret for the API key with the given ID, returning the key to be handed
+/// out and the hash of the secret to be stored.
+pub fn generate_api_key(id: Uuid) -> (String, Vec<u8>) {
+    let mut secret = [0; 32];
+    OsRng.fill_bytes(&mut secret);
+    let secret = secret.iter().fold(String::new(), |mut secret, byte| {
+        let _ = write!(secret, "{byte:02x}");
+        secret
+    });
+
+    let hash = hash_api_key_secret(&secret);
+    (format!("{id}.{secret}"), hash)
+}
+
+/// Split the given key into ID and secret.
+pub fn parse_api_key(key: &str) -> Option<(Uuid, &str)> {
+    let (id, secret) = key.split_once('.')?;
+    let id = id.parse().ok()?;
+    Some((id, secret))
+}
+
+/// The hash of the given secret. As secrets are random with 256 bits, a plain SHA-256 suffices.
+pub fn hash_api_key_secret(secret: &str) -> Vec<u8> {
+    Sha256::digest(secret.as_bytes()).to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{generate_api_key, hash_api_key_secret, parse_api_key};
+    use uuid::Uuid;
+
+    #[test]
+    fn test_generate_api_key() {
+        let id = Uuid::now_v7();
+        let (key, hash) = generate_api_key(id);
+
+        let (parsed_id, secret) = parse_api_key(&key).unwrap();
+        assert_eq!(parsed_id, id);
+        assert_eq!(hash_api_key_secret(secret), hash);
+
+        let (other_key, _) = generate_api_key(id);
+        assert_ne!(other_key, key);
+
+        assert!(parse_api_key("no-id.secret").is_none());
+        assert!(parse_api_key(&id.to_string()).is_none());
+    }
+}
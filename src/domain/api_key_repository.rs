@@ -0,0 +1,56 @@
+use crate::domain::{hash_api_key_secret, parse_api_key, ApiKey};
+use std::error::Error as StdError;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[trait_variant::make(Send)]
+pub trait ApiKeyRepository
+where
+    Self: Clone + Send + Sync + 'static,
+{
+    type Error: StdError;
+
+    async fn api_keys(&self) -> Result<Vec<ApiKey>, Self::Error>;
+
+    /// The API key with the given ID together with the hash of its secret.
+    async fn api_key(&self, id: Uuid) -> Result<Option<(ApiKey, Vec<u8>)>, Self::Error>;
+
+    async fn insert_api_key(&self, api_key: &ApiKey, hash: Vec<u8>) -> Result<(), Self::Error>;
+
+    /// Replace the hash of the secret of the API key with the given ID unless revoked; returns
+    /// the updated API key, if any.
+    async fn rotate_api_key(
+        &self,
+        id: Uuid,
+        hash: Vec<u8>,
+        at: OffsetDateTime,
+    ) -> Result<Option<ApiKey>, Self::Error>;
+
+    /// Revoke the API key with the given ID; returns the revoked API key, if any.
+    async fn revoke_api_key(
+        &self,
+        id: Uuid,
+        at: OffsetDateTime,
+    ) -> Result<Option<ApiKey>, Self::Error>;
+}
+
+/// The API key for the given key, i.e. `<id>.<secret>`, if it exists, is not revoked and its
+/// secret matches.
+pub async fn verify_api_key<K>(
+    api_key_repository: &K,
+    key: &str,
+) -> Result<Option<ApiKey>, K::Error>
+where
+    K: ApiKeyRepository,
+{
+    let Some((id, secret)) = parse_api_key(key) else {
+        return Ok(None);
+    };
+
+    let api_key = api_key_repository
+        .api_key(id)
+        .await?
+        .filter(|(api_key, hash)| !api_key.is_revoked() && *hash == hash_api_key_secret(secret))
+        .map(|(api_key, _)| api_key);
+    Ok(api_key)
+}
@@ -0,0 +1,223 @@
+use crate::domain::customer::{Customer, KycStatus};
+use eventsourced::{Command, CommandEffect, EventSourced};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CustomerEntity {
+    #[default]
+    Nonexistent,
+
+    Existing(Customer),
+}
+
+impl EventSourced for CustomerEntity {
+    type Id = Uuid;
+    type Event = CustomerEvent;
+
+    const TYPE_NAME: &'static str = "customer";
+
+    fn handle_event(self, event: Self::Event) -> Self {
+        match self {
+            CustomerEntity::Nonexistent => match event {
+                CustomerEvent::Created { id, name, email } => CustomerEntity::Existing(Customer {
+                    id,
+                    name,
+                    email,
+                    kyc_status: KycStatus::default(),
+                }),
+                CustomerEvent::ContactChanged { .. } | CustomerEvent::KycStatusChanged { .. } => {
+                    panic!("invalid event {event:?} in state Nonexistent")
+                }
+            },
+
+            CustomerEntity::Existing(mut customer) => match event {
+                CustomerEvent::Created { .. } => {
+                    panic!("invalid event {event:?} in state Existing")
+                }
+
+                CustomerEvent::ContactChanged { email, .. } => {
+                    customer.email = email;
+                    CustomerEntity::Existing(customer)
+                }
+
+                CustomerEvent::KycStatusChanged { kyc_status, .. } => {
+                    customer.kyc_status = kyc_status;
+                    CustomerEntity::Existing(customer)
+                }
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum CustomerEvent {
+    Created {
+        id: Uuid,
+        name: String,
+        email: String,
+    },
+    ContactChanged {
+        id: Uuid,
+        email: String,
+    },
+    KycStatusChanged {
+        id: Uuid,
+        kyc_status: KycStatus,
+    },
+}
+
+// Command: CreateCustomer =========================================================================
+
+#[derive(Debug)]
+pub struct CreateCustomer {
+    name: String,
+    email: String,
+}
+
+impl CreateCustomer {
+    pub fn new(name: String, email: String) -> Self {
+        Self { name, email }
+    }
+}
+
+impl Command<CustomerEntity> for CreateCustomer {
+    type Reply = Customer;
+    type Error = CreateCustomerError;
+
+    fn handle_command(
+        self,
+        id: &Uuid,
+        state: &CustomerEntity,
+    ) -> CommandEffect<CustomerEntity, Self::Reply, Self::Error> {
+        let id = *id;
+
+        match state {
+            CustomerEntity::Nonexistent => {
+                let event = CustomerEvent::Created {
+                    id,
+                    name: self.name,
+                    email: self.email,
+                };
+                CommandEffect::emit_and_reply(event, move |state| match state {
+                    CustomerEntity::Nonexistent => {
+                        panic!("invalid command CreateCustomer in state Nonexistent")
+                    }
+
+                    CustomerEntity::Existing(customer) => customer.clone(),
+                })
+            }
+
+            CustomerEntity::Existing(_) => {
+                CommandEffect::reject(CreateCustomerError::AlreadyExisting(id))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum CreateCustomerError {
+    #[error("customer with ID {0} already exists")]
+    AlreadyExisting(Uuid),
+}
+
+// Command: ChangeContact ==========================================================================
+
+#[derive(Debug)]
+pub struct ChangeContact {
+    email: String,
+}
+
+impl From<String> for ChangeContact {
+    fn from(email: String) -> Self {
+        Self { email }
+    }
+}
+
+impl Command<CustomerEntity> for ChangeContact {
+    type Reply = Customer;
+    type Error = ChangeContactError;
+
+    fn handle_command(
+        self,
+        id: &Uuid,
+        state: &CustomerEntity,
+    ) -> CommandEffect<CustomerEntity, Self::Reply, Self::Error> {
+        let id = *id;
+
+        match state {
+            CustomerEntity::Nonexistent => CommandEffect::reject(ChangeContactError::NotFound(id)),
+
+            CustomerEntity::Existing(_) => {
+                let event = CustomerEvent::ContactChanged {
+                    id,
+                    email: self.email,
+                };
+                CommandEffect::emit_and_reply(event, move |state| match state {
+                    CustomerEntity::Nonexistent => {
+                        panic!("invalid command ChangeContact in state Nonexistent")
+                    }
+
+                    CustomerEntity::Existing(customer) => customer.clone(),
+                })
+            }
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ChangeContactError {
+    #[error("customer with ID {0} not found")]
+    NotFound(Uuid),
+}
+
+// Command: SetKycStatus ===========================================================================
+
+#[derive(Debug)]
+pub struct SetKycStatus {
+    kyc_status: KycStatus,
+}
+
+impl From<KycStatus> for SetKycStatus {
+    fn from(kyc_status: KycStatus) -> Self {
+        Self { kyc_status }
+    }
+}
+
+impl Command<CustomerEntity> for SetKycStatus {
+    type Reply = Customer;
+    type Error = SetKycStatusError;
+
+    fn handle_command(
+        self,
+        id: &Uuid,
+        state: &CustomerEntity,
+    ) -> CommandEffect<CustomerEntity, Self::Reply, Self::Error> {
+        let id = *id;
+
+        match state {
+            CustomerEntity::Nonexistent => CommandEffect::reject(SetKycStatusError::NotFound(id)),
+
+            CustomerEntity::Existing(_) => {
+                let event = CustomerEvent::KycStatusChanged {
+                    id,
+                    kyc_status: self.kyc_status,
+                };
+                CommandEffect::emit_and_reply(event, move |state| match state {
+                    CustomerEntity::Nonexistent => {
+                        panic!("invalid command SetKycStatus in state Nonexistent")
+                    }
+
+                    CustomerEntity::Existing(customer) => customer.clone(),
+                })
+            }
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SetKycStatusError {
+    #[error("customer with ID {0} not found")]
+    NotFound(Uuid),
+}
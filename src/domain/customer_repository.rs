@@ -0,0 +1,30 @@
+use crate::domain::{Account, Customer};
+use futures::Stream;
+use std::error::Error as StdError;
+use uuid::Uuid;
+
+#[trait_variant::make(Send)]
+pub trait CustomerRepository
+where
+    Self: Clone + Send + Sync + 'static,
+{
+    type Error: StdError;
+
+    async fn customers(
+        &self,
+    ) -> Result<impl Stream<Item = Result<Customer, Self::Error>> + Send, Self::Error>;
+
+    async fn customer(&self, id: Uuid) -> Result<Option<Customer>, Self::Error>;
+
+    /// Those of the given customer IDs for which customers exist.
+    async fn existing_customers(&self, ids: &[Uuid]) -> Result<Vec<Uuid>, Self::Error>;
+
+    /// The accounts the customer with the given ID is a holder of.
+    async fn accounts(
+        &self,
+        id: Uuid,
+    ) -> Result<impl Stream<Item = Result<Account, Self::Error>> + Send, Self::Error>;
+
+    /// Whether the customer with the given ID is a holder of the account with the given ID.
+    async fn is_holder(&self, id: Uuid, account_id: Uuid) -> Result<bool, Self::Error>;
+}
@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// The fees charged for accounts of a product; zero means no fee.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct FeeSchedule {
+    /// Charged after the last day of each month.
+    #[serde(default, alias = "monthly-maintenance")]
+    pub monthly_maintenance: u64,
+
+    /// Charged with every withdrawal.
+    #[serde(default)]
+    pub withdrawal: u64,
+
+    /// Charged with a withdrawal making the balance negative.
+    #[serde(default)]
+    pub overdraft: u64,
+}
+
+impl FeeSchedule {
+    /// The fees for withdrawing the given amount from the given balance. The overdraft fee is
+    /// charged if the balance is not negative before, but after withdrawing the amount and the
+    /// withdrawal fee.
+    pub fn withdrawal_fees(&self, balance: i64, amount: u64) -> Vec<Fee> {
+        let mut fees = vec![];
+        if self.withdrawal > 0 {
+            fees.push(Fee {
+                kind: FeeKind::Withdrawal,
+                amount: self.withdrawal,
+            });
+        }
+        // A debit not representable as a balance makes any balance negative.
+        let becomes_negative = amount
+            .checked_add(self.withdrawal)
+            .and_then(|debit| i64::try_from(debit).ok())
+            .and_then(|debit| balance.checked_sub(debit))
+            .is_none_or(|balance| balance < 0);
+        if self.overdraft > 0 && balance >= 0 && becomes_negative {
+            fees.push(Fee {
+                kind: FeeKind::Overdraft,
+                amount: self.overdraft,
+            });
+        }
+        fees
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct Fee {
+    pub kind: FeeKind,
+    pub amount: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeKind {
+    MonthlyMaintenance,
+    Withdrawal,
+    Overdraft,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{Fee, FeeKind, FeeSchedule};
+
+    #[test]
+    fn test_withdrawal_fees() {
+        let fees = FeeSchedule {
+            monthly_maintenance: 500,
+            withdrawal: 10,
+            overdraft: 2500,
+        };
+
+        assert_eq!(
+            fees.withdrawal_fees(100, 90),
+            vec![Fee {
+                kind: FeeKind::Withdrawal,
+                amount: 10
+            }]
+        );
+
+        // The withdrawal fee alone makes the balance negative.
+        assert_eq!(
+            fees.withdrawal_fees(100, 91),
+            vec![
+                Fee {
+                    kind: FeeKind::Withdrawal,
+                    amount: 10
+                },
+                Fee {
+                    kind: FeeKind::Overdraft,
+                    amount: 2500
+                }
+            ]
+        );
+
+        // The overdraft fee is only charged when the balance becomes negative.
+        assert_eq!(
+            fees.withdrawal_fees(-100, 100),
+            vec![Fee {
+                kind: FeeKind::Withdrawal,
+                amount: 10
+            }]
+        );
+
+        // Amounts too large to be withdrawn make the balance negative instead of overflowing.
+        assert_eq!(fees.withdrawal_fees(i64::MAX, u64::MAX).len(), 2);
+
+        assert!(FeeSchedule::default().withdrawal_fees(0, 100).is_empty());
+    }
+}
@@ -0,0 +1,204 @@
+use serde::{Deserialize, Serialize};
+use time::{util::days_in_year_month, Date};
+use utoipa::ToSchema;
+
+/// Accrued interest is tracked in millionths of the smallest currency unit to avoid losing
+/// fractions when accruing daily.
+pub const MICROS_PER_UNIT: u64 = 1_000_000;
+
+const BPS_PER_UNIT: u128 = 10_000;
+
+/// Day-count convention determining the fraction of a year a single day of accrual amounts to.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum DayCount {
+    /// Actual days over a fixed year of 365 days.
+    #[default]
+    #[serde(rename = "act-365")]
+    Act365,
+
+    /// Months of 30 days over a year of 360 days (ISDA), i.e. the 31st accrues nothing and the
+    /// last day of February makes up for the missing days.
+    #[serde(rename = "30-360")]
+    Thirty360,
+}
+
+impl DayCount {
+    /// The fraction of a year accrued for the given date as numerator and denominator.
+    pub fn day_fraction(self, date: Date) -> (u32, u32) {
+        match self {
+            DayCount::Act365 => (1, 365),
+            DayCount::Thirty360 => match date.next_day() {
+                Some(next) => (days_30_360(date, next), 360),
+                None => (0, 360),
+            },
+        }
+    }
+
+    /// The interest in millionths of the smallest currency unit accrued on the given end-of-day
+    /// balance for the given date at the given annual rate in basis points. Negative balances
+    /// accrue no interest.
+    pub fn daily_interest(self, balance: i64, rate_bps: u32, date: Date) -> u64 {
+        if balance <= 0 {
+            return 0;
+        }
+
+        let (numerator, denominator) = self.day_fraction(date);
+        let micros = balance as u128 * rate_bps as u128 * MICROS_PER_UNIT as u128 / BPS_PER_UNIT
+            * numerator as u128
+            / denominator as u128;
+        micros as u64
+    }
+}
+
+fn days_30_360(from: Date, to: Date) -> u32 {
+    let d1 = from.day().min(30);
+    let d2 = if d1 == 30 { to.day().min(30) } else { to.day() };
+    let months = (to.year() - from.year()) * 12 + to.month() as i32 - from.month() as i32;
+    (months * 30 + d2 as i32 - d1 as i32) as u32
+}
+
+/// Whether the given date is the last day of its month, i.e. the day interest gets posted.
+pub fn is_month_end(date: Date) -> bool {
+    date.day() == days_in_year_month(date.year(), date.month())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{
+        AccountEntity, AccountEvent, DayCount, FeeSchedule, Operation, Product, MICROS_PER_UNIT,
+    };
+    use eventsourced::EventSourced;
+    use time::macros::{date, datetime};
+    use uuid::Uuid;
+
+    #[test]
+    fn test_day_fraction() {
+        assert_eq!(
+            DayCount::Act365.day_fraction(date!(2024 - 02 - 29)),
+            (1, 365)
+        );
+
+        assert_eq!(
+            DayCount::Thirty360.day_fraction(date!(2024 - 07 - 15)),
+            (1, 360)
+        );
+        assert_eq!(
+            DayCount::Thirty360.day_fraction(date!(2024 - 07 - 30)),
+            (0, 360)
+        );
+        assert_eq!(
+            DayCount::Thirty360.day_fraction(date!(2024 - 07 - 31)),
+            (1, 360)
+        );
+        assert_eq!(
+            DayCount::Thirty360.day_fraction(date!(2023 - 02 - 28)),
+            (3, 360)
+        );
+        assert_eq!(
+            DayCount::Thirty360.day_fraction(date!(2024 - 02 - 29)),
+            (2, 360)
+        );
+
+        // Every month amounts to 30 days.
+        let days = (1..=31)
+            .map(|day| {
+                date!(2024 - 01 - 01)
+                    .replace_day(day)
+                    .map(|date| DayCount::Thirty360.day_fraction(date).0)
+                    .unwrap()
+            })
+            .sum::<u32>();
+        assert_eq!(days, 30);
+    }
+
+    #[test]
+    fn test_daily_interest() {
+        // 1% of 365_000 for a single day of a 365 day year is 10.
+        let interest = DayCount::Act365.daily_interest(365_000, 100, date!(2024 - 07 - 01));
+        assert_eq!(interest, 10 * MICROS_PER_UNIT);
+
+        let interest = DayCount::Thirty360.daily_interest(360_000, 100, date!(2024 - 07 - 31));
+        assert_eq!(interest, 10 * MICROS_PER_UNIT);
+        let interest = DayCount::Thirty360.daily_interest(360_000, 100, date!(2024 - 07 - 30));
+        assert_eq!(interest, 0);
+
+        // Fractions of the smallest currency unit are retained.
+        let interest = DayCount::Act365.daily_interest(1_000, 150, date!(2024 - 07 - 01));
+        assert_eq!(interest, 41_095);
+
+        let interest = DayCount::Act365.daily_interest(-1_000, 150, date!(2024 - 07 - 01));
+        assert_eq!(interest, 0);
+    }
+
+    #[test]
+    fn test_is_month_end() {
+        assert!(super::is_month_end(date!(2024 - 02 - 29)));
+        assert!(!super::is_month_end(date!(2023 - 02 - 27)));
+        assert!(super::is_month_end(date!(2023 - 02 - 28)));
+        assert!(super::is_month_end(date!(2024 - 12 - 31)));
+    }
+
+    #[test]
+    fn test_end_of_day_balance_and_posting() {
+        let id = Uuid::now_v7();
+        let events = [
+            AccountEvent::Created {
+                id,
+                holders: vec![Uuid::now_v7()],
+                product: savings(),
+            },
+            AccountEvent::Deposited {
+                id,
+                seq_no: 2,
+                amount: 100,
+                balance: 100,
+                at: datetime!(2024-07-01 10:00 UTC),
+            },
+            AccountEvent::Deposited {
+                id,
+                seq_no: 3,
+                amount: 50,
+                balance: 150,
+                at: datetime!(2024-07-02 10:00 UTC),
+            },
+            AccountEvent::InterestAccrued {
+                id,
+                date: date!(2024 - 07 - 01),
+                amount: 1_500_000,
+            },
+            AccountEvent::InterestPosted {
+                id,
+                seq_no: 5,
+                amount: 1,
+                balance: 151,
+                at: datetime!(2024-07-03 00:00 UTC),
+            },
+        ];
+        let mut entity = AccountEntity::default();
+        for event in events {
+            entity = entity.handle_event(event);
+        }
+
+        let AccountEntity::Existing(state) = entity else {
+            panic!("account must exist");
+        };
+        assert_eq!(state.seq_no, 5);
+        assert_eq!(state.balance, 151);
+        assert_eq!(state.end_of_day_balance(date!(2024 - 07 - 02)), 150);
+        assert_eq!(state.end_of_day_balance(date!(2024 - 07 - 03)), 151);
+        assert_eq!(state.accrued_interest, 500_000);
+        assert_eq!(state.accrued_through, Some(date!(2024 - 07 - 01)));
+    }
+
+    fn savings() -> Product {
+        Product {
+            name: "savings".to_string(),
+            allowed_operations: vec![Operation::Deposit, Operation::Withdraw],
+            currency: "EUR".to_string(),
+            overdraft: 0,
+            interest_rate_bps: 150,
+            day_count: DayCount::Act365,
+            fees: FeeSchedule::default(),
+        }
+    }
+}
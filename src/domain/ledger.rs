@@ -0,0 +1,263 @@
+use crate::domain::AccountEvent;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// The chart of accounts of the general ledger.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LedgerAccount {
+    /// Asset: money held by the bank.
+    Cash,
+
+    /// Liability: the balances of all customer accounts.
+    CustomerLiabilities,
+
+    /// Income: fees charged to customers.
+    FeeIncome,
+
+    /// Expense: interest paid to customers.
+    InterestExpense,
+}
+
+/// One side of a journal entry; exactly one of `debit` and `credit` is non-zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Posting {
+    pub ledger_account: LedgerAccount,
+    pub debit: u64,
+    pub credit: u64,
+}
+
+impl Posting {
+    pub fn debit(ledger_account: LedgerAccount, amount: u64) -> Self {
+        Self {
+            ledger_account,
+            debit: amount,
+            credit: 0,
+        }
+    }
+
+    pub fn credit(ledger_account: LedgerAccount, amount: u64) -> Self {
+        Self {
+            ledger_account,
+            debit: 0,
+            credit: amount,
+        }
+    }
+
+    /// The posting with debit and credit swapped.
+    pub fn reversed(self) -> Self {
+        Self {
+            ledger_account: self.ledger_account,
+            debit: self.credit,
+            credit: self.debit,
+        }
+    }
+}
+
+/// The balanced postings for the given event; empty for events not moving money.
+///
+/// `Reversed` needs the cash posting of the reversed transaction, hence has to be journalized via
+/// [reversal_postings].
+pub fn postings(event: &AccountEvent) -> Vec<Posting> {
+    use LedgerAccount::*;
+
+    match event {
+        AccountEvent::Deposited { amount, .. } => {
+            vec![
+                Posting::debit(Cash, *amount),
+                Posting::credit(CustomerLiabilities, *amount),
+            ]
+        }
+
+        AccountEvent::Withdrawn { amount, fees, .. } => {
+            let fee = fees.iter().map(|fee| fee.amount).sum::<u64>();
+            let mut postings = vec![
+                Posting::debit(CustomerLiabilities, amount + fee),
+                Posting::credit(Cash, *amount),
+            ];
+            if fee > 0 {
+                postings.push(Posting::credit(FeeIncome, fee));
+            }
+            postings
+        }
+
+        AccountEvent::HoldCaptured { amount, .. } => {
+            vec![
+                Posting::debit(CustomerLiabilities, *amount),
+                Posting::credit(Cash, *amount),
+            ]
+        }
+
+        AccountEvent::InterestPosted { amount, .. } => vec![
+            Posting::debit(InterestExpense, *amount),
+            Posting::credit(CustomerLiabilities, *amount),
+        ],
+
+        AccountEvent::FeeCharged { fee, .. } => vec![
+            Posting::debit(CustomerLiabilities, fee.amount),
+            Posting::credit(FeeIncome, fee.amount),
+        ],
+
+        AccountEvent::FeeWaived { amount, .. } => vec![
+            Posting::debit(FeeIncome, *amount),
+            Posting::credit(CustomerLiabilities, *amount),
+        ],
+
+        AccountEvent::Created { .. }
+        | AccountEvent::WithdrawalLimitsSet { .. }
+        | AccountEvent::HoldPlaced { .. }
+        | AccountEvent::HoldReleased { .. }
+        | AccountEvent::Reversed { .. }
+        | AccountEvent::InterestAccrued { .. }
+        | AccountEvent::StatusChanged { .. } => vec![],
+    }
+}
+
+/// The balanced postings for reversing a deposit or withdrawal with the given cash posting and
+/// refunding the given fees of a withdrawal. Like for `Withdrawn`, each ledger account is posted
+/// at most once, i.e. the refunded fees are included in the liabilities posting.
+pub fn reversal_postings(cash_posting: Posting, fee: u64) -> Vec<Posting> {
+    let cash_posting = cash_posting.reversed();
+    let liabilities_posting = Posting {
+        ledger_account: LedgerAccount::CustomerLiabilities,
+        debit: cash_posting.credit,
+        credit: cash_posting.debit + fee,
+    };
+    let mut postings = vec![cash_posting, liabilities_posting];
+    if fee > 0 {
+        postings.push(Posting::debit(LedgerAccount::FeeIncome, fee));
+    }
+    postings
+}
+
+/// Debit and credit totals per ledger account; the books are balanced if the totals over all
+/// ledger accounts are equal.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct TrialBalance {
+    pub accounts: Vec<LedgerAccountBalance>,
+    pub debit: u64,
+    pub credit: u64,
+    pub balanced: bool,
+}
+
+impl TrialBalance {
+    pub fn new(accounts: Vec<LedgerAccountBalance>) -> Self {
+        let debit = accounts.iter().map(|account| account.debit).sum();
+        let credit = accounts.iter().map(|account| account.credit).sum();
+        Self {
+            accounts,
+            debit,
+            credit,
+            balanced: debit == credit,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct LedgerAccountBalance {
+    pub ledger_account: LedgerAccount,
+    pub debit: u64,
+    pub credit: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{
+        postings, reversal_postings, AccountEvent, Fee, FeeKind, LedgerAccount, Posting,
+    };
+    use time::macros::datetime;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_postings_are_balanced() {
+        let id = Uuid::now_v7();
+        let at = datetime!(2024-07-01 12:00 UTC);
+        let events = [
+            AccountEvent::Deposited {
+                id,
+                seq_no: 2,
+                amount: 100,
+                balance: 100,
+                at,
+            },
+            AccountEvent::Withdrawn {
+                id,
+                seq_no: 3,
+                amount: 50,
+                fees: vec![
+                    Fee {
+                        kind: FeeKind::Withdrawal,
+                        amount: 1,
+                    },
+                    Fee {
+                        kind: FeeKind::Overdraft,
+                        amount: 25,
+                    },
+                ],
+                balance: 24,
+                at,
+            },
+            AccountEvent::InterestPosted {
+                id,
+                seq_no: 4,
+                amount: 1,
+                balance: 25,
+                at,
+            },
+        ];
+
+        for event in events {
+            let postings = postings(&event);
+            assert!(!postings.is_empty());
+            let debit = postings.iter().map(|posting| posting.debit).sum::<u64>();
+            let credit = postings.iter().map(|posting| posting.credit).sum::<u64>();
+            assert_eq!(debit, credit);
+        }
+    }
+
+    #[test]
+    fn test_reversal_postings() {
+        let postings = reversal_postings(Posting::debit(LedgerAccount::Cash, 100), 0);
+        assert_eq!(
+            postings,
+            vec![
+                Posting::credit(LedgerAccount::Cash, 100),
+                Posting::debit(LedgerAccount::CustomerLiabilities, 100)
+            ]
+        );
+
+        let postings = reversal_postings(Posting::credit(LedgerAccount::Cash, 50), 5);
+        assert_eq!(
+            postings,
+            vec![
+                Posting::debit(LedgerAccount::Cash, 50),
+                Posting::credit(LedgerAccount::CustomerLiabilities, 55),
+                Posting::debit(LedgerAccount::FeeIncome, 5)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reversal_postings_per_ledger_account() {
+        for (cash_posting, fee) in [
+            (Posting::debit(LedgerAccount::Cash, 100), 0),
+            (Posting::credit(LedgerAccount::Cash, 50), 0),
+            (Posting::credit(LedgerAccount::Cash, 50), 5),
+        ] {
+            let postings = reversal_postings(cash_posting, fee);
+
+            // The ledger account is part of the primary key of the postings of a transaction.
+            for posting in &postings {
+                let count = postings
+                    .iter()
+                    .filter(|p| p.ledger_account == posting.ledger_account)
+                    .count();
+                assert_eq!(count, 1);
+            }
+
+            let debit = postings.iter().map(|posting| posting.debit).sum::<u64>();
+            let credit = postings.iter().map(|posting| posting.credit).sum::<u64>();
+            assert_eq!(debit, credit);
+        }
+    }
+}
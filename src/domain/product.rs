@@ -0,0 +1,135 @@
+use crate::domain::{DayCount, FeeSchedule};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// An account product like checking, savings or escrow, defining the rules for its accounts.
+///
+/// Fields are snake_case like the rest of the API; the kebab-case aliases are for the
+/// configuration and for events persisted before.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct Product {
+    pub name: String,
+
+    #[serde(alias = "allowed-operations")]
+    pub allowed_operations: Vec<Operation>,
+
+    /// ISO 4217 code of the currency of all amounts.
+    #[serde(default = "default_currency")]
+    pub currency: String,
+
+    /// The amount by which the balance may become negative.
+    #[serde(default)]
+    pub overdraft: u64,
+
+    /// Annual interest rate in basis points.
+    #[serde(default, alias = "interest-rate-bps")]
+    pub interest_rate_bps: u32,
+
+    /// Day-count convention for accruing interest.
+    #[serde(default, alias = "day-count")]
+    pub day_count: DayCount,
+
+    #[serde(default)]
+    pub fees: FeeSchedule,
+}
+
+fn default_currency() -> String {
+    "EUR".to_string()
+}
+
+impl Product {
+    /// The product of accounts created before products were introduced: deposits and withdrawals
+    /// without overdraft, interest or fees, like back then. Named like the product the account
+    /// projection has been migrated to, see `004_account_product.sql`.
+    pub fn legacy() -> Self {
+        Self {
+            name: "checking".to_string(),
+            allowed_operations: vec![Operation::Deposit, Operation::Withdraw],
+            currency: default_currency(),
+            overdraft: 0,
+            interest_rate_bps: 0,
+            day_count: DayCount::default(),
+            fees: FeeSchedule::default(),
+        }
+    }
+
+    pub fn allows(&self, operation: Operation) -> bool {
+        self.allowed_operations.contains(&operation)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum Operation {
+    Deposit,
+    /// Withdrawals as well as holds.
+    Withdraw,
+}
+
+/// The products accounts can be created for, loaded from the configuration.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(transparent)]
+pub struct ProductCatalog(Vec<Product>);
+
+impl ProductCatalog {
+    pub fn product(&self, name: &str) -> Option<&Product> {
+        self.0.iter().find(|product| product.name == name)
+    }
+
+    pub fn products(&self) -> &[Product] {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{DayCount, FeeSchedule, Operation, Product};
+    use serde_json::json;
+
+    #[test]
+    fn test_serde() {
+        let product = Product {
+            name: "savings".to_string(),
+            allowed_operations: vec![Operation::Deposit],
+            currency: "EUR".to_string(),
+            overdraft: 0,
+            interest_rate_bps: 150,
+            day_count: DayCount::Act365,
+            fees: FeeSchedule {
+                monthly_maintenance: 500,
+                withdrawal: 0,
+                overdraft: 0,
+            },
+        };
+
+        let json = json!({
+            "name": "savings",
+            "allowed_operations": ["deposit"],
+            "currency": "EUR",
+            "overdraft": 0,
+            "interest_rate_bps": 150,
+            "day_count": "act-365",
+            "fees": {
+                "monthly_maintenance": 500,
+                "withdrawal": 0,
+                "overdraft": 0
+            }
+        });
+        assert_eq!(serde_json::to_value(&product).unwrap(), json);
+
+        // Like in the configuration and in events persisted before.
+        let kebab_case = json!({
+            "name": "savings",
+            "allowed-operations": ["deposit"],
+            "interest-rate-bps": 150,
+            "day-count": "act-365",
+            "fees": {
+                "monthly-maintenance": 500
+            }
+        });
+        assert_eq!(
+            serde_json::from_value::<Product>(kebab_case).unwrap(),
+            product
+        );
+    }
+}
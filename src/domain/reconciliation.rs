@@ -0,0 +1,116 @@
+use crate::domain::Account;
+use serde::Serialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// A difference between the state of an account replayed from the event log and the one of the
+/// account projection.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Drift {
+    /// The account exists in the event log, but not in the projection.
+    MissingRow { account_id: Uuid, balance: i64 },
+
+    /// The balance of the projection differs from the replayed one.
+    WrongBalance {
+        account_id: Uuid,
+        expected: i64,
+        actual: i64,
+    },
+
+    /// The account exists in the projection, but not in the event log.
+    OrphanRow { account_id: Uuid, balance: i64 },
+}
+
+impl Drift {
+    /// The drift between the given replayed and projected account, if any.
+    pub fn detect(
+        account_id: Uuid,
+        replayed: Option<&Account>,
+        projected: Option<&Account>,
+    ) -> Option<Self> {
+        match (replayed, projected) {
+            (Some(replayed), None) => Some(Drift::MissingRow {
+                account_id,
+                balance: replayed.balance,
+            }),
+
+            (Some(replayed), Some(projected)) if replayed.balance != projected.balance => {
+                Some(Drift::WrongBalance {
+                    account_id,
+                    expected: replayed.balance,
+                    actual: projected.balance,
+                })
+            }
+
+            (None, Some(projected)) => Some(Drift::OrphanRow {
+                account_id,
+                balance: projected.balance,
+            }),
+
+            _ => None,
+        }
+    }
+
+    /// Whether this drift can be repaired from the event log; orphan rows cannot, because there
+    /// is nothing to replay.
+    pub fn is_repairable(&self) -> bool {
+        !matches!(self, Drift::OrphanRow { .. })
+    }
+}
+
+/// The result of reconciling the account projection with the event log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct Reconciliation {
+    /// The number of checked accounts.
+    pub checked: usize,
+
+    pub drifts: Vec<Drift>,
+
+    /// The number of repaired drifts.
+    pub repaired: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{Account, Drift};
+    use uuid::Uuid;
+
+    #[test]
+    fn test_detect() {
+        let id = Uuid::now_v7();
+        let account = |balance| Account {
+            id,
+            product: "checking".to_string(),
+            balance,
+        };
+
+        assert_eq!(Drift::detect(id, None, None), None);
+        assert_eq!(
+            Drift::detect(id, Some(&account(42)), Some(&account(42))),
+            None
+        );
+        assert_eq!(
+            Drift::detect(id, Some(&account(42)), None),
+            Some(Drift::MissingRow {
+                account_id: id,
+                balance: 42
+            })
+        );
+        assert_eq!(
+            Drift::detect(id, Some(&account(42)), Some(&account(40))),
+            Some(Drift::WrongBalance {
+                account_id: id,
+                expected: 42,
+                actual: 40
+            })
+        );
+        assert_eq!(
+            Drift::detect(id, None, Some(&account(40))),
+            Some(Drift::OrphanRow {
+                account_id: id,
+                balance: 40
+            })
+        );
+    }
+}
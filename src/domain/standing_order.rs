@@ -0,0 +1,125 @@
+use serde::{Deserialize, Serialize};
+use time::{util::days_in_year_month, Date, Month, OffsetDateTime};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// A recurring monthly transfer from an account to a payee account.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct StandingOrder {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub payee_id: Uuid,
+    pub amount: u64,
+
+    /// Day of the month the transfer is due; for shorter months the last day.
+    pub day_of_month: u8,
+
+    #[schema(value_type = String, format = Date)]
+    pub next_due: Date,
+
+    pub status: StandingOrderStatus,
+
+    /// Failed attempts to execute the transfer due at the date of `last_failure`.
+    pub failed_attempts: u32,
+
+    pub last_failure: Option<ExecutionFailure>,
+
+    /// Due date of the transfer claimed for execution, until its execution or failure has been
+    /// recorded.
+    #[schema(value_type = Option<String>, format = Date)]
+    pub claimed: Option<Date>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StandingOrderStatus {
+    #[default]
+    Active,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ExecutionFailure {
+    #[schema(value_type = String, format = Date)]
+    pub due: Date,
+
+    pub reason: String,
+
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    pub at: OffsetDateTime,
+}
+
+/// Whether the given day of the month is valid for a standing order.
+pub fn is_valid_day_of_month(day_of_month: u8) -> bool {
+    (1..=31).contains(&day_of_month)
+}
+
+/// The first due date for the given day of the month on or after the given date.
+pub fn due_date_from(day_of_month: u8, from: Date) -> Date {
+    let due = due_date(from.year(), from.month(), day_of_month);
+    if due >= from {
+        due
+    } else {
+        let (year, month) = next_month(from.year(), from.month());
+        due_date(year, month, day_of_month)
+    }
+}
+
+/// The due date for the given day of the month in the month following the one of the given due
+/// date.
+pub fn next_due_date(day_of_month: u8, due: Date) -> Date {
+    let (year, month) = next_month(due.year(), due.month());
+    due_date(year, month, day_of_month)
+}
+
+fn due_date(year: i32, month: Month, day_of_month: u8) -> Date {
+    let day = day_of_month.min(days_in_year_month(year, month));
+    Date::from_calendar_date(year, month, day).expect("valid date")
+}
+
+fn next_month(year: i32, month: Month) -> (i32, Month) {
+    match month {
+        Month::December => (year + 1, Month::January),
+        month => (year, month.next()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{due_date_from, next_due_date};
+    use time::macros::date;
+
+    #[test]
+    fn test_due_dates() {
+        assert_eq!(
+            due_date_from(5, date!(2024 - 07 - 05)),
+            date!(2024 - 07 - 05)
+        );
+        assert_eq!(
+            due_date_from(5, date!(2024 - 07 - 06)),
+            date!(2024 - 08 - 05)
+        );
+        assert_eq!(
+            due_date_from(31, date!(2024 - 02 - 10)),
+            date!(2024 - 02 - 29)
+        );
+        assert_eq!(
+            due_date_from(1, date!(2024 - 12 - 02)),
+            date!(2025 - 01 - 01)
+        );
+
+        assert_eq!(
+            next_due_date(31, date!(2024 - 01 - 31)),
+            date!(2024 - 02 - 29)
+        );
+        assert_eq!(
+            next_due_date(31, date!(2024 - 02 - 29)),
+            date!(2024 - 03 - 31)
+        );
+        assert_eq!(
+            next_due_date(15, date!(2024 - 12 - 15)),
+            date!(2025 - 01 - 15)
+        );
+    }
+}
@@ -0,0 +1,599 @@
+use crate::domain::standing_order::{
+    due_date_from, is_valid_day_of_month, next_due_date, ExecutionFailure, StandingOrder,
+    StandingOrderStatus,
+};
+use eventsourced::{Command, CommandEffect, EventSourced};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use time::{Date, OffsetDateTime};
+use uuid::Uuid;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StandingOrderEntity {
+    #[default]
+    Nonexistent,
+
+    Existing(StandingOrder),
+}
+
+impl EventSourced for StandingOrderEntity {
+    type Id = Uuid;
+    type Event = StandingOrderEvent;
+
+    const TYPE_NAME: &'static str = "standing-order";
+
+    fn handle_event(self, event: Self::Event) -> Self {
+        match self {
+            StandingOrderEntity::Nonexistent => match event {
+                StandingOrderEvent::Created {
+                    id,
+                    account_id,
+                    payee_id,
+                    amount,
+                    day_of_month,
+                    next_due,
+                } => StandingOrderEntity::Existing(StandingOrder {
+                    id,
+                    account_id,
+                    payee_id,
+                    amount,
+                    day_of_month,
+                    next_due,
+                    status: StandingOrderStatus::Active,
+                    failed_attempts: 0,
+                    last_failure: None,
+                    claimed: None,
+                }),
+                StandingOrderEvent::Updated { .. }
+                | StandingOrderEvent::Cancelled { .. }
+                | StandingOrderEvent::ExecutionClaimed { .. }
+                | StandingOrderEvent::Executed { .. }
+                | StandingOrderEvent::ExecutionFailed { .. } => {
+                    panic!("invalid event {event:?} in state Nonexistent")
+                }
+            },
+
+            StandingOrderEntity::Existing(mut standing_order) => match event {
+                StandingOrderEvent::Created { .. } => {
+                    panic!("invalid event {event:?} in state Existing")
+                }
+
+                StandingOrderEvent::Updated {
+                    amount,
+                    day_of_month,
+                    next_due,
+                    ..
+                } => {
+                    standing_order.amount = amount;
+                    standing_order.day_of_month = day_of_month;
+                    standing_order.next_due = next_due;
+                    standing_order.failed_attempts = 0;
+                    StandingOrderEntity::Existing(standing_order)
+                }
+
+                StandingOrderEvent::Cancelled { .. } => {
+                    standing_order.status = StandingOrderStatus::Cancelled;
+                    StandingOrderEntity::Existing(standing_order)
+                }
+
+                StandingOrderEvent::ExecutionClaimed { due, .. } => {
+                    standing_order.claimed = Some(due);
+                    StandingOrderEntity::Existing(standing_order)
+                }
+
+                StandingOrderEvent::Executed { next_due, .. } => {
+                    standing_order.next_due = next_due;
+                    standing_order.failed_attempts = 0;
+                    standing_order.last_failure = None;
+                    standing_order.claimed = None;
+                    StandingOrderEntity::Existing(standing_order)
+                }
+
+                StandingOrderEvent::ExecutionFailed {
+                    due,
+                    reason,
+                    failed_attempts,
+                    next_due,
+                    at,
+                    ..
+                } => {
+                    standing_order.next_due = next_due;
+                    standing_order.failed_attempts = failed_attempts;
+                    standing_order.last_failure = Some(ExecutionFailure { due, reason, at });
+                    standing_order.claimed = None;
+                    StandingOrderEntity::Existing(standing_order)
+                }
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum StandingOrderEvent {
+    Created {
+        id: Uuid,
+        account_id: Uuid,
+        payee_id: Uuid,
+        amount: u64,
+        day_of_month: u8,
+        next_due: Date,
+    },
+    Updated {
+        id: Uuid,
+        amount: u64,
+        day_of_month: u8,
+        next_due: Date,
+    },
+    Cancelled {
+        id: Uuid,
+    },
+    /// The transfer due at `due` is about to be executed; it must not be executed again.
+    ExecutionClaimed {
+        id: Uuid,
+        due: Date,
+        #[serde(with = "time::serde::rfc3339")]
+        at: OffsetDateTime,
+    },
+    Executed {
+        id: Uuid,
+        due: Date,
+        next_due: Date,
+        #[serde(with = "time::serde::rfc3339")]
+        at: OffsetDateTime,
+    },
+    /// `next_due` equals `due` if the execution is to be retried, else the execution has been
+    /// given up for `due`.
+    ExecutionFailed {
+        id: Uuid,
+        due: Date,
+        reason: String,
+        failed_attempts: u32,
+        next_due: Date,
+        #[serde(with = "time::serde::rfc3339")]
+        at: OffsetDateTime,
+    },
+}
+
+// Command: CreateStandingOrder ====================================================================
+
+#[derive(Debug)]
+pub struct CreateStandingOrder {
+    account_id: Uuid,
+    payee_id: Uuid,
+    amount: u64,
+    day_of_month: u8,
+    start: Date,
+}
+
+impl CreateStandingOrder {
+    /// Create a standing order transferring the given amount from the account with the given ID
+    /// to the payee account with the given ID on the given day of each month, starting on or after
+    /// the given date.
+    pub fn new(
+        account_id: Uuid,
+        payee_id: Uuid,
+        amount: u64,
+        day_of_month: u8,
+        start: Date,
+    ) -> Self {
+        Self {
+            account_id,
+            payee_id,
+            amount,
+            day_of_month,
+            start,
+        }
+    }
+}
+
+impl Command<StandingOrderEntity> for CreateStandingOrder {
+    type Reply = StandingOrder;
+    type Error = CreateStandingOrderError;
+
+    fn handle_command(
+        self,
+        id: &Uuid,
+        state: &StandingOrderEntity,
+    ) -> CommandEffect<StandingOrderEntity, Self::Reply, Self::Error> {
+        let id = *id;
+
+        match state {
+            StandingOrderEntity::Nonexistent if !is_valid_day_of_month(self.day_of_month) => {
+                CommandEffect::reject(CreateStandingOrderError::InvalidDayOfMonth(
+                    self.day_of_month,
+                ))
+            }
+
+            StandingOrderEntity::Nonexistent if self.account_id == self.payee_id => {
+                CommandEffect::reject(CreateStandingOrderError::SameAccount(self.account_id))
+            }
+
+            StandingOrderEntity::Nonexistent => {
+                let event = StandingOrderEvent::Created {
+                    id,
+                    account_id: self.account_id,
+                    payee_id: self.payee_id,
+                    amount: self.amount,
+                    day_of_month: self.day_of_month,
+                    next_due: due_date_from(self.day_of_month, self.start),
+                };
+                CommandEffect::emit_and_reply(event, move |state| match state {
+                    StandingOrderEntity::Nonexistent => {
+                        panic!("invalid command CreateStandingOrder in state Nonexistent")
+                    }
+
+                    StandingOrderEntity::Existing(standing_order) => standing_order.clone(),
+                })
+            }
+
+            StandingOrderEntity::Existing(_) => {
+                CommandEffect::reject(CreateStandingOrderError::AlreadyExisting(id))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum CreateStandingOrderError {
+    #[error("standing order with ID {0} already exists")]
+    AlreadyExisting(Uuid),
+
+    #[error("day of month {0} is invalid")]
+    InvalidDayOfMonth(u8),
+
+    #[error("account with ID {0} cannot be its own payee")]
+    SameAccount(Uuid),
+}
+
+// Command: UpdateStandingOrder ====================================================================
+
+#[derive(Debug)]
+pub struct UpdateStandingOrder {
+    account_id: Uuid,
+    amount: u64,
+    day_of_month: u8,
+    today: Date,
+}
+
+impl UpdateStandingOrder {
+    /// Change amount and day of month of the standing order of the account with the given ID. A
+    /// transfer already executed for the current month is not repeated.
+    pub fn new(account_id: Uuid, amount: u64, day_of_month: u8, today: Date) -> Self {
+        Self {
+            account_id,
+            amount,
+            day_of_month,
+            today,
+        }
+    }
+}
+
+impl Command<StandingOrderEntity> for UpdateStandingOrder {
+    type Reply = StandingOrder;
+    type Error = UpdateStandingOrderError;
+
+    fn handle_command(
+        self,
+        id: &Uuid,
+        state: &StandingOrderEntity,
+    ) -> CommandEffect<StandingOrderEntity, Self::Reply, Self::Error> {
+        let id = *id;
+
+        match state {
+            StandingOrderEntity::Existing(standing_order)
+                if standing_order.account_id == self.account_id =>
+            {
+                if standing_order.status == StandingOrderStatus::Cancelled {
+                    return CommandEffect::reject(UpdateStandingOrderError::Cancelled(id));
+                }
+                if !is_valid_day_of_month(self.day_of_month) {
+                    return CommandEffect::reject(UpdateStandingOrderError::InvalidDayOfMonth(
+                        self.day_of_month,
+                    ));
+                }
+
+                let month_of_next_due = standing_order.next_due.replace_day(1).expect("valid date");
+                let next_due = due_date_from(self.day_of_month, self.today.max(month_of_next_due));
+                let event = StandingOrderEvent::Updated {
+                    id,
+                    amount: self.amount,
+                    day_of_month: self.day_of_month,
+                    next_due,
+                };
+                CommandEffect::emit_and_reply(event, move |state| match state {
+                    StandingOrderEntity::Nonexistent => {
+                        panic!("invalid command UpdateStandingOrder in state Nonexistent")
+                    }
+
+                    StandingOrderEntity::Existing(standing_order) => standing_order.clone(),
+                })
+            }
+
+            _ => CommandEffect::reject(UpdateStandingOrderError::NotFound(id)),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum UpdateStandingOrderError {
+    #[error("standing order with ID {0} not found")]
+    NotFound(Uuid),
+
+    #[error("standing order with ID {0} has been cancelled")]
+    Cancelled(Uuid),
+
+    #[error("day of month {0} is invalid")]
+    InvalidDayOfMonth(u8),
+}
+
+// Command: CancelStandingOrder ====================================================================
+
+#[derive(Debug)]
+pub struct CancelStandingOrder {
+    account_id: Uuid,
+}
+
+impl From<Uuid> for CancelStandingOrder {
+    /// Cancel the standing order of the account with the given ID.
+    fn from(account_id: Uuid) -> Self {
+        Self { account_id }
+    }
+}
+
+impl Command<StandingOrderEntity> for CancelStandingOrder {
+    type Reply = ();
+    type Error = CancelStandingOrderError;
+
+    fn handle_command(
+        self,
+        id: &Uuid,
+        state: &StandingOrderEntity,
+    ) -> CommandEffect<StandingOrderEntity, Self::Reply, Self::Error> {
+        let id = *id;
+
+        match state {
+            StandingOrderEntity::Existing(standing_order)
+                if standing_order.account_id == self.account_id =>
+            {
+                if standing_order.status == StandingOrderStatus::Cancelled {
+                    CommandEffect::reply(())
+                } else {
+                    let event = StandingOrderEvent::Cancelled { id };
+                    CommandEffect::emit_and_reply(event, |_| ())
+                }
+            }
+
+            _ => CommandEffect::reject(CancelStandingOrderError::NotFound(id)),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum CancelStandingOrderError {
+    #[error("standing order with ID {0} not found")]
+    NotFound(Uuid),
+}
+
+// Command: ClaimExecution =========================================================================
+
+#[derive(Debug)]
+pub struct ClaimExecution {
+    due: Date,
+    at: OffsetDateTime,
+}
+
+impl ClaimExecution {
+    /// Claim the transfer due at the given date for execution, which must happen before the
+    /// transfer is executed, such that it is executed at most once.
+    pub fn new(due: Date, at: OffsetDateTime) -> Self {
+        Self { due, at }
+    }
+}
+
+impl Command<StandingOrderEntity> for ClaimExecution {
+    type Reply = StandingOrder;
+    type Error = ClaimExecutionError;
+
+    fn handle_command(
+        self,
+        id: &Uuid,
+        state: &StandingOrderEntity,
+    ) -> CommandEffect<StandingOrderEntity, Self::Reply, Self::Error> {
+        let id = *id;
+
+        match state {
+            StandingOrderEntity::Nonexistent => {
+                CommandEffect::reject(ClaimExecutionError::NotFound(id))
+            }
+
+            StandingOrderEntity::Existing(standing_order)
+                if standing_order.status != StandingOrderStatus::Active
+                    || standing_order.next_due != self.due =>
+            {
+                CommandEffect::reject(ClaimExecutionError::NotDue { id, due: self.due })
+            }
+
+            StandingOrderEntity::Existing(standing_order) if standing_order.claimed.is_some() => {
+                CommandEffect::reject(ClaimExecutionError::AlreadyClaimed { id, due: self.due })
+            }
+
+            StandingOrderEntity::Existing(_) => {
+                let event = StandingOrderEvent::ExecutionClaimed {
+                    id,
+                    due: self.due,
+                    at: self.at,
+                };
+                CommandEffect::emit_and_reply(event, move |state| match state {
+                    StandingOrderEntity::Nonexistent => {
+                        panic!("invalid command ClaimExecution in state Nonexistent")
+                    }
+
+                    StandingOrderEntity::Existing(standing_order) => standing_order.clone(),
+                })
+            }
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ClaimExecutionError {
+    #[error("standing order with ID {0} not found")]
+    NotFound(Uuid),
+
+    #[error("standing order with ID {id} is not due at {due}")]
+    NotDue { id: Uuid, due: Date },
+
+    #[error("execution of standing order with ID {id} due at {due} has already been claimed")]
+    AlreadyClaimed { id: Uuid, due: Date },
+}
+
+// Command: RecordExecution ========================================================================
+
+#[derive(Debug)]
+pub struct RecordExecution {
+    due: Date,
+    at: OffsetDateTime,
+}
+
+impl RecordExecution {
+    /// Record the successful execution of the transfer due at the given date, which must have been
+    /// claimed, see [ClaimExecution].
+    pub fn new(due: Date, at: OffsetDateTime) -> Self {
+        Self { due, at }
+    }
+}
+
+impl Command<StandingOrderEntity> for RecordExecution {
+    type Reply = StandingOrder;
+    type Error = RecordExecutionError;
+
+    fn handle_command(
+        self,
+        id: &Uuid,
+        state: &StandingOrderEntity,
+    ) -> CommandEffect<StandingOrderEntity, Self::Reply, Self::Error> {
+        let id = *id;
+
+        match state {
+            StandingOrderEntity::Nonexistent => {
+                CommandEffect::reject(RecordExecutionError::NotFound(id))
+            }
+
+            StandingOrderEntity::Existing(standing_order)
+                if standing_order.claimed != Some(self.due) =>
+            {
+                CommandEffect::reject(RecordExecutionError::NotClaimed { id, due: self.due })
+            }
+
+            StandingOrderEntity::Existing(standing_order) => {
+                let event = StandingOrderEvent::Executed {
+                    id,
+                    due: self.due,
+                    next_due: next_due_date(standing_order.day_of_month, self.due),
+                    at: self.at,
+                };
+                CommandEffect::emit_and_reply(event, move |state| match state {
+                    StandingOrderEntity::Nonexistent => {
+                        panic!("invalid command RecordExecution in state Nonexistent")
+                    }
+
+                    StandingOrderEntity::Existing(standing_order) => standing_order.clone(),
+                })
+            }
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum RecordExecutionError {
+    #[error("standing order with ID {0} not found")]
+    NotFound(Uuid),
+
+    #[error("execution of standing order with ID {id} due at {due} has not been claimed")]
+    NotClaimed { id: Uuid, due: Date },
+}
+
+// Command: RecordFailure ==========================================================================
+
+#[derive(Debug)]
+pub struct RecordFailure {
+    due: Date,
+    reason: String,
+    max_attempts: u32,
+    at: OffsetDateTime,
+}
+
+impl RecordFailure {
+    /// Record a failed attempt to execute the transfer due at the given date, which must have been
+    /// claimed, see [ClaimExecution]; after the given maximum number of attempts the transfer is
+    /// given up and the next one becomes due.
+    pub fn new(due: Date, reason: String, max_attempts: u32, at: OffsetDateTime) -> Self {
+        Self {
+            due,
+            reason,
+            max_attempts,
+            at,
+        }
+    }
+}
+
+impl Command<StandingOrderEntity> for RecordFailure {
+    type Reply = StandingOrder;
+    type Error = RecordFailureError;
+
+    fn handle_command(
+        self,
+        id: &Uuid,
+        state: &StandingOrderEntity,
+    ) -> CommandEffect<StandingOrderEntity, Self::Reply, Self::Error> {
+        let id = *id;
+
+        match state {
+            StandingOrderEntity::Nonexistent => {
+                CommandEffect::reject(RecordFailureError::NotFound(id))
+            }
+
+            StandingOrderEntity::Existing(standing_order)
+                if standing_order.claimed != Some(self.due) =>
+            {
+                CommandEffect::reject(RecordFailureError::NotClaimed { id, due: self.due })
+            }
+
+            StandingOrderEntity::Existing(standing_order) => {
+                let failed_attempts = match &standing_order.last_failure {
+                    Some(failure) if failure.due == self.due => standing_order.failed_attempts + 1,
+                    _ => 1,
+                };
+                let next_due = if failed_attempts >= self.max_attempts {
+                    next_due_date(standing_order.day_of_month, self.due)
+                } else {
+                    self.due
+                };
+                let event = StandingOrderEvent::ExecutionFailed {
+                    id,
+                    due: self.due,
+                    reason: self.reason,
+                    failed_attempts,
+                    next_due,
+                    at: self.at,
+                };
+                CommandEffect::emit_and_reply(event, move |state| match state {
+                    StandingOrderEntity::Nonexistent => {
+                        panic!("invalid command RecordFailure in state Nonexistent")
+                    }
+
+                    StandingOrderEntity::Existing(standing_order) => standing_order.clone(),
+                })
+            }
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum RecordFailureError {
+    #[error("standing order with ID {0} not found")]
+    NotFound(Uuid),
+
+    #[error("execution of standing order with ID {id} due at {due} has not been claimed")]
+    NotClaimed { id: Uuid, due: Date },
+}
@@ -0,0 +1,25 @@
+use crate::domain::StandingOrder;
+use futures::Stream;
+use std::error::Error as StdError;
+use time::Date;
+use uuid::Uuid;
+
+#[trait_variant::make(Send)]
+pub trait StandingOrderRepository
+where
+    Self: Clone + Send + Sync + 'static,
+{
+    type Error: StdError;
+
+    /// The standing orders of the account with the given ID.
+    async fn standing_orders(
+        &self,
+        account_id: Uuid,
+    ) -> Result<impl Stream<Item = Result<StandingOrder, Self::Error>> + Send, Self::Error>;
+
+    /// The active standing orders due at or before the given date.
+    async fn due_standing_orders(
+        &self,
+        date: Date,
+    ) -> Result<impl Stream<Item = Result<StandingOrder, Self::Error>> + Send, Self::Error>;
+}
@@ -0,0 +1,266 @@
+use crate::domain::{AccountEntity, AccountEvent, Transaction, TransactionKind};
+use eventsourced::EventSourced;
+use serde::Serialize;
+use std::fmt::Write;
+use time::{format_description::well_known::Rfc3339, Date, UtcOffset};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// The transactions of an account within a date range (inclusive) together with the balances
+/// before and after; `opening_balance + credits - debits` always equals `closing_balance`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct Statement {
+    pub account_id: Uuid,
+
+    #[schema(value_type = String, format = Date)]
+    pub from: Date,
+
+    #[schema(value_type = String, format = Date)]
+    pub to: Date,
+
+    pub opening_balance: i64,
+    pub closing_balance: i64,
+
+    /// The sum of all increases of the balance.
+    pub credits: u64,
+
+    /// The sum of all decreases of the balance, including fees.
+    pub debits: u64,
+
+    pub transactions: Vec<Transaction>,
+}
+
+impl Statement {
+    /// Create a statement from the balance before `from` and the transactions within the range,
+    /// ordered by sequence number.
+    pub fn new(
+        account_id: Uuid,
+        from: Date,
+        to: Date,
+        opening_balance: i64,
+        transactions: Vec<Transaction>,
+    ) -> Self {
+        let mut credits = 0;
+        let mut debits = 0;
+        let mut balance = opening_balance;
+        for transaction in &transactions {
+            let movement = transaction.balance - balance;
+            if movement >= 0 {
+                credits += movement as u64;
+            } else {
+                debits += movement.unsigned_abs();
+            }
+            balance = transaction.balance;
+        }
+
+        Self {
+            account_id,
+            from,
+            to,
+            opening_balance,
+            closing_balance: balance,
+            credits,
+            debits,
+            transactions,
+        }
+    }
+
+    /// Create a statement by replaying the given events of an account, for accounts the
+    /// transactions of which have not been projected, e.g. because they were created before
+    /// statements existed. Legacy transactions without a time count toward the opening balance.
+    pub fn replay(
+        account_id: Uuid,
+        from: Date,
+        to: Date,
+        events: impl IntoIterator<Item = AccountEvent>,
+    ) -> Self {
+        let mut account = AccountEntity::default();
+        let mut transactions = Vec::<Transaction>::new();
+        for event in events {
+            let transaction = event.transaction(0);
+            account = account.handle_event(event);
+
+            // Legacy events have no sequence number, hence take the one resolved by the entity.
+            let (Some(mut transaction), AccountEntity::Existing(state)) = (transaction, &account)
+            else {
+                continue;
+            };
+            transaction.seq_no = state.seq_no;
+            if transaction.kind == TransactionKind::Reversal {
+                if let Some(reversed) = transactions
+                    .iter_mut()
+                    .find(|reversed| transaction.reverses == Some(reversed.seq_no))
+                {
+                    reversed.reversed_by = Some(transaction.seq_no);
+                }
+            }
+            transactions.push(transaction);
+        }
+
+        let date = |transaction: &Transaction| transaction.at.to_offset(UtcOffset::UTC).date();
+        let opening_balance = transactions
+            .iter()
+            .rev()
+            .find(|transaction| date(transaction) < from)
+            .map(|transaction| transaction.balance)
+            .unwrap_or_default();
+        let transactions = transactions
+            .into_iter()
+            .filter(|transaction| (from..=to).contains(&date(transaction)))
+            .collect();
+
+        Self::new(account_id, from, to, opening_balance, transactions)
+    }
+
+    /// Render as CSV with a row per transaction framed by rows for opening and closing balance.
+    pub fn to_csv(&self) -> String {
+        let mut csv = "at,seq_no,kind,amount,fee,balance\n".to_string();
+
+        writeln!(
+            csv,
+            "{},,opening_balance,,,{}",
+            self.from, self.opening_balance
+        )
+        .expect("can write to string");
+        for transaction in &self.transactions {
+            let at = transaction
+                .at
+                .format(&Rfc3339)
+                .expect("can format as RFC 3339");
+            let kind = serde_json::to_value(transaction.kind).expect("can serialize kind");
+            writeln!(
+                csv,
+                "{at},{},{},{},{},{}",
+                transaction.seq_no,
+                kind.as_str().unwrap_or_default(),
+                transaction.amount,
+                transaction.fee,
+                transaction.balance
+            )
+            .expect("can write to string");
+        }
+        writeln!(
+            csv,
+            "{},,closing_balance,,,{}",
+            self.to, self.closing_balance
+        )
+        .expect("can write to string");
+
+        csv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{AccountEvent, Statement, Transaction, TransactionKind};
+    use serde_json::json;
+    use time::macros::{date, datetime};
+    use uuid::Uuid;
+
+    #[test]
+    fn test_statement() {
+        let id = Uuid::now_v7();
+        let transactions = vec![
+            Transaction {
+                seq_no: 2,
+                kind: TransactionKind::Deposit,
+                amount: 100,
+                fee: 0,
+                balance: 150,
+                at: datetime!(2024-07-01 12:00 UTC),
+                reverses: None,
+                reversed_by: None,
+            },
+            Transaction {
+                seq_no: 3,
+                kind: TransactionKind::Withdrawal,
+                amount: 20,
+                fee: 5,
+                balance: 125,
+                at: datetime!(2024-07-02 12:00 UTC),
+                reverses: None,
+                reversed_by: None,
+            },
+        ];
+        let statement = Statement::new(
+            id,
+            date!(2024 - 07 - 01),
+            date!(2024 - 07 - 31),
+            50,
+            transactions,
+        );
+        assert_eq!(statement.closing_balance, 125);
+        assert_eq!(statement.credits, 100);
+        assert_eq!(statement.debits, 25);
+
+        assert_eq!(
+            statement.to_csv(),
+            "at,seq_no,kind,amount,fee,balance\n\
+             2024-07-01,,opening_balance,,,50\n\
+             2024-07-01T12:00:00Z,2,deposit,100,0,150\n\
+             2024-07-02T12:00:00Z,3,withdrawal,20,5,125\n\
+             2024-07-31,,closing_balance,,,125\n"
+        );
+    }
+
+    #[test]
+    fn test_replay() {
+        let id = Uuid::now_v7();
+        // Legacy events have neither sequence numbers nor times.
+        let legacy_events = [
+            json!({ "Created": { "id": id } }),
+            json!({ "Deposited": { "id": id, "amount": 100, "balance": 100 } }),
+        ]
+        .map(|event| serde_json::from_value::<AccountEvent>(event).unwrap());
+        let events = legacy_events.into_iter().chain([
+            AccountEvent::Withdrawn {
+                id,
+                seq_no: 3,
+                amount: 30,
+                fees: vec![],
+                balance: 70,
+                at: datetime!(2024-07-01 12:00 UTC),
+            },
+            AccountEvent::Reversed {
+                id,
+                seq_no: 4,
+                transaction_ref: 3,
+                amount: 30,
+                fee: 0,
+                balance: 100,
+                at: datetime!(2024-07-02 12:00 UTC),
+            },
+            AccountEvent::Deposited {
+                id,
+                seq_no: 5,
+                amount: 10,
+                balance: 110,
+                at: datetime!(2024-08-01 12:00 UTC),
+            },
+        ]);
+
+        let statement = Statement::replay(id, date!(2024 - 07 - 01), date!(2024 - 07 - 31), events);
+        assert_eq!(statement.opening_balance, 100);
+        assert_eq!(statement.closing_balance, 100);
+        assert_eq!(statement.credits, 30);
+        assert_eq!(statement.debits, 30);
+        let transactions = statement
+            .transactions
+            .iter()
+            .map(|transaction| {
+                (
+                    transaction.seq_no,
+                    transaction.kind,
+                    transaction.reversed_by,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            transactions,
+            vec![
+                (3, TransactionKind::Withdrawal, Some(4)),
+                (4, TransactionKind::Reversal, None)
+            ]
+        );
+    }
+}
@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct Transaction {
+    pub seq_no: u64,
+    pub kind: TransactionKind,
+    pub amount: u64,
+
+    /// The fees charged with this transaction, included in `balance`.
+    pub fee: u64,
+
+    pub balance: i64,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    pub at: OffsetDateTime,
+
+    /// Sequence number of the reversed transaction, if this is a reversal, or of the transaction
+    /// the fees of which are waived, if this is a fee waiver.
+    pub reverses: Option<u64>,
+
+    /// Sequence number of the reversal, if this transaction has been reversed.
+    pub reversed_by: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionKind {
+    Deposit,
+    Withdrawal,
+    HoldCapture,
+    Reversal,
+    Interest,
+    Fee,
+    FeeWaiver,
+}
@@ -0,0 +1,122 @@
+use crate::domain::AccountEvent;
+use hmac::{Hmac, Mac};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::Sha256;
+use std::fmt::Write;
+use time::{Duration, OffsetDateTime};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// An endpoint of a partner which gets account events pushed. The secret for signing the payloads
+/// is only handed out when the subscription is created.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct WebhookSubscription {
+    pub id: Uuid,
+    pub url: String,
+
+    /// Names of the account events to be pushed, e.g. `Deposited`; empty means all.
+    pub event_types: Vec<String>,
+
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    pub created_at: OffsetDateTime,
+}
+
+/// The URL and secret of a webhook subscription, needed to deliver to it.
+#[derive(Debug, Clone)]
+pub struct WebhookEndpoint {
+    pub url: String,
+    pub secret: String,
+}
+
+/// An account event enqueued for a webhook subscription; dead, i.e. given up, if its attempts
+/// are exhausted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub subscription_id: Uuid,
+    pub event_type: String,
+
+    /// The JSON body to be delivered, exactly as signed.
+    pub payload: String,
+
+    pub attempts: u32,
+    pub last_error: Option<String>,
+
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    pub created_at: OffsetDateTime,
+}
+
+/// The JSON body for the given account event.
+pub fn webhook_payload(event: &AccountEvent) -> serde_json::Result<String> {
+    let payload = json!({
+        "type": event.name(),
+        "account_id": event.id(),
+        "event": event,
+    });
+    serde_json::to_string(&payload)
+}
+
+/// Generate a new random secret for signing webhook payloads.
+pub fn generate_webhook_secret() -> String {
+    let mut secret = [0; 32];
+    OsRng.fill_bytes(&mut secret);
+    hex(&secret)
+}
+
+/// The signature for the given payload sent at the given time, i.e. `sha256=` followed by the
+/// hex encoded HMAC-SHA256 of `<timestamp>.<payload>` keyed with the secret. Including the
+/// timestamp allows receivers to reject replayed requests.
+pub fn sign_webhook_payload(secret: &str, timestamp: i64, payload: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any size");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(payload.as_bytes());
+    format!("sha256={}", hex(&mac.finalize().into_bytes()))
+}
+
+/// The delay before retrying a delivery after the given number of failed attempts, doubling with
+/// every attempt up to the given maximum.
+pub fn webhook_backoff(attempts: u32, base: Duration, max: Duration) -> Duration {
+    let factor = 2_i32.saturating_pow(attempts.saturating_sub(1));
+    base.checked_mul(factor).unwrap_or(max).min(max)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{byte:02x}");
+        hex
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{sign_webhook_payload, webhook_backoff};
+    use time::Duration;
+
+    #[test]
+    fn test_sign_webhook_payload() {
+        // Test vector computed with `openssl dgst -sha256 -hmac secret`.
+        let signature = sign_webhook_payload("secret", 1_700_000_000, r#"{"type":"Deposited"}"#);
+        assert_eq!(
+            signature,
+            "sha256=9d7f9ed371f6be91f7664ace6f2051081c52ecc104133922011c7e29d9739686"
+        );
+    }
+
+    #[test]
+    fn test_webhook_backoff() {
+        let base = Duration::seconds(10);
+        let max = Duration::hours(1);
+
+        assert_eq!(webhook_backoff(1, base, max), Duration::seconds(10));
+        assert_eq!(webhook_backoff(2, base, max), Duration::seconds(20));
+        assert_eq!(webhook_backoff(4, base, max), Duration::seconds(80));
+        assert_eq!(webhook_backoff(10, base, max), max);
+        assert_eq!(webhook_backoff(100, base, max), max);
+    }
+}
@@ -0,0 +1,50 @@
+use crate::domain::{WebhookDelivery, WebhookEndpoint, WebhookSubscription};
+use std::error::Error as StdError;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[trait_variant::make(Send)]
+pub trait WebhookRepository
+where
+    Self: Clone + Send + Sync + 'static,
+{
+    type Error: StdError;
+
+    async fn webhook_subscriptions(&self) -> Result<Vec<WebhookSubscription>, Self::Error>;
+
+    async fn insert_webhook_subscription(
+        &self,
+        subscription: &WebhookSubscription,
+        secret: &str,
+    ) -> Result<(), Self::Error>;
+
+    /// Delete the webhook subscription with the given ID together with its deliveries; returns
+    /// whether it existed.
+    async fn delete_webhook_subscription(&self, id: Uuid) -> Result<bool, Self::Error>;
+
+    /// Up to `limit` pending deliveries due at or before the given time, oldest first, each with
+    /// the endpoint to deliver to.
+    async fn due_webhook_deliveries(
+        &self,
+        at: OffsetDateTime,
+        limit: u32,
+    ) -> Result<Vec<(WebhookDelivery, WebhookEndpoint)>, Self::Error>;
+
+    async fn record_webhook_delivered(
+        &self,
+        id: Uuid,
+        at: OffsetDateTime,
+    ) -> Result<(), Self::Error>;
+
+    /// Record a failed attempt for the delivery with the given ID, either to be retried at
+    /// `retry_at` or, if `None`, given up, i.e. moved to the dead letters.
+    async fn record_webhook_failure(
+        &self,
+        id: Uuid,
+        error: &str,
+        retry_at: Option<OffsetDateTime>,
+    ) -> Result<(), Self::Error>;
+
+    /// The deliveries which have been given up, newest first.
+    async fn dead_webhook_deliveries(&self) -> Result<Vec<WebhookDelivery>, Self::Error>;
+}
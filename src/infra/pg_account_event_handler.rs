@@ -1,7 +1,11 @@
-use crate::domain::AccountEvent;
+use crate::{
+    domain::{self, AccountEvent, LEGACY_SEQ_NO},
+    infra::TransactionKind,
+};
 use eventsourced_projection::postgres::EventHandler;
 use sqlx::{Postgres, QueryBuilder, Transaction};
 use std::iter::once;
+use time::OffsetDateTime;
 use tracing::{info, instrument};
 use uuid::Uuid;
 
@@ -18,14 +22,31 @@ impl EventHandler<AccountEvent> for PgAccountEventHandler {
         tx: &mut Transaction<'static, Postgres>,
     ) -> Result<(), Self::Error> {
         match event {
-            AccountEvent::Created { id } => {
-                QueryBuilder::new("INSERT INTO account (id, balance) ")
-                    .push_values(once(id), |mut q, id| {
-                        q.push_bind(id).push_bind(0);
+            AccountEvent::Created {
+                id,
+                holders,
+                product,
+            } => {
+                QueryBuilder::new("INSERT INTO account (id, product, balance) ")
+                    .push_values(once((id, product.name)), |mut q, (id, product)| {
+                        q.push_bind(id).push_bind(product).push_bind(0_i64);
                     })
+                    // The account might already have been inserted by a reconciliation.
+                    .push(" ON CONFLICT DO NOTHING")
                     .build()
                     .execute(&mut **tx)
                     .await?;
+                // Legacy events have no holders.
+                if !holders.is_empty() {
+                    QueryBuilder::new("INSERT INTO account_holder (account_id, customer_id) ")
+                        .push_values(holders, |mut q, customer_id| {
+                            q.push_bind(id).push_bind(customer_id);
+                        })
+                        .push(" ON CONFLICT DO NOTHING")
+                        .build()
+                        .execute(&mut **tx)
+                        .await?;
+                }
 
                 info!(%id, "inserted account");
                 Ok(())
@@ -33,10 +54,21 @@ impl EventHandler<AccountEvent> for PgAccountEventHandler {
 
             AccountEvent::Deposited {
                 id,
+                seq_no,
                 amount,
                 balance,
+                at,
             } => {
+                let seq_no = resolve_seq_no(id, seq_no, tx).await?;
                 update(id, balance, tx).await?;
+                let transaction = transaction(
+                    seq_no,
+                    domain::TransactionKind::Deposit,
+                    amount,
+                    balance,
+                    at,
+                );
+                insert_transaction(id, transaction, tx).await?;
 
                 info!(amount, "account updated with deposited amount");
                 Ok(())
@@ -44,14 +76,162 @@ impl EventHandler<AccountEvent> for PgAccountEventHandler {
 
             AccountEvent::Withdrawn {
                 id,
-                balance,
+                seq_no,
                 amount,
+                fees,
+                balance,
+                at,
             } => {
+                let seq_no = resolve_seq_no(id, seq_no, tx).await?;
                 update(id, balance, tx).await?;
+                let transaction = domain::Transaction {
+                    fee: fees.iter().map(|fee| fee.amount).sum(),
+                    ..transaction(
+                        seq_no,
+                        domain::TransactionKind::Withdrawal,
+                        amount,
+                        balance,
+                        at,
+                    )
+                };
+                insert_transaction(id, transaction, tx).await?;
 
                 info!(amount, "account updated with withdrawn amount");
                 Ok(())
             }
+
+            AccountEvent::HoldCaptured {
+                id,
+                seq_no,
+                amount,
+                balance,
+                at,
+                ..
+            } => {
+                update(id, balance, tx).await?;
+                let transaction = transaction(
+                    seq_no,
+                    domain::TransactionKind::HoldCapture,
+                    amount,
+                    balance,
+                    at,
+                );
+                insert_transaction(id, transaction, tx).await?;
+
+                info!(amount, "account updated with captured hold amount");
+                Ok(())
+            }
+
+            AccountEvent::Reversed {
+                id,
+                seq_no,
+                transaction_ref,
+                amount,
+                fee,
+                balance,
+                at,
+            } => {
+                update(id, balance, tx).await?;
+                // Refunded fees are part of the amount of the reversal.
+                let transaction = domain::Transaction {
+                    reverses: Some(transaction_ref),
+                    ..transaction(
+                        seq_no,
+                        domain::TransactionKind::Reversal,
+                        amount + fee,
+                        balance,
+                        at,
+                    )
+                };
+                insert_transaction(id, transaction, tx).await?;
+                QueryBuilder::new("UPDATE account_transaction SET reversed_by = ")
+                    .push_bind(seq_no as i64)
+                    .push(" WHERE account_id = ")
+                    .push_bind(id)
+                    .push(" AND seq_no = ")
+                    .push_bind(transaction_ref as i64)
+                    .build()
+                    .execute(&mut **tx)
+                    .await?;
+
+                info!(amount, transaction_ref, "account updated with reversal");
+                Ok(())
+            }
+
+            AccountEvent::InterestPosted {
+                id,
+                seq_no,
+                amount,
+                balance,
+                at,
+            } => {
+                update(id, balance, tx).await?;
+                let transaction = transaction(
+                    seq_no,
+                    domain::TransactionKind::Interest,
+                    amount,
+                    balance,
+                    at,
+                );
+                insert_transaction(id, transaction, tx).await?;
+
+                info!(amount, "account updated with posted interest");
+                Ok(())
+            }
+
+            AccountEvent::FeeCharged {
+                id,
+                seq_no,
+                fee,
+                balance,
+                at,
+            } => {
+                update(id, balance, tx).await?;
+                let transaction = transaction(
+                    seq_no,
+                    domain::TransactionKind::Fee,
+                    fee.amount,
+                    balance,
+                    at,
+                );
+                insert_transaction(id, transaction, tx).await?;
+
+                info!(?fee, "account updated with charged fee");
+                Ok(())
+            }
+
+            AccountEvent::FeeWaived {
+                id,
+                seq_no,
+                transaction_ref,
+                amount,
+                balance,
+                at,
+            } => {
+                update(id, balance, tx).await?;
+                let transaction = domain::Transaction {
+                    reverses: Some(transaction_ref),
+                    ..transaction(
+                        seq_no,
+                        domain::TransactionKind::FeeWaiver,
+                        amount,
+                        balance,
+                        at,
+                    )
+                };
+                insert_transaction(id, transaction, tx).await?;
+
+                info!(amount, transaction_ref, "account updated with waived fee");
+                Ok(())
+            }
+
+            // Withdrawal limits, open holds, accrued interest and the status are not part of the
+            // account read model.
+            AccountEvent::WithdrawalLimitsSet { .. }
+            | AccountEvent::HoldPlaced { .. }
+            | AccountEvent::HoldReleased { .. }
+            | AccountEvent::InterestAccrued { .. }
+            | AccountEvent::StatusChanged { .. } => Ok(()),
         }
     }
 }
@@ -59,11 +239,11 @@ impl EventHandler<AccountEvent> for PgAccountEventHandler {
 #[instrument(skip(tx))]
 async fn update(
     id: Uuid,
-    balance: u64,
+    balance: i64,
     tx: &mut Transaction<'static, Postgres>,
 ) -> Result<(), sqlx::Error> {
     QueryBuilder::new("UPDATE account SET balance = ")
-        .push_bind(balance as i64)
+        .push_bind(balance)
         .push(" WHERE id = ")
         .push_bind(id)
         .build()
@@ -71,3 +251,69 @@ async fn update(
         .await?;
     Ok(())
 }
+
+/// The given sequence number or, for legacy events, the one following the last transaction: legacy
+/// streams only contain deposits and withdrawals after `Created`.
+#[instrument(skip(tx))]
+async fn resolve_seq_no(
+    id: Uuid,
+    seq_no: u64,
+    tx: &mut Transaction<'static, Postgres>,
+) -> Result<u64, sqlx::Error> {
+    if seq_no != LEGACY_SEQ_NO {
+        return Ok(seq_no);
+    }
+
+    let seq_no = sqlx::query_scalar::<_, i64>(
+        "SELECT COALESCE(MAX(seq_no), 1) + 1 FROM account_transaction WHERE account_id = $1",
+    )
+    .bind(id)
+    .fetch_one(&mut **tx)
+    .await?;
+    Ok(seq_no as u64)
+}
+
+fn transaction(
+    seq_no: u64,
+    kind: domain::TransactionKind,
+    amount: u64,
+    balance: i64,
+    at: OffsetDateTime,
+) -> domain::Transaction {
+    domain::Transaction {
+        seq_no,
+        kind,
+        amount,
+        fee: 0,
+        balance,
+        at,
+        reverses: None,
+        reversed_by: None,
+    }
+}
+
+#[instrument(skip(tx))]
+async fn insert_transaction(
+    id: Uuid,
+    transaction: domain::Transaction,
+    tx: &mut Transaction<'static, Postgres>,
+) -> Result<(), sqlx::Error> {
+    QueryBuilder::new(
+        "INSERT INTO account_transaction (account_id, seq_no, kind, amount, fee, balance, at, \
+         reverses) ",
+    )
+    .push_values(once(transaction), |mut q, transaction| {
+        q.push_bind(id)
+            .push_bind(transaction.seq_no as i64)
+            .push_bind(TransactionKind::from(transaction.kind))
+            .push_bind(transaction.amount as i64)
+            .push_bind(transaction.fee as i64)
+            .push_bind(transaction.balance)
+            .push_bind(transaction.at)
+            .push_bind(transaction.reverses.map(|reverses| reverses as i64));
+    })
+    .build()
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
@@ -1,6 +1,10 @@
-use crate::domain::{self, AccountRepository};
+use crate::{
+    domain::{self, AccountFilter, AccountRepository, TransactionFilter},
+    infra::{LedgerAccount, TransactionKind},
+};
 use futures::{Stream, TryStreamExt};
-use sqlx::{prelude::FromRow, PgPool};
+use sqlx::{prelude::FromRow, PgPool, QueryBuilder};
+use time::{Date, Duration, OffsetDateTime};
 use tracing::instrument;
 use uuid::Uuid;
 
@@ -27,17 +31,275 @@ impl AccountRepository for PgAccountRepository {
             .map_ok(domain::Account::from);
         Ok(accounts)
     }
+
+    #[instrument(skip(self))]
+    async fn account(&self, id: Uuid) -> Result<Option<domain::Account>, Self::Error> {
+        let account = sqlx::query_as::<_, Account>("SELECT * FROM account WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(account.map(domain::Account::from))
+    }
+
+    #[instrument(skip(self))]
+    async fn accounts_page(
+        &self,
+        filter: &AccountFilter,
+        after: Option<Uuid>,
+        limit: u32,
+    ) -> Result<Vec<domain::Account>, Self::Error> {
+        let mut query = QueryBuilder::new("SELECT id, product, balance FROM account WHERE TRUE");
+        if let Some(product) = &filter.product {
+            query.push(" AND product = ").push_bind(product);
+        }
+        if let Some(min_balance) = filter.min_balance {
+            query.push(" AND balance >= ").push_bind(min_balance);
+        }
+        if let Some(max_balance) = filter.max_balance {
+            query.push(" AND balance <= ").push_bind(max_balance);
+        }
+        if let Some(holder) = filter.holder {
+            query
+                .push(" AND id IN (SELECT account_id FROM account_holder WHERE customer_id = ")
+                .push_bind(holder)
+                .push(")");
+        }
+        if let Some(after) = after {
+            query.push(" AND id > ").push_bind(after);
+        }
+        query.push(" ORDER BY id LIMIT ").push_bind(limit as i64);
+
+        let accounts = query
+            .build_query_as::<Account>()
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(domain::Account::from)
+            .collect();
+        Ok(accounts)
+    }
+
+    #[instrument(skip(self))]
+    async fn transactions(
+        &self,
+        id: Uuid,
+    ) -> Result<impl Stream<Item = Result<domain::Transaction, Self::Error>> + Send, Self::Error>
+    {
+        let transactions = sqlx::query_as::<_, Transaction>(
+            "SELECT seq_no, kind, amount, fee, balance, at, reverses, reversed_by
+             FROM account_transaction
+             WHERE account_id = $1
+             ORDER BY seq_no",
+        )
+        .bind(id)
+        .fetch(&self.pool)
+        .map_ok(domain::Transaction::from);
+        Ok(transactions)
+    }
+
+    #[instrument(skip(self))]
+    async fn transactions_page(
+        &self,
+        id: Uuid,
+        filter: &TransactionFilter,
+        after: Option<u64>,
+        limit: u32,
+    ) -> Result<Vec<domain::Transaction>, Self::Error> {
+        let mut query = QueryBuilder::new(
+            "SELECT seq_no, kind, amount, fee, balance, at, reverses, reversed_by
+             FROM account_transaction
+             WHERE account_id = ",
+        );
+        query.push_bind(id);
+        if let Some(kind) = filter.kind {
+            query
+                .push(" AND kind = ")
+                .push_bind(TransactionKind::from(kind));
+        }
+        if let Some(from) = filter.from {
+            query.push(" AND at >= ").push_bind(from);
+        }
+        if let Some(to) = filter.to {
+            query.push(" AND at < ").push_bind(to);
+        }
+        if let Some(after) = after {
+            query.push(" AND seq_no > ").push_bind(after as i64);
+        }
+        query
+            .push(" ORDER BY seq_no LIMIT ")
+            .push_bind(limit as i64);
+
+        let transactions = query
+            .build_query_as::<Transaction>()
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(domain::Transaction::from)
+            .collect();
+        Ok(transactions)
+    }
+
+    #[instrument(skip(self))]
+    async fn statement(
+        &self,
+        id: Uuid,
+        from: Date,
+        to: Date,
+    ) -> Result<Option<(domain::Account, domain::Statement)>, Self::Error> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+            .execute(&mut *tx)
+            .await?;
+
+        let account = sqlx::query_as::<_, Account>("SELECT * FROM account WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        let Some(account) = account else {
+            return Ok(None);
+        };
+
+        let from_at = from.midnight().assume_utc();
+        let to_at = (to + Duration::days(1)).midnight().assume_utc();
+
+        let opening_balance = sqlx::query_scalar::<_, i64>(
+            "SELECT balance
+             FROM account_transaction
+             WHERE account_id = $1 AND at < $2
+             ORDER BY seq_no DESC
+             LIMIT 1",
+        )
+        .bind(id)
+        .bind(from_at)
+        .fetch_optional(&mut *tx)
+        .await?
+        .unwrap_or_default();
+
+        let transactions = sqlx::query_as::<_, Transaction>(
+            "SELECT seq_no, kind, amount, fee, balance, at, reverses, reversed_by
+             FROM account_transaction
+             WHERE account_id = $1 AND at >= $2 AND at < $3
+             ORDER BY seq_no",
+        )
+        .bind(id)
+        .bind(from_at)
+        .bind(to_at)
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .map(domain::Transaction::from)
+        .collect();
+
+        tx.commit().await?;
+
+        let statement = domain::Statement::new(id, from, to, opening_balance, transactions);
+        Ok(Some((account.into(), statement)))
+    }
+
+    #[instrument(skip(self))]
+    async fn repair_account(
+        &self,
+        account: domain::Account,
+        holders: Vec<Uuid>,
+    ) -> Result<(), Self::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            "INSERT INTO account (id, product, balance)
+             VALUES ($1, $2, $3)
+             ON CONFLICT (id) DO UPDATE SET product = $2, balance = $3",
+        )
+        .bind(account.id)
+        .bind(account.product)
+        .bind(account.balance)
+        .execute(&mut *tx)
+        .await?;
+
+        if !holders.is_empty() {
+            QueryBuilder::new("INSERT INTO account_holder (account_id, customer_id) ")
+                .push_values(holders, |mut q, customer_id| {
+                    q.push_bind(account.id).push_bind(customer_id);
+                })
+                .push(" ON CONFLICT DO NOTHING")
+                .build()
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await
+    }
+
+    #[instrument(skip(self))]
+    async fn trial_balance(&self) -> Result<domain::TrialBalance, Self::Error> {
+        let accounts = sqlx::query_as::<_, (LedgerAccount, i64, i64)>(
+            "SELECT ledger_account, SUM(debit)::bigint, SUM(credit)::bigint
+             FROM ledger_posting
+             GROUP BY ledger_account
+             ORDER BY ledger_account",
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(
+            |(ledger_account, debit, credit)| domain::LedgerAccountBalance {
+                ledger_account: ledger_account.into(),
+                debit: debit as u64,
+                credit: credit as u64,
+            },
+        )
+        .collect();
+        Ok(domain::TrialBalance::new(accounts))
+    }
 }
 
 #[derive(Debug, FromRow)]
 struct Account {
     id: Uuid,
+    product: String,
     balance: i64,
 }
 
 impl From<Account> for domain::Account {
-    fn from(Account { id, balance }: Account) -> Self {
-        let balance = balance as u64;
-        domain::Account { id, balance }
+    fn from(
+        Account {
+            id,
+            product,
+            balance,
+        }: Account,
+    ) -> Self {
+        domain::Account {
+            id,
+            product,
+            balance,
+        }
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct Transaction {
+    seq_no: i64,
+    kind: TransactionKind,
+    amount: i64,
+    fee: i64,
+    balance: i64,
+    at: OffsetDateTime,
+    reverses: Option<i64>,
+    reversed_by: Option<i64>,
+}
+
+impl From<Transaction> for domain::Transaction {
+    fn from(transaction: Transaction) -> Self {
+        domain::Transaction {
+            seq_no: transaction.seq_no as u64,
+            kind: transaction.kind.into(),
+            amount: transaction.amount as u64,
+            fee: transaction.fee as u64,
+            balance: transaction.balance,
+            at: transaction.at,
+            reverses: transaction.reverses.map(|reverses| reverses as u64),
+            reversed_by: transaction
+                .reversed_by
+                .map(|reversed_by| reversed_by as u64),
+        }
     }
 }
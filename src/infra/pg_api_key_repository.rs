@@ -0,0 +1,134 @@
+use crate::{
+    domain::{self, ApiKeyRepository},
+    infra::ApiKeyScope,
+};
+use sqlx::{prelude::FromRow, PgPool};
+use time::OffsetDateTime;
+use tracing::instrument;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct PgApiKeyRepository {
+    pool: PgPool,
+}
+
+impl PgApiKeyRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+impl ApiKeyRepository for PgApiKeyRepository {
+    type Error = sqlx::Error;
+
+    #[instrument(skip(self))]
+    async fn api_keys(&self) -> Result<Vec<domain::ApiKey>, Self::Error> {
+        let api_keys = sqlx::query_as::<_, ApiKey>("SELECT * FROM api_key ORDER BY id")
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|api_key| api_key.into_parts().0)
+            .collect();
+        Ok(api_keys)
+    }
+
+    #[instrument(skip(self))]
+    async fn api_key(&self, id: Uuid) -> Result<Option<(domain::ApiKey, Vec<u8>)>, Self::Error> {
+        let api_key = sqlx::query_as::<_, ApiKey>("SELECT * FROM api_key WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(api_key.map(ApiKey::into_parts))
+    }
+
+    #[instrument(skip(self, hash))]
+    async fn insert_api_key(
+        &self,
+        api_key: &domain::ApiKey,
+        hash: Vec<u8>,
+    ) -> Result<(), Self::Error> {
+        let scopes = api_key
+            .scopes
+            .iter()
+            .copied()
+            .map(ApiKeyScope::from)
+            .collect::<Vec<_>>();
+        sqlx::query(
+            "INSERT INTO api_key (id, name, hash, scopes, created_at, rotated_at, revoked_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        )
+        .bind(api_key.id)
+        .bind(&api_key.name)
+        .bind(hash)
+        .bind(scopes)
+        .bind(api_key.created_at)
+        .bind(api_key.rotated_at)
+        .bind(api_key.revoked_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    #[instrument(skip(self, hash))]
+    async fn rotate_api_key(
+        &self,
+        id: Uuid,
+        hash: Vec<u8>,
+        at: OffsetDateTime,
+    ) -> Result<Option<domain::ApiKey>, Self::Error> {
+        let api_key = sqlx::query_as::<_, ApiKey>(
+            "UPDATE api_key SET hash = $2, rotated_at = $3
+             WHERE id = $1 AND revoked_at IS NULL
+             RETURNING *",
+        )
+        .bind(id)
+        .bind(hash)
+        .bind(at)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(api_key.map(|api_key| api_key.into_parts().0))
+    }
+
+    #[instrument(skip(self))]
+    async fn revoke_api_key(
+        &self,
+        id: Uuid,
+        at: OffsetDateTime,
+    ) -> Result<Option<domain::ApiKey>, Self::Error> {
+        let api_key = sqlx::query_as::<_, ApiKey>(
+            "UPDATE api_key SET revoked_at = COALESCE(revoked_at, $2)
+             WHERE id = $1
+             RETURNING *",
+        )
+        .bind(id)
+        .bind(at)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(api_key.map(|api_key| api_key.into_parts().0))
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct ApiKey {
+    id: Uuid,
+    name: String,
+    hash: Vec<u8>,
+    scopes: Vec<ApiKeyScope>,
+    created_at: OffsetDateTime,
+    rotated_at: Option<OffsetDateTime>,
+    revoked_at: Option<OffsetDateTime>,
+}
+
+impl ApiKey {
+    fn into_parts(self) -> (domain::ApiKey, Vec<u8>) {
+        let api_key = domain::ApiKey {
+            id: self.id,
+            name: self.name,
+            scopes: self.scopes.into_iter().map(Into::into).collect(),
+            created_at: self.created_at,
+            rotated_at: self.rotated_at,
+            revoked_at: self.revoked_at,
+        };
+        (api_key, self.hash)
+    }
+}
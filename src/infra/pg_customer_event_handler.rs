@@ -0,0 +1,63 @@
+use crate::{domain::CustomerEvent, infra::KycStatus};
+use eventsourced_projection::postgres::EventHandler;
+use sqlx::{Postgres, QueryBuilder, Transaction};
+use std::iter::once;
+use tracing::{info, instrument};
+
+#[derive(Debug, Clone)]
+pub struct PgCustomerEventHandler;
+
+impl EventHandler<CustomerEvent> for PgCustomerEventHandler {
+    type Error = sqlx::Error;
+
+    #[instrument(skip(self, tx))]
+    async fn handle_event(
+        &self,
+        event: CustomerEvent,
+        tx: &mut Transaction<'static, Postgres>,
+    ) -> Result<(), Self::Error> {
+        match event {
+            CustomerEvent::Created { id, name, email } => {
+                QueryBuilder::new("INSERT INTO customer (id, name, email, kyc_status) ")
+                    .push_values(once((id, name, email)), |mut q, (id, name, email)| {
+                        q.push_bind(id)
+                            .push_bind(name)
+                            .push_bind(email)
+                            .push_bind(KycStatus::Pending);
+                    })
+                    .build()
+                    .execute(&mut **tx)
+                    .await?;
+
+                info!(%id, "inserted customer");
+                Ok(())
+            }
+
+            CustomerEvent::ContactChanged { id, email } => {
+                QueryBuilder::new("UPDATE customer SET email = ")
+                    .push_bind(email)
+                    .push(" WHERE id = ")
+                    .push_bind(id)
+                    .build()
+                    .execute(&mut **tx)
+                    .await?;
+
+                info!(%id, "customer updated with changed contact");
+                Ok(())
+            }
+
+            CustomerEvent::KycStatusChanged { id, kyc_status } => {
+                QueryBuilder::new("UPDATE customer SET kyc_status = ")
+                    .push_bind(KycStatus::from(kyc_status))
+                    .push(" WHERE id = ")
+                    .push_bind(id)
+                    .build()
+                    .execute(&mut **tx)
+                    .await?;
+
+                info!(%id, ?kyc_status, "customer updated with changed KYC status");
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use crate::{
+    domain::{self, CustomerRepository},
+    infra::KycStatus,
+};
+use futures::{Stream, TryStreamExt};
+use sqlx::{prelude::FromRow, PgPool};
+use tracing::instrument;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct PgCustomerRepository {
+    pool: PgPool,
+}
+
+impl PgCustomerRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+impl CustomerRepository for PgCustomerRepository {
+    type Error = sqlx::Error;
+
+    #[instrument(skip(self))]
+    async fn customers(
+        &self,
+    ) -> Result<impl Stream<Item = Result<domain::Customer, Self::Error>> + Send, Self::Error> {
+        let customers = sqlx::query_as::<_, Customer>("SELECT * FROM customer")
+            .fetch(&self.pool)
+            .map_ok(domain::Customer::from);
+        Ok(customers)
+    }
+
+    #[instrument(skip(self))]
+    async fn customer(&self, id: Uuid) -> Result<Option<domain::Customer>, Self::Error> {
+        let customer = sqlx::query_as::<_, Customer>("SELECT * FROM customer WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(customer.map(domain::Customer::from))
+    }
+
+    #[instrument(skip(self))]
+    async fn existing_customers(&self, ids: &[Uuid]) -> Result<Vec<Uuid>, Self::Error> {
+        sqlx::query_scalar::<_, Uuid>("SELECT id FROM customer WHERE id = ANY($1)")
+            .bind(ids)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    #[instrument(skip(self))]
+    async fn accounts(
+        &self,
+        id: Uuid,
+    ) -> Result<impl Stream<Item = Result<domain::Account, Self::Error>> + Send, Self::Error> {
+        let accounts = sqlx::query_as::<_, (Uuid, String, i64)>(
+            "SELECT a.id, a.product, a.balance
+             FROM account a JOIN account_holder h ON h.account_id = a.id
+             WHERE h.customer_id = $1",
+        )
+        .bind(id)
+        .fetch(&self.pool)
+        .map_ok(|(id, product, balance)| domain::Account {
+            id,
+            product,
+            balance,
+        });
+        Ok(accounts)
+    }
+
+    #[instrument(skip(self))]
+    async fn is_holder(&self, id: Uuid, account_id: Uuid) -> Result<bool, Self::Error> {
+        sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS (
+               SELECT 1 FROM account_holder WHERE account_id = $1 AND customer_id = $2
+             )",
+        )
+        .bind(account_id)
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct Customer {
+    id: Uuid,
+    name: String,
+    email: String,
+    kyc_status: KycStatus,
+}
+
+impl From<Customer> for domain::Customer {
+    fn from(customer: Customer) -> Self {
+        domain::Customer {
+            id: customer.id,
+            name: customer.name,
+            email: customer.email,
+            kyc_status: customer.kyc_status.into(),
+        }
+    }
+}
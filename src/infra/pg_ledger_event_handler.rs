@@ -0,0 +1,91 @@
+use crate::{
+    domain::{self, postings, reversal_postings, AccountEvent, Posting, LEGACY_SEQ_NO},
+    infra::LedgerAccount,
+};
+use eventsourced_projection::postgres::EventHandler;
+use sqlx::{Postgres, QueryBuilder, Transaction};
+use tracing::{debug, instrument};
+
+/// Journalizes account events as balanced postings into the general ledger.
+#[derive(Debug, Clone)]
+pub struct PgLedgerEventHandler;
+
+impl EventHandler<AccountEvent> for PgLedgerEventHandler {
+    type Error = sqlx::Error;
+
+    #[instrument(skip(self, tx))]
+    async fn handle_event(
+        &self,
+        event: AccountEvent,
+        tx: &mut Transaction<'static, Postgres>,
+    ) -> Result<(), Self::Error> {
+        let (id, seq_no, at) = match &event {
+            AccountEvent::Deposited { id, seq_no, at, .. }
+            | AccountEvent::Withdrawn { id, seq_no, at, .. }
+            | AccountEvent::HoldCaptured { id, seq_no, at, .. }
+            | AccountEvent::Reversed { id, seq_no, at, .. }
+            | AccountEvent::InterestPosted { id, seq_no, at, .. }
+            | AccountEvent::FeeCharged { id, seq_no, at, .. }
+            | AccountEvent::FeeWaived { id, seq_no, at, .. } => (*id, *seq_no, *at),
+
+            // Other events do not move money.
+            _ => return Ok(()),
+        };
+
+        // Legacy streams only contain deposits and withdrawals after `Created`, all journalized.
+        let seq_no = if seq_no == LEGACY_SEQ_NO {
+            sqlx::query_scalar::<_, i64>(
+                "SELECT COALESCE(MAX(seq_no), 1) + 1 FROM ledger_posting WHERE account_id = $1",
+            )
+            .bind(id)
+            .fetch_one(&mut **tx)
+            .await? as u64
+        } else {
+            seq_no
+        };
+
+        let postings = match event {
+            AccountEvent::Reversed {
+                transaction_ref,
+                fee,
+                ..
+            } => {
+                let (debit, credit) = sqlx::query_as::<_, (i64, i64)>(
+                    "SELECT debit, credit
+                     FROM ledger_posting
+                     WHERE account_id = $1 AND seq_no = $2 AND ledger_account = 'cash'",
+                )
+                .bind(id)
+                .bind(transaction_ref as i64)
+                .fetch_one(&mut **tx)
+                .await?;
+                let cash_posting = Posting {
+                    ledger_account: domain::LedgerAccount::Cash,
+                    debit: debit as u64,
+                    credit: credit as u64,
+                };
+                reversal_postings(cash_posting, fee)
+            }
+
+            event => postings(&event),
+        };
+
+        QueryBuilder::new(
+            "INSERT INTO ledger_posting (account_id, seq_no, ledger_account, debit, credit, at) ",
+        )
+        .push_values(&postings, |mut q, posting| {
+            q.push_bind(id)
+                .push_bind(seq_no as i64)
+                .push_bind(LedgerAccount::from(posting.ledger_account))
+                .push_bind(posting.debit as i64)
+                .push_bind(posting.credit as i64)
+                .push_bind(at);
+        })
+        .build()
+        .execute(&mut **tx)
+        .await?;
+
+        debug!(%id, seq_no, ?postings, "inserted postings");
+        Ok(())
+    }
+}
@@ -0,0 +1,143 @@
+use crate::{domain::StandingOrderEvent, infra::StandingOrderStatus};
+use eventsourced_projection::postgres::EventHandler;
+use sqlx::{Postgres, QueryBuilder, Transaction};
+use std::iter::once;
+use tracing::{info, instrument};
+
+#[derive(Debug, Clone)]
+pub struct PgStandingOrderEventHandler;
+
+impl EventHandler<StandingOrderEvent> for PgStandingOrderEventHandler {
+    type Error = sqlx::Error;
+
+    #[instrument(skip(self, tx))]
+    async fn handle_event(
+        &self,
+        event: StandingOrderEvent,
+        tx: &mut Transaction<'static, Postgres>,
+    ) -> Result<(), Self::Error> {
+        match event {
+            StandingOrderEvent::Created {
+                id,
+                account_id,
+                payee_id,
+                amount,
+                day_of_month,
+                next_due,
+            } => {
+                QueryBuilder::new(
+                    "INSERT INTO standing_order (id, account_id, payee_id, amount, day_of_month, \
+                     next_due, status, failed_attempts) ",
+                )
+                .push_values(once(()), |mut q, _| {
+                    q.push_bind(id)
+                        .push_bind(account_id)
+                        .push_bind(payee_id)
+                        .push_bind(amount as i64)
+                        .push_bind(day_of_month as i16)
+                        .push_bind(next_due)
+                        .push_bind(StandingOrderStatus::Active)
+                        .push_bind(0_i32);
+                })
+                .build()
+                .execute(&mut **tx)
+                .await?;
+
+                info!(%id, "inserted standing order");
+                Ok(())
+            }
+
+            StandingOrderEvent::Updated {
+                id,
+                amount,
+                day_of_month,
+                next_due,
+            } => {
+                QueryBuilder::new("UPDATE standing_order SET amount = ")
+                    .push_bind(amount as i64)
+                    .push(", day_of_month = ")
+                    .push_bind(day_of_month as i16)
+                    .push(", next_due = ")
+                    .push_bind(next_due)
+                    .push(", failed_attempts = 0 WHERE id = ")
+                    .push_bind(id)
+                    .build()
+                    .execute(&mut **tx)
+                    .await?;
+
+                info!(%id, "standing order updated");
+                Ok(())
+            }
+
+            StandingOrderEvent::Cancelled { id } => {
+                QueryBuilder::new("UPDATE standing_order SET status = ")
+                    .push_bind(StandingOrderStatus::Cancelled)
+                    .push(" WHERE id = ")
+                    .push_bind(id)
+                    .build()
+                    .execute(&mut **tx)
+                    .await?;
+
+                info!(%id, "standing order cancelled");
+                Ok(())
+            }
+
+            StandingOrderEvent::ExecutionClaimed { id, due, .. } => {
+                QueryBuilder::new("UPDATE standing_order SET claimed = ")
+                    .push_bind(due)
+                    .push(" WHERE id = ")
+                    .push_bind(id)
+                    .build()
+                    .execute(&mut **tx)
+                    .await?;
+
+                info!(%id, %due, "standing order execution claimed");
+                Ok(())
+            }
+
+            StandingOrderEvent::Executed { id, next_due, .. } => {
+                QueryBuilder::new("UPDATE standing_order SET next_due = ")
+                    .push_bind(next_due)
+                    .push(
+                        ", failed_attempts = 0, last_failure_due = NULL, last_failure_reason = \
+                         NULL, last_failure_at = NULL, claimed = NULL WHERE id = ",
+                    )
+                    .push_bind(id)
+                    .build()
+                    .execute(&mut **tx)
+                    .await?;
+
+                info!(%id, %next_due, "standing order updated with execution");
+                Ok(())
+            }
+
+            StandingOrderEvent::ExecutionFailed {
+                id,
+                due,
+                reason,
+                failed_attempts,
+                next_due,
+                at,
+            } => {
+                QueryBuilder::new("UPDATE standing_order SET next_due = ")
+                    .push_bind(next_due)
+                    .push(", failed_attempts = ")
+                    .push_bind(failed_attempts as i32)
+                    .push(", last_failure_due = ")
+                    .push_bind(due)
+                    .push(", last_failure_reason = ")
+                    .push_bind(reason)
+                    .push(", last_failure_at = ")
+                    .push_bind(at)
+                    .push(", claimed = NULL WHERE id = ")
+                    .push_bind(id)
+                    .build()
+                    .execute(&mut **tx)
+                    .await?;
+
+                info!(%id, %due, failed_attempts, "standing order updated with failed execution");
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use crate::{
+    domain::{self, StandingOrderRepository},
+    infra::StandingOrderStatus,
+};
+use futures::{Stream, TryStreamExt};
+use sqlx::{prelude::FromRow, PgPool};
+use time::{Date, OffsetDateTime};
+use tracing::instrument;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct PgStandingOrderRepository {
+    pool: PgPool,
+}
+
+impl PgStandingOrderRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+impl StandingOrderRepository for PgStandingOrderRepository {
+    type Error = sqlx::Error;
+
+    #[instrument(skip(self))]
+    async fn standing_orders(
+        &self,
+        account_id: Uuid,
+    ) -> Result<impl Stream<Item = Result<domain::StandingOrder, Self::Error>> + Send, Self::Error>
+    {
+        let standing_orders = sqlx::query_as::<_, StandingOrder>(
+            "SELECT * FROM standing_order WHERE account_id = $1 ORDER BY id",
+        )
+        .bind(account_id)
+        .fetch(&self.pool)
+        .map_ok(domain::StandingOrder::from);
+        Ok(standing_orders)
+    }
+
+    #[instrument(skip(self))]
+    async fn due_standing_orders(
+        &self,
+        date: Date,
+    ) -> Result<impl Stream<Item = Result<domain::StandingOrder, Self::Error>> + Send, Self::Error>
+    {
+        let standing_orders = sqlx::query_as::<_, StandingOrder>(
+            "SELECT * FROM standing_order WHERE status = 'active' AND next_due <= $1 ORDER BY \
+             next_due, id",
+        )
+        .bind(date)
+        .fetch(&self.pool)
+        .map_ok(domain::StandingOrder::from);
+        Ok(standing_orders)
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct StandingOrder {
+    id: Uuid,
+    account_id: Uuid,
+    payee_id: Uuid,
+    amount: i64,
+    day_of_month: i16,
+    next_due: Date,
+    status: StandingOrderStatus,
+    failed_attempts: i32,
+    last_failure_due: Option<Date>,
+    last_failure_reason: Option<String>,
+    last_failure_at: Option<OffsetDateTime>,
+    claimed: Option<Date>,
+}
+
+impl From<StandingOrder> for domain::StandingOrder {
+    fn from(standing_order: StandingOrder) -> Self {
+        let last_failure = match (
+            standing_order.last_failure_due,
+            standing_order.last_failure_reason,
+            standing_order.last_failure_at,
+        ) {
+            (Some(due), Some(reason), Some(at)) => {
+                Some(domain::ExecutionFailure { due, reason, at })
+            }
+            _ => None,
+        };
+
+        domain::StandingOrder {
+            id: standing_order.id,
+            account_id: standing_order.account_id,
+            payee_id: standing_order.payee_id,
+            amount: standing_order.amount as u64,
+            day_of_month: standing_order.day_of_month as u8,
+            next_due: standing_order.next_due,
+            status: standing_order.status.into(),
+            failed_attempts: standing_order.failed_attempts as u32,
+            last_failure,
+            claimed: standing_order.claimed,
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use crate::domain::{webhook_payload, AccountEvent};
+use eventsourced_projection::postgres::EventHandler;
+use sqlx::{Postgres, QueryBuilder, Transaction};
+use time::OffsetDateTime;
+use tracing::{debug, instrument};
+use uuid::Uuid;
+
+/// Enqueues account events into the outbox, i.e. as pending deliveries for all webhook
+/// subscriptions with matching event types. As enqueueing happens in the transaction of the
+/// projection, no event is lost or enqueued twice.
+#[derive(Debug, Clone)]
+pub struct PgWebhookEventHandler;
+
+impl EventHandler<AccountEvent> for PgWebhookEventHandler {
+    type Error = sqlx::Error;
+
+    #[instrument(skip(self, tx))]
+    async fn handle_event(
+        &self,
+        event: AccountEvent,
+        tx: &mut Transaction<'static, Postgres>,
+    ) -> Result<(), Self::Error> {
+        let event_type = event.name();
+
+        let subscription_ids = sqlx::query_scalar::<_, Uuid>(
+            "SELECT id
+             FROM webhook_subscription
+             WHERE cardinality(event_types) = 0 OR $1 = ANY(event_types)",
+        )
+        .bind(event_type)
+        .fetch_all(&mut **tx)
+        .await?;
+        if subscription_ids.is_empty() {
+            return Ok(());
+        }
+
+        let payload = webhook_payload(&event).map_err(|error| sqlx::Error::Encode(error.into()))?;
+        let now = OffsetDateTime::now_utc();
+
+        QueryBuilder::new(
+            "INSERT INTO webhook_delivery (id, subscription_id, event_type, payload, status, \
+             attempts, next_attempt_at, created_at) ",
+        )
+        .push_values(&subscription_ids, |mut q, subscription_id| {
+            q.push_bind(Uuid::now_v7())
+                .push_bind(subscription_id)
+                .push_bind(event_type)
+                .push_bind(&payload)
+                .push("'pending'")
+                .push_bind(0_i32)
+                .push_bind(now)
+                .push_bind(now);
+        })
+        .build()
+        .execute(&mut **tx)
+        .await?;
+
+        debug!(id = %event.id(), event_type, ?subscription_ids, "enqueued webhook deliveries");
+        Ok(())
+    }
+}
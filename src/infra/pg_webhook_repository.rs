@@ -0,0 +1,189 @@
+use crate::domain::{self, WebhookEndpoint, WebhookRepository, WebhookSubscription};
+use sqlx::{prelude::FromRow, PgPool};
+use time::OffsetDateTime;
+use tracing::instrument;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct PgWebhookRepository {
+    pool: PgPool,
+}
+
+impl PgWebhookRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+impl WebhookRepository for PgWebhookRepository {
+    type Error = sqlx::Error;
+
+    #[instrument(skip(self))]
+    async fn webhook_subscriptions(&self) -> Result<Vec<WebhookSubscription>, Self::Error> {
+        sqlx::query_as::<_, (Uuid, String, Vec<String>, OffsetDateTime)>(
+            "SELECT id, url, event_types, created_at FROM webhook_subscription ORDER BY id",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map(|subscriptions| {
+            subscriptions
+                .into_iter()
+                .map(|(id, url, event_types, created_at)| WebhookSubscription {
+                    id,
+                    url,
+                    event_types,
+                    created_at,
+                })
+                .collect()
+        })
+    }
+
+    #[instrument(skip(self, secret))]
+    async fn insert_webhook_subscription(
+        &self,
+        subscription: &WebhookSubscription,
+        secret: &str,
+    ) -> Result<(), Self::Error> {
+        sqlx::query(
+            "INSERT INTO webhook_subscription (id, url, secret, event_types, created_at)
+             VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(subscription.id)
+        .bind(&subscription.url)
+        .bind(secret)
+        .bind(&subscription.event_types)
+        .bind(subscription.created_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn delete_webhook_subscription(&self, id: Uuid) -> Result<bool, Self::Error> {
+        let result = sqlx::query("DELETE FROM webhook_subscription WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[instrument(skip(self))]
+    async fn due_webhook_deliveries(
+        &self,
+        at: OffsetDateTime,
+        limit: u32,
+    ) -> Result<Vec<(domain::WebhookDelivery, WebhookEndpoint)>, Self::Error> {
+        let deliveries = sqlx::query_as::<_, DueWebhookDelivery>(
+            "SELECT d.*, s.url, s.secret
+             FROM webhook_delivery d
+             JOIN webhook_subscription s ON s.id = d.subscription_id
+             WHERE d.status = 'pending' AND d.next_attempt_at <= $1
+             ORDER BY d.next_attempt_at, d.id
+             LIMIT $2",
+        )
+        .bind(at)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|delivery| {
+            let endpoint = WebhookEndpoint {
+                url: delivery.url,
+                secret: delivery.secret,
+            };
+            (delivery.delivery.into(), endpoint)
+        })
+        .collect();
+        Ok(deliveries)
+    }
+
+    #[instrument(skip(self))]
+    async fn record_webhook_delivered(
+        &self,
+        id: Uuid,
+        at: OffsetDateTime,
+    ) -> Result<(), Self::Error> {
+        sqlx::query(
+            "UPDATE webhook_delivery
+             SET status = 'delivered', attempts = attempts + 1, last_error = NULL, delivered_at = $2
+             WHERE id = $1",
+        )
+        .bind(id)
+        .bind(at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn record_webhook_failure(
+        &self,
+        id: Uuid,
+        error: &str,
+        retry_at: Option<OffsetDateTime>,
+    ) -> Result<(), Self::Error> {
+        sqlx::query(
+            "UPDATE webhook_delivery
+             SET status = CASE WHEN $3::timestamptz IS NULL
+                            THEN 'dead'::webhook_delivery_status
+                            ELSE 'pending'::webhook_delivery_status
+                          END,
+                 attempts = attempts + 1,
+                 last_error = $2,
+                 next_attempt_at = COALESCE($3, next_attempt_at)
+             WHERE id = $1",
+        )
+        .bind(id)
+        .bind(error)
+        .bind(retry_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn dead_webhook_deliveries(&self) -> Result<Vec<domain::WebhookDelivery>, Self::Error> {
+        let deliveries = sqlx::query_as::<_, WebhookDelivery>(
+            "SELECT * FROM webhook_delivery WHERE status = 'dead' ORDER BY id DESC",
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect();
+        Ok(deliveries)
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct WebhookDelivery {
+    id: Uuid,
+    subscription_id: Uuid,
+    event_type: String,
+    payload: String,
+    attempts: i32,
+    last_error: Option<String>,
+    created_at: OffsetDateTime,
+}
+
+impl From<WebhookDelivery> for domain::WebhookDelivery {
+    fn from(delivery: WebhookDelivery) -> Self {
+        Self {
+            id: delivery.id,
+            subscription_id: delivery.subscription_id,
+            event_type: delivery.event_type,
+            payload: delivery.payload,
+            attempts: delivery.attempts as u32,
+            last_error: delivery.last_error,
+            created_at: delivery.created_at,
+        }
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct DueWebhookDelivery {
+    #[sqlx(flatten)]
+    delivery: WebhookDelivery,
+    url: String,
+    secret: String,
+}
@@ -1,11 +1,20 @@
 mod api;
 mod domain;
 mod infra;
+mod scheduler;
 mod util;
 
 use crate::{
-    domain::AccountEntity,
-    infra::{PgAccountEventHandler, PgAccountRepository},
+    domain::{AccountEntity, CustomerEntity, ProductCatalog, StandingOrderEntity},
+    infra::{
+        PgAccountEventHandler, PgAccountRepository, PgApiKeyRepository, PgCustomerEventHandler,
+        PgCustomerRepository, PgLedgerEventHandler, PgStandingOrderEventHandler,
+        PgStandingOrderRepository, PgWebhookEventHandler, PgWebhookRepository,
+    },
+    scheduler::{
+        EndOfDayJob, StandingOrderJob, StandingOrderJobConfig, SystemClock, WebhookDispatcher,
+        WebhookDispatcherConfig,
+    },
     util::PgConfig,
 };
 use anyhow::{Context, Result};
@@ -35,8 +44,9 @@ async fn main() -> Result<()> {
         .context("load configuration")
         .inspect_err(log_error)?;
 
-    // Initialize tracing.
+    // Initialize tracing and metrics.
     init_tracing(config.tracing.clone()).inspect_err(log_error)?;
+    init_metrics(config.tracing.clone()).inspect_err(log_error)?;
 
     // Replace the default panic hook with one that uses structured logging at ERROR level.
     panic::set_hook(Box::new(|panic| error!(%panic, "process panicked")));
@@ -58,6 +68,9 @@ struct Config {
     tracing: TracingConfig,
     pg_config: PgConfig,
     event_log: NatsEventLogConfig,
+    products: ProductCatalog,
+    standing_order_job: StandingOrderJobConfig,
+    webhook_dispatcher: WebhookDispatcherConfig,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -108,6 +121,26 @@ where
     Ok(tracing_opentelemetry::layer().with_tracer(tracer))
 }
 
+/// Initialize metrics: set a global meter provider exporting metrics via OTLP.
+fn init_metrics(config: TracingConfig) -> Result<()> {
+    let exporter = opentelemetry_otlp::new_exporter()
+        .tonic()
+        .with_endpoint(config.otlp_exporter_endpoint);
+
+    let meter_provider = opentelemetry_otlp::new_pipeline()
+        .metrics(runtime::Tokio)
+        .with_exporter(exporter)
+        .with_resource(Resource::new(vec![KeyValue::new(
+            "service.name",
+            config.service_name,
+        )]))
+        .build()
+        .context("build meter provider")?;
+    global::set_meter_provider(meter_provider);
+
+    Ok(())
+}
+
 fn log_error(error: &impl Display) {
     let now = OffsetDateTime::now_utc().format(&Rfc3339).unwrap();
     let error = serde_json::to_string(&json!({
@@ -132,8 +165,12 @@ async fn run(config: Config) -> Result<()> {
     // Run DB migrations.
     sqlx::migrate!().run(&pool).await?;
 
-    // Create account repository.
+    // Create repositories.
     let account_repository = PgAccountRepository::new(pool.clone());
+    let customer_repository = PgCustomerRepository::new(pool.clone());
+    let standing_order_repository = PgStandingOrderRepository::new(pool.clone());
+    let api_key_repository = PgApiKeyRepository::new(pool.clone());
+    let webhook_repository = PgWebhookRepository::new(pool.clone());
 
     // Create event log.
     let event_log = NatsEventLog::<Uuid>::new(config.event_log)
@@ -147,7 +184,7 @@ async fn run(config: Config) -> Result<()> {
         event_log.clone(),
         PgAccountEventHandler,
         ErrorStrategy::Stop,
-        pool,
+        pool.clone(),
     )
     .await
     .context("create account projection")?;
@@ -156,5 +193,123 @@ async fn run(config: Config) -> Result<()> {
         .await
         .context("run account projection")?;
 
-    api::serve(config.api, account_repository, event_log).await
+    // Run ledger projection.
+    let ledger_projection = Projection::new(
+        AccountEntity::TYPE_NAME,
+        "ledger".to_string(),
+        event_log.clone(),
+        PgLedgerEventHandler,
+        ErrorStrategy::Stop,
+        pool.clone(),
+    )
+    .await
+    .context("create ledger projection")?;
+    ledger_projection
+        .run()
+        .await
+        .context("run ledger projection")?;
+
+    // Run webhook projection, i.e. enqueue account events into the outbox.
+    let webhook_projection = Projection::new(
+        AccountEntity::TYPE_NAME,
+        "webhook".to_string(),
+        event_log.clone(),
+        PgWebhookEventHandler,
+        ErrorStrategy::Stop,
+        pool.clone(),
+    )
+    .await
+    .context("create webhook projection")?;
+    webhook_projection
+        .run()
+        .await
+        .context("run webhook projection")?;
+
+    // Run customer projection.
+    let customer_projection = Projection::new(
+        CustomerEntity::TYPE_NAME,
+        "customer".to_string(),
+        event_log.clone(),
+        PgCustomerEventHandler,
+        ErrorStrategy::Stop,
+        pool.clone(),
+    )
+    .await
+    .context("create customer projection")?;
+    customer_projection
+        .run()
+        .await
+        .context("run customer projection")?;
+
+    // Run standing order projection.
+    let standing_order_projection = Projection::new(
+        StandingOrderEntity::TYPE_NAME,
+        "standing-order".to_string(),
+        event_log.clone(),
+        PgStandingOrderEventHandler,
+        ErrorStrategy::Stop,
+        pool,
+    )
+    .await
+    .context("create standing order projection")?;
+    standing_order_projection
+        .run()
+        .await
+        .context("run standing order projection")?;
+
+    // Run end-of-day job.
+    let end_of_day_job =
+        EndOfDayJob::new(account_repository.clone(), event_log.clone(), SystemClock);
+    tokio::spawn(async move {
+        if let Err(error) = end_of_day_job.run().await {
+            error!(
+                error = format!("{error:#}"),
+                "end-of-day job exited with ERROR"
+            );
+        }
+    });
+
+    // Run standing order job.
+    let standing_order_job = StandingOrderJob::new(
+        config.standing_order_job,
+        standing_order_repository.clone(),
+        event_log.clone(),
+        SystemClock,
+    );
+    tokio::spawn(async move {
+        if let Err(error) = standing_order_job.run().await {
+            error!(
+                error = format!("{error:#}"),
+                "standing order job exited with ERROR"
+            );
+        }
+    });
+
+    // Run webhook dispatcher.
+    let webhook_dispatcher = WebhookDispatcher::new(
+        config.webhook_dispatcher,
+        webhook_repository.clone(),
+        SystemClock,
+    )
+    .context("create webhook dispatcher")?;
+    tokio::spawn(async move {
+        if let Err(error) = webhook_dispatcher.run().await {
+            error!(
+                error = format!("{error:#}"),
+                "webhook dispatcher exited with ERROR"
+            );
+        }
+    });
+
+    api::serve(
+        config.api,
+        config.products,
+        account_repository,
+        customer_repository,
+        standing_order_repository,
+        api_key_repository,
+        webhook_repository,
+        event_log,
+    )
+    .await
 }
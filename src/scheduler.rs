@@ -0,0 +1,61 @@
+mod clock;
+mod end_of_day_job;
+mod reconciler;
+mod standing_order_job;
+mod webhook_dispatcher;
+
+pub use clock::*;
+pub use end_of_day_job::*;
+pub use reconciler::*;
+pub use standing_order_job::*;
+pub use webhook_dispatcher::*;
+
+use crate::domain::{AccountEntity, StandingOrderEntity};
+use anyhow::{Context, Result};
+use eventsourced::{
+    binarize::serde_json::SerdeJsonBinarize, event_log::EventLog,
+    snapshot_store::noop::NoopSnapshotStore, EntityRef, EventSourcedExt,
+};
+use std::num::NonZeroUsize;
+use uuid::Uuid;
+
+// In the real-world, entities would be cached.
+async fn spawn_account_entity<E>(id: Uuid, event_log: E) -> Result<EntityRef<AccountEntity>>
+where
+    E: EventLog<Id = Uuid>,
+{
+    AccountEntity::default()
+        .entity()
+        .spawn(
+            id,
+            None,
+            NonZeroUsize::MIN,
+            event_log,
+            NoopSnapshotStore::default(),
+            SerdeJsonBinarize,
+        )
+        .await
+        .context("spawn AccountEntity")
+}
+
+// In the real-world, entities would be cached.
+async fn spawn_standing_order_entity<E>(
+    id: Uuid,
+    event_log: E,
+) -> Result<EntityRef<StandingOrderEntity>>
+where
+    E: EventLog<Id = Uuid>,
+{
+    StandingOrderEntity::default()
+        .entity()
+        .spawn(
+            id,
+            None,
+            NonZeroUsize::MIN,
+            event_log,
+            NoopSnapshotStore::default(),
+            SerdeJsonBinarize,
+        )
+        .await
+        .context("spawn StandingOrderEntity")
+}
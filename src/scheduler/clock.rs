@@ -0,0 +1,29 @@
+use time::OffsetDateTime;
+
+/// Source of the current time for scheduled jobs, allowing tests to let days pass instantly.
+#[trait_variant::make(Send)]
+pub trait Clock
+where
+    Self: Clone + Send + Sync + 'static,
+{
+    fn now(&self) -> OffsetDateTime;
+
+    /// Wait until the given time has been reached.
+    async fn sleep_until(&self, at: OffsetDateTime);
+}
+
+/// The system clock in UTC.
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> OffsetDateTime {
+        OffsetDateTime::now_utc()
+    }
+
+    async fn sleep_until(&self, at: OffsetDateTime) {
+        // A negative duration means the time has already been reached.
+        let duration = (at - self.now()).try_into().unwrap_or_default();
+        tokio::time::sleep(duration).await;
+    }
+}
@@ -0,0 +1,322 @@
+use crate::{
+    domain::{
+        is_month_end, AccountEntity, AccountRepository, Accrual, AccrueInterest,
+        AccrueInterestError, ChargeMaintenanceFee, ChargeMaintenanceFeeError, PostInterest,
+    },
+    scheduler::{spawn_account_entity, Clock},
+};
+use anyhow::{Context, Result};
+use error_ext::StdErrorExt;
+use eventsourced::{event_log::EventLog, EntityRef};
+use futures::TryStreamExt;
+use time::{Date, Duration, UtcOffset};
+use tracing::{debug, error, info, instrument};
+use uuid::Uuid;
+
+/// Accrues interest on the end-of-day balances of all accounts right after midnight (UTC); after
+/// the last day of each month posts the accrued interest and charges the maintenance fees.
+///
+/// Each account tracks the last day interest has been accrued for, hence days missed, e.g. while
+/// the job was not running, are caught up day by day.
+#[derive(Debug, Clone)]
+pub struct EndOfDayJob<R, E, C> {
+    account_repository: R,
+    event_log: E,
+    clock: C,
+}
+
+impl<R, E, C> EndOfDayJob<R, E, C>
+where
+    R: AccountRepository,
+    E: EventLog<Id = Uuid> + Sync,
+    C: Clock,
+{
+    pub fn new(account_repository: R, event_log: E, clock: C) -> Self {
+        Self {
+            account_repository,
+            event_log,
+            clock,
+        }
+    }
+
+    /// Run forever, processing all days up to the one before today right away and then after
+    /// each midnight. Failures are logged and retried after the next midnight.
+    pub async fn run(self) -> Result<()> {
+        loop {
+            let today = self.clock.now().to_offset(UtcOffset::UTC).date();
+            let yesterday = today - Duration::days(1);
+            if let Err(error) = self.process(yesterday).await {
+                error!(%yesterday, error = format!("{error:#}"), "cannot process end of day");
+            }
+
+            let end_of_today = (today + Duration::days(1)).midnight().assume_utc();
+            self.clock.sleep_until(end_of_today).await;
+        }
+    }
+
+    /// For all accounts accrue interest for each day up to the given one which has not yet been
+    /// accrued for and, for each last day of a month, post it and charge the maintenance fee.
+    /// Failures for single accounts are logged and do not stop processing the others.
+    #[instrument(skip(self))]
+    pub async fn process(&self, through: Date) -> Result<()> {
+        let accounts = self
+            .account_repository
+            .accounts()
+            .await
+            .context("get accounts")?
+            .try_collect::<Vec<_>>()
+            .await
+            .context("get accounts")?;
+
+        for account in accounts {
+            let entity = match spawn_account_entity(account.id, self.event_log.clone()).await {
+                Ok(entity) => entity,
+                Err(error) => {
+                    error!(
+                        id = %account.id,
+                        error = format!("{error:#}"),
+                        "cannot spawn account entity"
+                    );
+                    continue;
+                }
+            };
+
+            loop {
+                let date = match entity.handle_command(AccrueInterest::from(through)).await {
+                    Ok(Ok(Accrual { date, amount })) => {
+                        debug!(id = %account.id, %date, amount, "accrued interest");
+                        date
+                    }
+
+                    Ok(Err(error @ AccrueInterestError::AlreadyAccrued { .. })) => {
+                        debug!(error = error.as_chain(), "account processed");
+                        break;
+                    }
+
+                    Ok(Err(error)) => {
+                        error!(error = error.as_chain(), "cannot accrue interest");
+                        break;
+                    }
+
+                    Err(error) => {
+                        error!(
+                            error = error.as_chain(),
+                            "cannot handle AccrueInterest command"
+                        );
+                        break;
+                    }
+                };
+
+                if is_month_end(date) {
+                    close_month(&entity, date).await;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Post the accrued interest and charge the maintenance fee after the given last day of a month.
+async fn close_month(entity: &EntityRef<AccountEntity>, date: Date) {
+    let month_end_at = (date + Duration::days(1)).midnight().assume_utc();
+
+    match entity
+        .handle_command(PostInterest::from(month_end_at))
+        .await
+    {
+        Ok(Ok(account)) => info!(?account, "posted interest"),
+        Ok(Err(error)) => error!(error = error.as_chain(), "cannot post interest"),
+        Err(error) => {
+            error!(
+                error = error.as_chain(),
+                "cannot handle PostInterest command"
+            )
+        }
+    }
+
+    match entity
+        .handle_command(ChargeMaintenanceFee::from(month_end_at))
+        .await
+    {
+        Ok(Ok(account)) => info!(?account, "charged maintenance fee"),
+        Ok(Err(error @ ChargeMaintenanceFeeError::AlreadyCharged(_))) => {
+            debug!(error = error.as_chain(), "skipping account")
+        }
+        Ok(Err(error)) => {
+            error!(error = error.as_chain(), "cannot charge maintenance fee")
+        }
+        Err(error) => {
+            error!(
+                error = error.as_chain(),
+                "cannot handle ChargeMaintenanceFee command"
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        domain::{
+            Account, AccountFilter, AccountRepository, AccrueInterest, CreateAccount, DayCount,
+            Deposit, FeeSchedule, GetHolds, Operation, Product, Statement, Transaction,
+            TransactionFilter, TrialBalance,
+        },
+        scheduler::{spawn_account_entity, EndOfDayJob, SystemClock},
+    };
+    use eventsourced::event_log::test::TestEventLog;
+    use futures::{stream, Stream};
+    use std::convert::Infallible;
+    use time::{
+        macros::{date, datetime},
+        Date,
+    };
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_catch_up() {
+        let event_log = TestEventLog::<Uuid>::default();
+
+        // With a balance of 100_000 one unit of interest per day is accrued for each 1_000.
+        let id = Uuid::now_v7();
+        let account = spawn_account_entity(id, event_log.clone()).await.unwrap();
+        account
+            .handle_command(CreateAccount::new(vec![Uuid::now_v7()], savings()))
+            .await
+            .unwrap()
+            .unwrap();
+        account
+            .handle_command(Deposit::new(100_000, datetime!(2024-07-27 12:00 UTC)))
+            .await
+            .unwrap()
+            .unwrap();
+        let accrual = account
+            .handle_command(AccrueInterest::from(date!(2024 - 07 - 27)))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(accrual.date, date!(2024 - 07 - 27));
+        assert_eq!(accrual.amount, 100_000_000);
+
+        // The job has not been running for the following days.
+        account
+            .handle_command(Deposit::new(100_000, datetime!(2024-07-29 12:00 UTC)))
+            .await
+            .unwrap()
+            .unwrap();
+        account
+            .handle_command(Deposit::new(100_000, datetime!(2024-07-30 12:00 UTC)))
+            .await
+            .unwrap()
+            .unwrap();
+
+        let job = EndOfDayJob::new(
+            TestAccountRepository(vec![Account {
+                id,
+                product: "savings".to_string(),
+                balance: 300_000,
+            }]),
+            event_log.clone(),
+            SystemClock,
+        );
+        job.process(date!(2024 - 07 - 31)).await.unwrap();
+
+        // Interest has been accrued on the end-of-day balance of each day and posted after the
+        // end of the month: 100 + 100 + 200 + 300 + 300.
+        let account = spawn_account_entity(id, event_log.clone()).await.unwrap();
+        let holds = account
+            .handle_command(GetHolds::from(datetime!(2024-08-01 12:00 UTC)))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(holds.balance, 301_000);
+
+        // Processing again does not accrue interest twice.
+        job.process(date!(2024 - 07 - 31)).await.unwrap();
+        let account = spawn_account_entity(id, event_log).await.unwrap();
+        let holds = account
+            .handle_command(GetHolds::from(datetime!(2024-08-01 12:00 UTC)))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(holds.balance, 301_000);
+    }
+
+    #[derive(Debug, Clone)]
+    struct TestAccountRepository(Vec<Account>);
+
+    impl AccountRepository for TestAccountRepository {
+        type Error = Infallible;
+
+        async fn accounts(
+            &self,
+        ) -> Result<impl Stream<Item = Result<Account, Self::Error>> + Send, Self::Error> {
+            Ok(stream::iter(self.0.clone().into_iter().map(Ok)))
+        }
+
+        async fn account(&self, id: Uuid) -> Result<Option<Account>, Self::Error> {
+            Ok(self.0.iter().find(|account| account.id == id).cloned())
+        }
+
+        async fn accounts_page(
+            &self,
+            _filter: &AccountFilter,
+            _after: Option<Uuid>,
+            _limit: u32,
+        ) -> Result<Vec<Account>, Self::Error> {
+            Ok(self.0.clone())
+        }
+
+        async fn transactions(
+            &self,
+            _id: Uuid,
+        ) -> Result<impl Stream<Item = Result<Transaction, Self::Error>> + Send, Self::Error>
+        {
+            Ok(stream::empty())
+        }
+
+        async fn transactions_page(
+            &self,
+            _id: Uuid,
+            _filter: &TransactionFilter,
+            _after: Option<u64>,
+            _limit: u32,
+        ) -> Result<Vec<Transaction>, Self::Error> {
+            Ok(vec![])
+        }
+
+        async fn statement(
+            &self,
+            _id: Uuid,
+            _from: Date,
+            _to: Date,
+        ) -> Result<Option<(Account, Statement)>, Self::Error> {
+            Ok(None)
+        }
+
+        async fn repair_account(
+            &self,
+            _account: Account,
+            _holders: Vec<Uuid>,
+        ) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        async fn trial_balance(&self) -> Result<TrialBalance, Self::Error> {
+            Ok(TrialBalance::new(vec![]))
+        }
+    }
+
+    fn savings() -> Product {
+        Product {
+            name: "savings".to_string(),
+            allowed_operations: vec![Operation::Deposit, Operation::Withdraw],
+            currency: "EUR".to_string(),
+            overdraft: 0,
+            interest_rate_bps: 3650,
+            day_count: DayCount::Act365,
+            fees: FeeSchedule::default(),
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use crate::domain::{
+    replay_account, AccountEntity, AccountEvent, AccountRepository, AsOf, Drift, Reconciliation,
+};
+use anyhow::{Context, Result};
+use eventsourced::{event_log::EventLog, EventSourced};
+use futures::TryStreamExt;
+use std::{collections::BTreeSet, num::NonZeroU64};
+use tracing::{info, instrument, warn};
+use uuid::Uuid;
+
+/// Recomputes the state of accounts by replaying their events and compares it with the account
+/// projection, optionally repairing missing rows and wrong balances.
+///
+/// As the projection is eventually consistent, drifts for recently changed accounts may just be
+/// lagging behind the event log; repairing those is harmless, because the projection overwrites
+/// balances with the ones of the events.
+#[derive(Debug, Clone)]
+pub struct Reconciler<R, E> {
+    account_repository: R,
+    event_log: E,
+}
+
+impl<R, E> Reconciler<R, E>
+where
+    R: AccountRepository,
+    E: EventLog<Id = Uuid> + Sync,
+{
+    pub fn new(account_repository: R, event_log: E) -> Self {
+        Self {
+            account_repository,
+            event_log,
+        }
+    }
+
+    /// Reconcile all accounts of the projection and the given ones. As the event log cannot be
+    /// queried for all account IDs, rows missing in the projection are only detected for the
+    /// given accounts.
+    #[instrument(skip(self))]
+    pub async fn reconcile(&self, account_ids: Vec<Uuid>, repair: bool) -> Result<Reconciliation> {
+        let projected = self
+            .account_repository
+            .accounts()
+            .await
+            .context("get accounts")?
+            .try_collect::<Vec<_>>()
+            .await
+            .context("get accounts")?;
+
+        let account_ids = projected
+            .iter()
+            .map(|account| account.id)
+            .chain(account_ids)
+            .collect::<BTreeSet<_>>();
+
+        let mut drifts = vec![];
+        let mut repaired = 0;
+
+        for &account_id in &account_ids {
+            let projected = projected.iter().find(|account| account.id == account_id);
+            let replayed = match self.replay(account_id).await? {
+                AccountEntity::Nonexistent => None,
+                AccountEntity::Existing(state) => Some((state.account(account_id), state.holders)),
+            };
+
+            let Some(drift) =
+                Drift::detect(account_id, replayed.as_ref().map(|(a, _)| a), projected)
+            else {
+                continue;
+            };
+            warn!(?drift, "account projection drifted from event log");
+
+            if repair && drift.is_repairable() {
+                if let Some((account, holders)) = replayed {
+                    self.account_repository
+                        .repair_account(account, holders)
+                        .await
+                        .context("repair account")?;
+                    info!(%account_id, "repaired account");
+                    repaired += 1;
+                }
+            }
+
+            drifts.push(drift);
+        }
+
+        Ok(Reconciliation {
+            checked: account_ids.len(),
+            drifts,
+            repaired,
+        })
+    }
+
+    async fn replay(&self, id: Uuid) -> Result<AccountEntity> {
+        let last_seq_no = self
+            .event_log
+            .last_seq_no(AccountEntity::TYPE_NAME, &id)
+            .await
+            .context("get last sequence number")?;
+        let Some(last_seq_no) = last_seq_no else {
+            return Ok(AccountEntity::Nonexistent);
+        };
+
+        let events = self
+            .event_log
+            .events_by_id::<AccountEvent, _, _>(
+                AccountEntity::TYPE_NAME,
+                &id,
+                NonZeroU64::MIN,
+                |bytes| serde_json::from_slice(&bytes),
+            )
+            .await
+            .context("get account events")?;
+
+        replay_account(events, last_seq_no, AsOf::SeqNo(u64::MAX))
+            .await
+            .context("replay account events")
+    }
+}
@@ -0,0 +1,357 @@
+use crate::{
+    domain::{
+        ClaimExecution, ClaimExecutionError, Deposit, RecordExecution, RecordFailure, Reverse,
+        StandingOrder, StandingOrderRepository, Withdraw,
+    },
+    scheduler::{spawn_account_entity, spawn_standing_order_entity, Clock},
+};
+use anyhow::{Context, Result};
+use error_ext::StdErrorExt;
+use eventsourced::event_log::EventLog;
+use futures::TryStreamExt;
+use serde::Deserialize;
+use time::{Duration, OffsetDateTime, UtcOffset};
+use tracing::{error, info, instrument, warn};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct StandingOrderJobConfig {
+    /// Seconds between two runs, i.e. the delay for retrying failed transfers.
+    interval_secs: u64,
+
+    /// Attempts to execute a due transfer before it is given up.
+    max_attempts: u32,
+}
+
+/// Periodically executes the transfers of all due standing orders by withdrawing from the account
+/// and depositing to the payee account. Failed transfers, e.g. because of an insufficient balance,
+/// are recorded with the standing order and retried with the next run until given up.
+///
+/// Each execution is claimed with the standing order before the transfer, hence executed at most
+/// once: if the job is interrupted before the execution or failure has been recorded, the standing
+/// order stays claimed and is not executed again, which is logged as an error with every run.
+#[derive(Debug, Clone)]
+pub struct StandingOrderJob<S, E, C> {
+    config: StandingOrderJobConfig,
+    standing_order_repository: S,
+    event_log: E,
+    clock: C,
+}
+
+impl<S, E, C> StandingOrderJob<S, E, C>
+where
+    S: StandingOrderRepository,
+    E: EventLog<Id = Uuid> + Sync,
+    C: Clock,
+{
+    pub fn new(
+        config: StandingOrderJobConfig,
+        standing_order_repository: S,
+        event_log: E,
+        clock: C,
+    ) -> Self {
+        Self {
+            config,
+            standing_order_repository,
+            event_log,
+            clock,
+        }
+    }
+
+    /// Run forever; failures are logged and retried with the next run.
+    pub async fn run(self) -> Result<()> {
+        let interval = Duration::seconds(self.config.interval_secs as i64);
+
+        loop {
+            let now = self.clock.now();
+            if let Err(error) = self.process(now).await {
+                error!(
+                    error = format!("{error:#}"),
+                    "cannot process standing orders"
+                );
+            }
+            self.clock.sleep_until(now + interval).await;
+        }
+    }
+
+    /// Execute the transfers of all standing orders due at or before the date of the given time.
+    /// Failures for single standing orders are logged and do not stop executing the others.
+    #[instrument(skip(self))]
+    pub async fn process(&self, at: OffsetDateTime) -> Result<()> {
+        let today = at.to_offset(UtcOffset::UTC).date();
+
+        let standing_orders = self
+            .standing_order_repository
+            .due_standing_orders(today)
+            .await
+            .context("get due standing orders")?
+            .try_collect::<Vec<_>>()
+            .await
+            .context("get due standing orders")?;
+
+        for standing_order in standing_orders {
+            let id = standing_order.id;
+            if let Err(error) = self.execute(standing_order, at).await {
+                error!(
+                    %id,
+                    error = format!("{error:#}"),
+                    "cannot execute standing order"
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn execute(&self, standing_order: StandingOrder, at: OffsetDateTime) -> Result<()> {
+        let id = standing_order.id;
+        let due = standing_order.next_due;
+
+        let entity = spawn_standing_order_entity(id, self.event_log.clone()).await?;
+        let reply = entity
+            .handle_command(ClaimExecution::new(due, at))
+            .await
+            .context("handle ClaimExecution command")?;
+        match reply {
+            Ok(_) => {}
+
+            // The projection might not yet reflect the last execution.
+            Err(error @ ClaimExecutionError::NotDue { .. }) => {
+                info!(error = error.as_chain(), "skipping standing order");
+                return Ok(());
+            }
+
+            Err(error) => {
+                error!(error = error.as_chain(), "cannot claim execution");
+                return Ok(());
+            }
+        }
+
+        match self.transfer(&standing_order, at).await? {
+            Ok(()) => {
+                let reply = entity
+                    .handle_command(RecordExecution::new(due, at))
+                    .await
+                    .context("handle RecordExecution command")?;
+                match reply {
+                    Ok(standing_order) => info!(?standing_order, "executed standing order"),
+                    Err(error) => error!(error = error.as_chain(), "cannot record execution"),
+                }
+            }
+
+            Err(reason) => {
+                let failure = RecordFailure::new(due, reason, self.config.max_attempts, at);
+                let reply = entity
+                    .handle_command(failure)
+                    .await
+                    .context("handle RecordFailure command")?;
+                match reply {
+                    Ok(standing_order) => warn!(?standing_order, "standing order execution failed"),
+                    Err(error) => error!(error = error.as_chain(), "cannot record failure"),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Withdraw the amount from the account and deposit it to the payee account. If the deposit
+    /// fails, including spawning the payee account entity, the withdrawal is reversed. Business
+    /// errors are returned as the reason of the failure.
+    async fn transfer(
+        &self,
+        standing_order: &StandingOrder,
+        at: OffsetDateTime,
+    ) -> Result<Result<(), String>> {
+        let amount = standing_order.amount;
+
+        let account =
+            spawn_account_entity(standing_order.account_id, self.event_log.clone()).await?;
+        let withdrawal = match account
+            .handle_command(Withdraw::new(amount, at))
+            .await
+            .context("handle Withdraw command")?
+        {
+            Ok(reply) => reply,
+            Err(error) => return Ok(Err(error.to_string())),
+        };
+
+        let reason =
+            match spawn_account_entity(standing_order.payee_id, self.event_log.clone()).await {
+                Ok(payee) => match payee.handle_command(Deposit::new(amount, at)).await {
+                    Ok(Ok(_)) => return Ok(Ok(())),
+                    Ok(Err(error)) => error.to_string(),
+                    Err(error) => {
+                        error!(error = error.as_chain(), "cannot handle Deposit command");
+                        "cannot deposit to payee account".to_string()
+                    }
+                },
+
+                Err(error) => {
+                    error!(error = format!("{error:#}"), "cannot spawn payee account");
+                    "cannot deposit to payee account".to_string()
+                }
+            };
+
+        match account
+            .handle_command(Reverse::new(withdrawal.seq_no, at))
+            .await
+        {
+            Ok(Ok(_)) => {}
+            Ok(Err(error)) => error!(
+                error = error.as_chain(),
+                id = %standing_order.id,
+                "cannot reverse withdrawal"
+            ),
+            Err(error) => error!(
+                error = error.as_chain(),
+                id = %standing_order.id,
+                "cannot handle Reverse command for reversing withdrawal"
+            ),
+        }
+
+        Ok(Err(reason))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        domain::{
+            ClaimExecution, CreateAccount, CreateStandingOrder, DayCount, Deposit, FeeSchedule,
+            GetHolds, Operation, Product, StandingOrder, StandingOrderRepository,
+        },
+        scheduler::{
+            spawn_account_entity, spawn_standing_order_entity, StandingOrderJob,
+            StandingOrderJobConfig, SystemClock,
+        },
+    };
+    use eventsourced::event_log::test::TestEventLog;
+    use futures::{stream, Stream};
+    use std::convert::Infallible;
+    use time::{macros::datetime, Date};
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_unknown_payee() {
+        let at = datetime!(2024-07-01 12:00 UTC);
+        let event_log = TestEventLog::<Uuid>::default();
+
+        let account_id = Uuid::now_v7();
+        let account = spawn_account_entity(account_id, event_log.clone())
+            .await
+            .unwrap();
+        account
+            .handle_command(CreateAccount::new(vec![Uuid::now_v7()], checking()))
+            .await
+            .unwrap()
+            .unwrap();
+        account
+            .handle_command(Deposit::new(100, at))
+            .await
+            .unwrap()
+            .unwrap();
+
+        let id = Uuid::now_v7();
+        let entity = spawn_standing_order_entity(id, event_log.clone())
+            .await
+            .unwrap();
+        let standing_order = entity
+            .handle_command(CreateStandingOrder::new(
+                account_id,
+                Uuid::now_v7(),
+                30,
+                1,
+                at.date(),
+            ))
+            .await
+            .unwrap()
+            .unwrap();
+        let due = standing_order.next_due;
+
+        let config = StandingOrderJobConfig {
+            interval_secs: 60,
+            max_attempts: 3,
+        };
+        let job = StandingOrderJob::new(
+            config,
+            TestStandingOrderRepository(vec![standing_order]),
+            event_log.clone(),
+            SystemClock,
+        );
+        job.process(at).await.unwrap();
+
+        // The withdrawal has been reversed.
+        let account = spawn_account_entity(account_id, event_log.clone())
+            .await
+            .unwrap();
+        let holds = account
+            .handle_command(GetHolds::from(at))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(holds.balance, 100);
+
+        // The failure has been recorded, hence the execution can be claimed again.
+        let entity = spawn_standing_order_entity(id, event_log).await.unwrap();
+        let standing_order = entity
+            .handle_command(ClaimExecution::new(due, at))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(standing_order.failed_attempts, 1);
+        assert!(standing_order
+            .last_failure
+            .is_some_and(|failure| failure.due == due));
+    }
+
+    #[derive(Debug, Clone)]
+    struct TestStandingOrderRepository(Vec<StandingOrder>);
+
+    impl StandingOrderRepository for TestStandingOrderRepository {
+        type Error = Infallible;
+
+        async fn standing_orders(
+            &self,
+            account_id: Uuid,
+        ) -> Result<impl Stream<Item = Result<StandingOrder, Self::Error>> + Send, Self::Error>
+        {
+            let standing_orders = self
+                .0
+                .iter()
+                .filter(|standing_order| standing_order.account_id == account_id)
+                .cloned()
+                .map(Ok)
+                .collect::<Vec<_>>();
+            Ok(stream::iter(standing_orders))
+        }
+
+        async fn due_standing_orders(
+            &self,
+            date: Date,
+        ) -> Result<impl Stream<Item = Result<StandingOrder, Self::Error>> + Send, Self::Error>
+        {
+            let standing_orders = self
+                .0
+                .iter()
+                .filter(|standing_order| standing_order.next_due <= date)
+                .cloned()
+                .map(Ok)
+                .collect::<Vec<_>>();
+            Ok(stream::iter(standing_orders))
+        }
+    }
+
+    fn checking() -> Product {
+        Product {
+            name: "checking".to_string(),
+            allowed_operations: vec![Operation::Deposit, Operation::Withdraw],
+            currency: "EUR".to_string(),
+            overdraft: 0,
+            interest_rate_bps: 0,
+            day_count: DayCount::default(),
+            fees: FeeSchedule::default(),
+        }
+    }
+}
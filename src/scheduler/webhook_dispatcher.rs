@@ -0,0 +1,205 @@
+use crate::{
+    domain::{
+        sign_webhook_payload, webhook_backoff, WebhookDelivery, WebhookEndpoint, WebhookRepository,
+    },
+    scheduler::Clock,
+};
+use anyhow::{Context, Result};
+use error_ext::StdErrorExt;
+use reqwest::{header::CONTENT_TYPE, Client};
+use serde::Deserialize;
+use std::time::Duration as StdDuration;
+use time::{Duration, OffsetDateTime};
+use tracing::{debug, error, instrument, warn};
+
+pub const X_WEBHOOK_ID: &str = "x-webhook-id";
+
+pub const X_WEBHOOK_TIMESTAMP: &str = "x-webhook-timestamp";
+
+pub const X_WEBHOOK_SIGNATURE: &str = "x-webhook-signature";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct WebhookDispatcherConfig {
+    /// Seconds between two polls of the outbox.
+    interval_secs: u64,
+
+    /// Maximum number of deliveries per poll.
+    batch_size: u32,
+
+    /// Seconds to wait for an endpoint to respond.
+    timeout_secs: u64,
+
+    /// Attempts to deliver before a delivery is given up, i.e. moved to the dead letters.
+    max_attempts: u32,
+
+    /// Seconds to wait before the first retry; doubled with every further retry.
+    backoff_base_secs: u64,
+
+    /// Maximum seconds to wait before a retry.
+    backoff_max_secs: u64,
+}
+
+/// Periodically delivers the pending webhook deliveries from the outbox by POSTing their payloads
+/// to the endpoints of the subscriptions. Each request is signed via the `X-Webhook-Signature`
+/// header, see [sign_webhook_payload]. Failed deliveries, i.e. without a 2xx response, are retried
+/// with exponential backoff until given up.
+///
+/// Deliveries are at least once: if recording a delivery fails, it is delivered again; receivers
+/// can deduplicate via the `X-Webhook-Id` header. Only one dispatcher must run at a time.
+#[derive(Debug, Clone)]
+pub struct WebhookDispatcher<W, C> {
+    config: WebhookDispatcherConfig,
+    webhook_repository: W,
+    client: Client,
+    clock: C,
+}
+
+impl<W, C> WebhookDispatcher<W, C>
+where
+    W: WebhookRepository,
+    C: Clock,
+{
+    pub fn new(config: WebhookDispatcherConfig, webhook_repository: W, clock: C) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(StdDuration::from_secs(config.timeout_secs))
+            .build()
+            .context("create HTTP client")?;
+
+        Ok(Self {
+            config,
+            webhook_repository,
+            client,
+            clock,
+        })
+    }
+
+    /// Run forever; failures are logged and retried with the next poll.
+    pub async fn run(self) -> Result<()> {
+        let interval = Duration::seconds(self.config.interval_secs as i64);
+
+        loop {
+            let now = self.clock.now();
+            if let Err(error) = self.process(now).await {
+                error!(
+                    error = format!("{error:#}"),
+                    "cannot process webhook deliveries"
+                );
+            }
+            self.clock.sleep_until(now + interval).await;
+        }
+    }
+
+    /// Deliver all pending deliveries due at or before the given time, batch by batch. Failures
+    /// for single deliveries are logged and do not stop delivering the others of the batch; as
+    /// such deliveries are still pending, no further batch is processed until the next poll.
+    #[instrument(skip(self))]
+    pub async fn process(&self, at: OffsetDateTime) -> Result<()> {
+        loop {
+            let deliveries = self
+                .webhook_repository
+                .due_webhook_deliveries(at, self.config.batch_size)
+                .await
+                .context("get due webhook deliveries")?;
+            let mut done = deliveries.len() < self.config.batch_size as usize;
+
+            for (delivery, endpoint) in deliveries {
+                let id = delivery.id;
+                if let Err(error) = self.deliver(delivery, endpoint, at).await {
+                    error!(
+                        %id,
+                        error = format!("{error:#}"),
+                        "cannot deliver webhook"
+                    );
+                    done = true;
+                }
+            }
+
+            if done {
+                return Ok(());
+            }
+        }
+    }
+
+    async fn deliver(
+        &self,
+        delivery: WebhookDelivery,
+        endpoint: WebhookEndpoint,
+        at: OffsetDateTime,
+    ) -> Result<()> {
+        let id = delivery.id;
+
+        match self.send(&delivery, &endpoint).await {
+            Ok(()) => {
+                self.webhook_repository
+                    .record_webhook_delivered(id, self.clock.now())
+                    .await
+                    .context("record webhook delivered")?;
+                debug!(%id, url = endpoint.url, "delivered webhook");
+            }
+
+            Err(reason) => {
+                let attempts = delivery.attempts + 1;
+                let retry_at = (attempts < self.config.max_attempts).then(|| {
+                    let base = Duration::seconds(self.config.backoff_base_secs as i64);
+                    let max = Duration::seconds(self.config.backoff_max_secs as i64);
+                    at + webhook_backoff(attempts, base, max)
+                });
+
+                self.webhook_repository
+                    .record_webhook_failure(id, &reason, retry_at)
+                    .await
+                    .context("record webhook failure")?;
+                match retry_at {
+                    Some(retry_at) => warn!(
+                        %id,
+                        url = endpoint.url,
+                        reason,
+                        attempts,
+                        %retry_at,
+                        "webhook delivery failed"
+                    ),
+                    None => error!(
+                        %id,
+                        url = endpoint.url,
+                        reason,
+                        attempts,
+                        "webhook delivery given up"
+                    ),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// POST the signed payload; errors and non-2xx responses are returned as the reason of the
+    /// failure.
+    async fn send(
+        &self,
+        delivery: &WebhookDelivery,
+        endpoint: &WebhookEndpoint,
+    ) -> Result<(), String> {
+        let timestamp = self.clock.now().unix_timestamp();
+        let signature = sign_webhook_payload(&endpoint.secret, timestamp, &delivery.payload);
+
+        let response = self
+            .client
+            .post(&endpoint.url)
+            .header(CONTENT_TYPE, "application/json")
+            .header(X_WEBHOOK_ID, delivery.id.to_string())
+            .header(X_WEBHOOK_TIMESTAMP, timestamp.to_string())
+            .header(X_WEBHOOK_SIGNATURE, signature)
+            .body(delivery.payload.clone())
+            .send()
+            .await
+            .map_err(|error| error.as_chain())?;
+
+        let status = response.status();
+        if status.is_success() {
+            Ok(())
+        } else {
+            Err(format!("endpoint responded with status {status}"))
+        }
+    }
+}